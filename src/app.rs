@@ -2,8 +2,9 @@
 //!
 //! Handles command-line argument parsing and delegates to appropriate command handlers.
 
-use crate::commands;
+use crate::commands::{self, BenchOptions, CompareOptions, RecordOptions};
 use crate::logging;
+use crate::transcription::language;
 use anyhow::anyhow;
 use dirs;
 use std::env;
@@ -23,11 +24,15 @@ fn suppress_alsa_warnings() {
 #[derive(Debug)]
 enum Command {
     /// Record audio and optionally transcribe
-    Record,
+    Record(RecordOptions),
     /// Authenticate with a transcription provider and select model
     Auth,
     /// View transcription history
     History,
+    /// Transcribe an audio file with several models side by side
+    Compare(CompareOptions),
+    /// Measure model accuracy over a corpus of recordings with reference transcripts
+    Bench(BenchOptions),
     /// Manage keywords for transcription
     Keywords,
     /// Edit configuration file
@@ -38,10 +43,18 @@ enum Command {
     Version,
     /// List available audio input devices
     ListDevices,
+    /// List available transcription providers and models
+    ListModels,
     /// Show recent log entries
     Logs,
+    /// Serve the mock provider APIs on the given port
+    MockServer(u16),
+    /// Show transcription usage and cost
+    Usage,
     /// Invalid command provided
     Invalid(String),
+    /// Invalid option or option value, with a description of the problem
+    InvalidOption(String),
 }
 
 const HELP_TEXT: &str = r#"
@@ -52,7 +65,7 @@ A terminal-based speech-to-text recorder with real-time waveform visualization
 and automatic transcription support.
 
 USAGE:
-    ostt [COMMAND]
+    ostt [COMMAND] [OPTIONS]
 
 COMMANDS:
     record              Record audio with real-time volume metering
@@ -63,7 +76,19 @@ COMMANDS:
                         and API key management in one unified flow.
 
     history             View and browse your transcription history
-                        Select a transcription to copy it to clipboard
+                        Select a transcription to copy it to clipboard,
+                        or press c to compare models on its recording
+
+    compare <FILE> [--models <IDS>]
+                        Transcribe an audio file with several models at
+                        once and show the results side by side with
+                        latency, cost and differences
+
+    bench <DIR> [--models <IDS>] [--preset <FORMAT>]... [--json <FILE>]
+                        Transcribe every audio file in DIR that has a .txt
+                        reference transcript next to it, and report word
+                        and character error rates, latency and upload size
+                        per model and encoding preset
 
     keywords            Manage keywords for improved transcription accuracy
                         Add, remove, and view keywords used by AI models
@@ -76,20 +101,53 @@ COMMANDS:
 
     list-devices        List available audio input devices
 
+    list-models         List available transcription providers and models,
+                        including custom providers from the config file
+
     logs                Show recent log entries from the application
 
+    usage               Show transcription usage and estimated cost by
+                        month, day and model, and this month's budget
+
+    mock-server [--port <PORT>]
+                        Serve offline imitations of the OpenAI, Groq,
+                        DeepInfra, Deepgram and AssemblyAI APIs (default
+                        port 8787) for testing without network access
+                        (only in builds with the mock-server feature)
+
     help, -h, --help    Show this help message
 
+RECORD OPTIONS:
+    -l, --language <CODE>
+                        Spoken language for this recording as an ISO 639-1
+                        code (e.g. de), or "auto" to detect it. Overrides
+                        the language in the config file.
+
+    -t, --translate     Translate the speech to English instead of
+                        transcribing it (toggle with 't' while recording)
+
 EXAMPLES:
     # Record audio
     $ ostt record
     
+    # Record German speech
+    $ ostt record --language de
+    
+    # Record and translate to English
+    $ ostt record --translate
+    
     # Set up authentication and select a model
     $ ostt auth
     
     # View your transcription history
     $ ostt history
     
+    # Compare three models on a recording
+    $ ostt compare meeting.mp3 --models gpt-4o-transcribe,nova-3,groq-whisper-large-v3-turbo
+    
+    # Benchmark two models with two encodings and save the results
+    $ ostt bench ~/corpus --models whisper,nova-3 -p wav -p original --json bench.json
+
     # Edit configuration file
     $ ostt config
 
@@ -104,28 +162,191 @@ impl Command {
     /// Parse command from command-line arguments.
     ///
     /// Returns the appropriate command based on the first argument.
-    /// If no arguments or "record" is provided, returns Record command.
+    /// If no arguments, only record options, or "record" is provided, returns Record command.
     /// If an unrecognized command is provided, returns Invalid.
     fn from_args() -> Self {
         let args: Vec<String> = env::args().collect();
 
         if args.len() > 1 {
             match args[1].as_str() {
-                "record" => Command::Record,
+                "record" => Command::parse_record(&args[2..]),
+                "-l" | "--language" | "-t" | "--translate" => Command::parse_record(&args[1..]),
+                option if option.starts_with("--language=") => Command::parse_record(&args[1..]),
                 "auth" => Command::Auth,
                 "history" => Command::History,
+                "compare" => Command::parse_compare(&args[2..]),
+                "bench" => Command::parse_bench(&args[2..]),
                 "keywords" => Command::Keywords,
                 "config" => Command::Config,
                 "help" | "-h" | "--help" => Command::Help,
                 "version" | "-V" | "--version" => Command::Version,
                 "list-devices" => Command::ListDevices,
+                "list-models" => Command::ListModels,
                 "logs" => Command::Logs,
+                "usage" => Command::Usage,
+                "mock-server" => Command::parse_mock_server(&args[2..]),
                 invalid => Command::Invalid(invalid.to_string()),
             }
         } else {
-            Command::Record
+            Command::Record(RecordOptions::default())
+        }
+    }
+
+    /// Parses the options of the record command.
+    ///
+    /// Returns InvalidOption for unknown options, missing values and invalid languages.
+    fn parse_record(args: &[String]) -> Self {
+        let mut options = RecordOptions::default();
+        let mut args = args.iter();
+
+        while let Some(arg) = args.next() {
+            if matches!(arg.as_str(), "-t" | "--translate") {
+                options.translate = true;
+                continue;
+            }
+
+            let value = match arg.as_str() {
+                "-l" | "--language" => match args.next() {
+                    Some(value) => value.as_str(),
+                    None => return Command::InvalidOption(format!("{arg} requires a value")),
+                },
+                other => match other.strip_prefix("--language=") {
+                    Some(value) => value,
+                    None => return Command::InvalidOption(format!("unknown option '{other}'")),
+                },
+            };
+            match language::parse(value) {
+                // "auto" overrides a configured language, so keep it rather than None
+                Ok(_) => options.language = Some(value.to_string()),
+                Err(e) => return Command::InvalidOption(e.to_string()),
+            }
+        }
+
+        Command::Record(options)
+    }
+
+    /// Parses the file and options of the compare command.
+    ///
+    /// Returns InvalidOption for unknown options, missing values and a missing file.
+    fn parse_compare(args: &[String]) -> Self {
+        let mut options = CompareOptions::default();
+        let mut file = None;
+        let mut args = args.iter();
+
+        while let Some(arg) = args.next() {
+            let value = match arg.as_str() {
+                "-m" | "--models" => match args.next() {
+                    Some(value) => value.as_str(),
+                    None => return Command::InvalidOption(format!("{arg} requires a value")),
+                },
+                other => match other.strip_prefix("--models=") {
+                    Some(value) => value,
+                    None if other.starts_with('-') => {
+                        return Command::InvalidOption(format!("unknown option '{other}'"))
+                    }
+                    None => {
+                        if file.replace(other.into()).is_some() {
+                            return Command::InvalidOption(format!(
+                                "unexpected argument '{other}'"
+                            ));
+                        }
+                        continue;
+                    }
+                },
+            };
+            options.models.extend(
+                value
+                    .split(',')
+                    .map(str::trim)
+                    .filter(|model| !model.is_empty())
+                    .map(str::to_string),
+            );
+        }
+
+        match file {
+            Some(file) => {
+                options.file = file;
+                Command::Compare(options)
+            }
+            None => Command::InvalidOption("compare requires an audio file".to_string()),
         }
     }
+
+    /// Parses the directory and options of the bench command.
+    ///
+    /// Returns InvalidOption for unknown options, missing values and a missing directory.
+    fn parse_bench(args: &[String]) -> Self {
+        let mut options = BenchOptions::default();
+        let mut directory = None;
+        let mut args = args.iter();
+
+        while let Some(arg) = args.next() {
+            let (option, value) = match arg.split_once('=') {
+                Some((option, value)) if option.starts_with("--") => (option, Some(value)),
+                _ => (arg.as_str(), None),
+            };
+            if !matches!(option, "-m" | "--models" | "-p" | "--preset" | "--json") {
+                if option.starts_with('-') {
+                    return Command::InvalidOption(format!("unknown option '{option}'"));
+                }
+                if directory.replace(arg.into()).is_some() {
+                    return Command::InvalidOption(format!("unexpected argument '{arg}'"));
+                }
+                continue;
+            }
+
+            let value = match value.or_else(|| args.next().map(String::as_str)) {
+                Some(value) => value,
+                None => return Command::InvalidOption(format!("{option} requires a value")),
+            };
+            match option {
+                "-m" | "--models" => options.models.extend(
+                    value
+                        .split(',')
+                        .map(str::trim)
+                        .filter(|model| !model.is_empty())
+                        .map(str::to_string),
+                ),
+                "-p" | "--preset" => options.presets.push(value.to_string()),
+                _ => options.json = Some(value.into()),
+            }
+        }
+
+        match directory {
+            Some(directory) => {
+                options.directory = directory;
+                Command::Bench(options)
+            }
+            None => Command::InvalidOption("bench requires a corpus directory".to_string()),
+        }
+    }
+
+    /// Parses the options of the mock-server command.
+    ///
+    /// Returns InvalidOption for unknown options and missing or invalid ports.
+    fn parse_mock_server(args: &[String]) -> Self {
+        let mut port = commands::mock_server::DEFAULT_PORT;
+        let mut args = args.iter();
+
+        while let Some(arg) = args.next() {
+            let value = match arg.as_str() {
+                "-p" | "--port" => match args.next() {
+                    Some(value) => value.as_str(),
+                    None => return Command::InvalidOption(format!("{arg} requires a value")),
+                },
+                other => match other.strip_prefix("--port=") {
+                    Some(value) => value,
+                    None => return Command::InvalidOption(format!("unknown option '{other}'")),
+                },
+            };
+            match value.parse() {
+                Ok(value) => port = value,
+                Err(_) => return Command::InvalidOption(format!("invalid port '{value}'")),
+            }
+        }
+
+        Command::MockServer(port)
+    }
 }
 
 /// Runs the main application based on command-line arguments.
@@ -162,6 +383,16 @@ pub async fn run() -> Result<(), anyhow::Error> {
         };
     }
 
+    if matches!(command, Command::ListModels) {
+        return match commands::handle_list_models() {
+            Ok(()) => Ok(()),
+            Err(e) => {
+                eprintln!("Error: {e}");
+                process::exit(1);
+            }
+        };
+    }
+
     if matches!(command, Command::Logs) {
         return match commands::handle_logs() {
             Ok(()) => Ok(()),
@@ -172,12 +403,38 @@ pub async fn run() -> Result<(), anyhow::Error> {
         };
     }
 
+    if matches!(command, Command::Usage) {
+        return match commands::handle_usage() {
+            Ok(()) => Ok(()),
+            Err(e) => {
+                eprintln!("Error: {e}");
+                process::exit(1);
+            }
+        };
+    }
+
+    if let Command::MockServer(port) = command {
+        return match commands::handle_mock_server(port).await {
+            Ok(()) => Ok(()),
+            Err(e) => {
+                eprintln!("Error: {e}");
+                process::exit(1);
+            }
+        };
+    }
+
     if let Command::Invalid(cmd) = &command {
         eprintln!("Error: unknown command '{}'", cmd);
         eprintln!("Run 'ostt help' to see available commands.");
         process::exit(2);
     }
 
+    if let Command::InvalidOption(message) = &command {
+        eprintln!("Error: {}", message);
+        eprintln!("Run 'ostt help' to see available options.");
+        process::exit(2);
+    }
+
     logging::init_logging()?;
 
     let config_path = dirs::home_dir()
@@ -207,15 +464,21 @@ pub async fn run() -> Result<(), anyhow::Error> {
                 }
             }
         }
-        Command::Record => commands::handle_record().await?,
+        Command::Record(options) => commands::handle_record(options).await?,
         Command::History => commands::handle_history().await?,
+        Command::Compare(options) => commands::handle_compare(options).await?,
+        Command::Bench(options) => commands::handle_bench(options).await?,
         Command::Keywords => commands::handle_keywords().await?,
         Command::Config => commands::handle_config()?,
         Command::Help => unreachable!(),
         Command::Version => unreachable!(),
         Command::ListDevices => unreachable!(),
+        Command::ListModels => unreachable!(),
         Command::Logs => unreachable!(),
+        Command::MockServer(_) => unreachable!(),
+        Command::Usage => unreachable!(),
         Command::Invalid(_) => unreachable!(),
+        Command::InvalidOption(_) => unreachable!(),
     }
 
     Ok(())
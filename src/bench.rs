@@ -0,0 +1,294 @@
+//! Accuracy benchmark over a reference corpus.
+//!
+//! A corpus is a directory of audio files, each with a reference transcript next to it
+//! (`interview.wav` + `interview.txt`). Every sample is encoded with each preset (an
+//! `output_format` setting, or `original` to upload the file unchanged) and transcribed
+//! with each model. Results are scored by word and character error rate against the
+//! reference, and reported per model and preset with latency and upload size.
+
+use anyhow::{anyhow, Result};
+use serde::Serialize;
+use std::fmt::Write;
+use std::path::{Path, PathBuf};
+
+use crate::compare::diff::ErrorCounts;
+use crate::recording::encode::read_wav;
+use crate::recording::{encode, AudioFormat};
+
+/// Preset name that uploads the corpus file as it is
+pub const ORIGINAL_PRESET: &str = "original";
+
+/// An audio file of the corpus with its reference transcript.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Sample {
+    /// Audio file
+    pub audio: PathBuf,
+    /// What was actually said
+    pub reference: String,
+}
+
+impl Sample {
+    /// File name of the audio, for reports
+    pub fn name(&self) -> String {
+        self.audio
+            .file_name()
+            .map(|name| name.to_string_lossy().to_string())
+            .unwrap_or_default()
+    }
+}
+
+/// Outcome of transcribing one sample with one model and preset.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct SampleResult {
+    /// ID of the model
+    pub model: String,
+    /// Encoding preset
+    pub preset: String,
+    /// File name of the sample
+    pub file: String,
+    /// Size of the uploaded audio
+    pub upload_bytes: u64,
+    /// How long the transcription took (`None` if it failed)
+    pub latency_ms: Option<u64>,
+    /// Word error rate (`None` if the transcription failed)
+    pub wer: Option<f64>,
+    /// Character error rate (`None` if the transcription failed)
+    pub cer: Option<f64>,
+    /// The transcript
+    pub transcript: Option<String>,
+    /// Why the transcription failed
+    pub error: Option<String>,
+    /// Error counts behind `wer` and `cer`, for corpus totals
+    #[serde(skip)]
+    pub counts: Option<ErrorCounts>,
+}
+
+/// Results of one model and preset over the whole corpus.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct BenchSummary {
+    /// ID of the model
+    pub model: String,
+    /// Encoding preset
+    pub preset: String,
+    /// Number of samples
+    pub samples: usize,
+    /// Number of samples that couldn't be transcribed
+    pub failures: usize,
+    /// Word error rate over all transcribed samples
+    pub wer: Option<f64>,
+    /// Character error rate over all transcribed samples
+    pub cer: Option<f64>,
+    /// Average latency of the transcribed samples
+    pub avg_latency_ms: Option<u64>,
+    /// Average upload size
+    pub avg_upload_bytes: u64,
+}
+
+/// Benchmark report written with `--json`.
+#[derive(Debug, Clone, Serialize)]
+pub struct BenchReport {
+    /// One entry per model and preset
+    pub summary: Vec<BenchSummary>,
+    /// One entry per model, preset and sample
+    pub samples: Vec<SampleResult>,
+}
+
+/// Finds the samples of a corpus directory.
+///
+/// Audio files without a reference transcript are skipped with a warning. Samples are
+/// returned sorted by file name.
+///
+/// # Errors
+/// - If the directory cannot be read
+/// - If a reference transcript cannot be read
+pub fn load_corpus(directory: &Path) -> Result<Vec<Sample>> {
+    let mut audio_files: Vec<PathBuf> = std::fs::read_dir(directory)
+        .map_err(|e| anyhow!("Failed to read corpus directory {}: {e}", directory.display()))?
+        .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+        .filter(|path| path.is_file() && AudioFormat::from_path(path).is_some())
+        .collect();
+    audio_files.sort();
+
+    let mut samples = Vec::with_capacity(audio_files.len());
+    for audio in audio_files {
+        let reference_path = audio.with_extension("txt");
+        if !reference_path.is_file() {
+            tracing::warn!("Skipping {}: no reference transcript", audio.display());
+            continue;
+        }
+        let reference = std::fs::read_to_string(&reference_path).map_err(|e| {
+            anyhow!("Failed to read reference transcript {}: {e}", reference_path.display())
+        })?;
+        samples.push(Sample {
+            audio,
+            reference: reference.trim().to_string(),
+        });
+    }
+    Ok(samples)
+}
+
+/// Returns the format a preset produces for a sample.
+///
+/// # Errors
+/// - If the preset names an unsupported codec
+pub fn preset_format(preset: &str, sample: &Sample) -> Result<AudioFormat> {
+    if preset == ORIGINAL_PRESET {
+        AudioFormat::from_path(&sample.audio)
+            .ok_or_else(|| anyhow!("Unknown audio format: {}", sample.audio.display()))
+    } else {
+        AudioFormat::from_output_format(preset)
+    }
+}
+
+/// Encodes a sample with a preset into `output_path`.
+///
+/// WAV samples are re-encoded from their PCM samples, so the built-in presets (`wav`,
+/// `flac` and, with the `opus` feature, `opus`) work without ffmpeg; other files are
+/// transcoded with ffmpeg.
+///
+/// # Errors
+/// - If the sample cannot be read
+/// - If encoding fails
+pub fn encode_sample(sample: &Sample, preset: &str, output_path: &Path) -> Result<()> {
+    if preset == ORIGINAL_PRESET {
+        std::fs::copy(&sample.audio, output_path)?;
+        return Ok(());
+    }
+    match read_wav(&sample.audio) {
+        Some((samples, sample_rate)) => encode(&samples, sample_rate, output_path, preset),
+        None => crate::recording::encode::transcode(&sample.audio, output_path, preset),
+    }
+}
+
+/// Returns the length of a sample in seconds, if it can be measured.
+///
+/// WAV files are measured from their header; other formats are decoded with ffmpeg.
+pub fn duration_secs(sample: &Sample) -> Option<f64> {
+    if let Ok(reader) = hound::WavReader::open(&sample.audio) {
+        return Some(reader.duration() as f64 / reader.spec().sample_rate as f64);
+    }
+    let (samples, sample_rate) = crate::recording::encode::read_audio(&sample.audio)
+        .map_err(|e| tracing::warn!("Failed to measure {}: {}", sample.name(), e))
+        .ok()?;
+    Some(samples.len() as f64 / sample_rate as f64)
+}
+
+/// Totals the results of each model and preset, in the order they first appear.
+pub fn summarize(results: &[SampleResult]) -> Vec<BenchSummary> {
+    let mut groups: Vec<(&str, &str, Vec<&SampleResult>)> = Vec::new();
+    for result in results {
+        match groups
+            .iter_mut()
+            .find(|(model, preset, _)| *model == result.model && *preset == result.preset)
+        {
+            Some((_, _, group)) => group.push(result),
+            None => groups.push((&result.model, &result.preset, vec![result])),
+        }
+    }
+
+    groups
+        .into_iter()
+        .map(|(model, preset, group)| {
+            let mut counts = ErrorCounts::default();
+            let mut latencies = Vec::new();
+            for result in &group {
+                if let Some(sample_counts) = result.counts {
+                    counts.add(sample_counts);
+                }
+                latencies.extend(result.latency_ms);
+            }
+            let transcribed = !latencies.is_empty();
+            BenchSummary {
+                model: model.to_string(),
+                preset: preset.to_string(),
+                samples: group.len(),
+                failures: group.iter().filter(|result| result.error.is_some()).count(),
+                wer: transcribed.then(|| counts.word_error_rate()),
+                cer: transcribed.then(|| counts.character_error_rate()),
+                avg_latency_ms: transcribed
+                    .then(|| latencies.iter().sum::<u64>() / latencies.len() as u64),
+                avg_upload_bytes: group.iter().map(|result| result.upload_bytes).sum::<u64>()
+                    / group.len() as u64,
+            }
+        })
+        .collect()
+}
+
+/// Formats the summaries as a text table.
+pub fn format_table(summaries: &[BenchSummary]) -> String {
+    let model_width = summaries
+        .iter()
+        .map(|s| s.model.len())
+        .chain([5])
+        .max()
+        .unwrap_or_default();
+    let preset_width = summaries
+        .iter()
+        .map(|s| s.preset.len())
+        .chain([6])
+        .max()
+        .unwrap_or_default();
+
+    let mut table = String::new();
+    let _ = writeln!(
+        table,
+        "  {:<model_width$}  {:<preset_width$}  {:>7}  {:>6}  {:>7}  {:>7}  {:>8}  {:>9}",
+        "Model", "Preset", "Samples", "Failed", "WER", "CER", "Latency", "Upload"
+    );
+    for summary in summaries {
+        let percent = |rate: Option<f64>| rate.map(|r| format!("{:.1}%", r * 100.0));
+        let _ = writeln!(
+            table,
+            "  {:<model_width$}  {:<preset_width$}  {:>7}  {:>6}  {:>7}  {:>7}  {:>8}  {:>9}",
+            summary.model,
+            summary.preset,
+            summary.samples,
+            summary.failures,
+            percent(summary.wer).unwrap_or_else(|| "-".to_string()),
+            percent(summary.cer).unwrap_or_else(|| "-".to_string()),
+            summary
+                .avg_latency_ms
+                .map(|ms| format!("{:.1}s", ms as f64 / 1000.0))
+                .unwrap_or_else(|| "-".to_string()),
+            format!("{:.1} KB", summary.avg_upload_bytes as f64 / 1024.0),
+        );
+    }
+    table
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn result(model: &str, reference: &str, transcript: Option<&str>) -> SampleResult {
+        let counts = transcript.map(|t| ErrorCounts::new(reference, t));
+        SampleResult {
+            model: model.to_string(),
+            preset: "mp3 -ab 16k".to_string(),
+            file: "a.wav".to_string(),
+            upload_bytes: 2048,
+            latency_ms: transcript.map(|_| 1000),
+            wer: counts.map(|c| c.word_error_rate()),
+            cer: counts.map(|c| c.character_error_rate()),
+            transcript: transcript.map(str::to_string),
+            error: transcript.is_none().then(|| "failed".to_string()),
+            counts,
+        }
+    }
+
+    #[test]
+    fn summarizes_corpus_error_rates() {
+        let results = vec![
+            result("nova-3", "one two three four", Some("one two three for")),
+            result("whisper", "one two three four", None),
+            result("nova-3", "five six", Some("five six")),
+        ];
+        let summaries = summarize(&results);
+        assert_eq!(summaries.len(), 2);
+        assert_eq!(summaries[0].model, "nova-3");
+        assert_eq!(summaries[0].wer, Some(1.0 / 6.0));
+        assert_eq!(summaries[0].avg_latency_ms, Some(1000));
+        assert_eq!((summaries[1].failures, summaries[1].wer), (1, None));
+        assert!(format_table(&summaries).contains("16.7%"));
+    }
+}
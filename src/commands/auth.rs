@@ -2,12 +2,16 @@
 //!
 //! Unified authentication flow: select a provider/model combination and optionally enter an API key.
 //! Users can keep existing API keys by pressing Enter without entering anything.
+//! Models discovered from the model lists of providers with a saved API key are offered
+//! next to the built-in ones.
 
+use super::record::data_dir;
 use crate::config;
 use crate::transcription;
+use crate::transcription::discovery;
 use cliclack::note;
 use cliclack::outro;
-use cliclack::{intro, password, select};
+use cliclack::{intro, password, select, spinner};
 use console::style;
 
 /// Handles provider + model selection and API key management.
@@ -15,6 +19,7 @@ use console::style;
 /// Shows all available provider/model combinations for the user to choose from.
 /// If a provider already has an API key saved, the user can press Enter to keep it.
 /// Supports switching between models of the same provider without re-entering the API key.
+/// Model lists that are missing or older than a day are fetched before the selection.
 pub async fn handle_auth() -> Result<(), anyhow::Error> {
     tracing::info!("=== ostt Authentication ===");
 
@@ -24,13 +29,24 @@ pub async fn handle_auth() -> Result<(), anyhow::Error> {
 
     intro(style(" auth ").on_white().black())?;
 
-    // Get all available provider/model combinations
-    let providers = transcription::TranscriptionProvider::all();
+    // Get all available provider/model combinations, including custom providers from ostt.toml
+    let (providers_config, network_config) = config::OsttConfig::load()
+        .map(|c| (c.providers, c.network))
+        .unwrap_or_default();
+    let data_dir = data_dir()?;
+
+    let discovering = spinner();
+    discovering.start("Checking providers for new models...");
+    let mut registry = transcription::ProviderRegistry::from_config(&providers_config);
+    let cache = discovery::refresh(&registry, &providers_config, &network_config, &data_dir).await;
+    registry.add_discovered(cache.models(&registry));
+    discovering.stop("Model lists checked");
+
     let mut provider_model_options: Vec<(
-        transcription::TranscriptionProvider,
+        std::sync::Arc<dyn transcription::Provider>,
         transcription::TranscriptionModel,
     )> = Vec::new();
-    let mut display_options: Vec<String> = Vec::new();
+    let mut display_options: Vec<(String, String)> = Vec::new();
 
     // Get the currently selected model from secrets (not from config file)
     let maybe_current_model_id = config::get_selected_model().ok().flatten();
@@ -40,10 +56,13 @@ pub async fn handle_auth() -> Result<(), anyhow::Error> {
     }
 
     // Build list of all provider/model combinations
-    for provider in providers.iter() {
-        let models = transcription::TranscriptionModel::models_for_provider(provider);
-        for model in models {
-            display_options.push(format!("{} / {}", provider.name(), model.description()));
+    for provider in registry.providers() {
+        for model in registry.provider_models(provider.as_ref()) {
+            // The hint describes what the model supports and costs
+            display_options.push((
+                format!("{} / {}", provider.name(), model.description()),
+                model.capabilities().summary(),
+            ));
             provider_model_options.push((provider.clone(), model));
         }
     }
@@ -53,8 +72,8 @@ pub async fn handle_auth() -> Result<(), anyhow::Error> {
     }
 
     let mut select_prompt = select("Select provider and model:");
-    for (i, option) in display_options.iter().enumerate() {
-        select_prompt = select_prompt.item(i, option, "");
+    for (i, (option, hint)) in display_options.iter().enumerate() {
+        select_prompt = select_prompt.item(i, option, hint);
     }
     let selected_idx: usize = select_prompt
         .interact()
@@ -62,6 +81,18 @@ pub async fn handle_auth() -> Result<(), anyhow::Error> {
 
     let (selected_provider, selected_model) = &provider_model_options[selected_idx];
 
+    // Providers without authentication only need the model selection
+    if !selected_provider.capabilities().requires_api_key {
+        config::save_selected_model(selected_provider.id(), selected_model.id())?;
+        outro("✅ Configuration saved.")?;
+        tracing::info!(
+            "Authentication completed: provider={}, model={} (no API key required)",
+            selected_provider.id(),
+            selected_model.id()
+        );
+        return Ok(());
+    }
+
     // Check if we already have an API key for this provider
     let current_api_key = config::get_api_key(selected_provider.id()).ok().flatten();
 
@@ -82,6 +113,9 @@ pub async fn handle_auth() -> Result<(), anyhow::Error> {
             .map_err(|e| anyhow::anyhow!("API key input cancelled: {e}"))?
     };
 
+    let key_changed =
+        !api_key.is_empty() && current_api_key.as_deref() != Some(api_key.as_str());
+
     // If empty input and we have a current key, keep the current one
     let api_key_to_save = if api_key.is_empty() {
         if let Some(key) = current_api_key {
@@ -101,6 +135,32 @@ pub async fn handle_auth() -> Result<(), anyhow::Error> {
     // Note: save_selected_model ignores provider_id and stores only the model_id
     // since only one model selection is active globally
 
+    // A new key may list other models; offer the provider's models next time
+    if key_changed {
+        let cache = discovery::refresh_provider(
+            selected_provider.as_ref(),
+            &providers_config,
+            &network_config,
+            &data_dir,
+        )
+        .await;
+        let mut updated = transcription::ProviderRegistry::from_config(&providers_config);
+        updated.add_discovered(cache.models(&updated));
+        let new_models = updated
+            .provider_models(selected_provider.as_ref())
+            .len()
+            .saturating_sub(registry.provider_models(selected_provider.as_ref()).len());
+        if new_models > 0 {
+            note(
+                "new models",
+                format!(
+                    "Found {new_models} more {} model(s); run 'ostt auth' again to select one",
+                    selected_provider.name()
+                ),
+            )?;
+        }
+    }
+
     outro("✅ Configuration saved.")?;
 
     tracing::info!(
@@ -0,0 +1,272 @@
+//! Benchmark transcription accuracy over a reference corpus.
+//!
+//! Runs every sample of a corpus through the selected models and encoding presets and
+//! reports word and character error rates, latency and upload size per combination.
+
+use std::path::PathBuf;
+use std::time::Instant;
+
+use futures_util::future::join_all;
+
+use super::compare::{comparison_models, model_config};
+use super::record::{budget_overrun, data_dir, load_keywords};
+use crate::bench::{self, BenchReport, Sample, SampleResult, ORIGINAL_PRESET};
+use crate::compare::diff::ErrorCounts;
+use crate::config;
+use crate::config::file::BudgetAction;
+use crate::history::{usage, HistoryManager, UsageRecord};
+use crate::recording::AudioFormat;
+use crate::transcription::{self, discovery, language, TranscriptionConfig};
+
+/// Options of `ostt bench`.
+#[derive(Debug, Clone, Default)]
+pub struct BenchOptions {
+    /// Directory of audio files with reference transcripts
+    pub directory: PathBuf,
+    /// Model IDs to benchmark instead of the configured ones
+    pub models: Vec<String>,
+    /// Encoding presets (`output_format` values or `original`) instead of the configured one
+    pub presets: Vec<String>,
+    /// File to write the full results to as JSON
+    pub json: Option<PathBuf>,
+}
+
+/// Transcribes a corpus with every model and preset and prints the error rates.
+///
+/// The models are chosen like those of `ostt compare`. The presets default to the
+/// configured `output_format`. Samples are transcribed with all models concurrently, one
+/// sample at a time. The cost of the whole run is checked against the monthly budget
+/// before it starts, and each transcription's usage is recorded.
+///
+/// # Errors
+/// - If the configuration cannot be loaded
+/// - If the corpus directory cannot be read or has no samples
+/// - If no models are configured or a model cannot be used
+/// - If a preset names an unsupported codec
+/// - If the run would exceed the monthly budget and `budget_action` is "block"
+/// - If the JSON report cannot be written
+pub async fn handle_bench(options: BenchOptions) -> Result<(), anyhow::Error> {
+    tracing::info!("=== ostt Benchmark ===");
+
+    let config_data = config::OsttConfig::load()
+        .map_err(|e| anyhow::anyhow!("Failed to load configuration: {e}"))?;
+
+    let samples = bench::load_corpus(&options.directory)?;
+    if samples.is_empty() {
+        return Err(anyhow::anyhow!(
+            "No samples in {}: add audio files with a .txt reference transcript next to each",
+            options.directory.display()
+        ));
+    }
+
+    let presets = if options.presets.is_empty() {
+        vec![config_data.audio.output_format.clone()]
+    } else {
+        options.presets.clone()
+    };
+    for preset in presets.iter().filter(|preset| *preset != ORIGINAL_PRESET) {
+        AudioFormat::from_output_format(preset)?;
+    }
+
+    let model_ids = comparison_models(&options.models, &config_data);
+    if model_ids.is_empty() {
+        return Err(anyhow::anyhow!(
+            "No models to benchmark: pass --models or run 'ostt auth' to select one"
+        ));
+    }
+    let registry = discovery::registry(&config_data.providers, &data_dir()?);
+    let language = language::parse(&config_data.transcription.language)?;
+    let keywords = load_keywords()?;
+    let configs = model_ids
+        .iter()
+        .map(|model_id| {
+            model_config(&registry, &config_data, model_id, &keywords)
+                .map(|config| config.with_language(language.clone()))
+        })
+        .collect::<anyhow::Result<Vec<_>>>()?;
+
+    let durations: Vec<Option<f64>> = samples.iter().map(bench::duration_secs).collect();
+    let estimate = presets.len() as f64
+        * configs
+            .iter()
+            .flat_map(|config| {
+                durations
+                    .iter()
+                    .flatten()
+                    .filter_map(|&secs| usage::cost(&config_data.usage, &config.model, secs))
+            })
+            .sum::<f64>();
+    if let Some(message) = budget_overrun(&config_data.usage, estimate) {
+        if config_data.usage.budget_action == BudgetAction::Block {
+            tracing::error!("Not starting benchmark: {}", message);
+            return Err(anyhow::anyhow!(
+                "{message}. Raise monthly_budget or set budget_action = \"warn\" in [usage] to benchmark anyway."
+            ));
+        }
+        tracing::warn!("{}", message);
+        eprintln!("Warning: {message}");
+    }
+
+    eprintln!(
+        "Benchmarking {} model(s) × {} preset(s) on {} sample(s)",
+        configs.len(),
+        presets.len(),
+        samples.len()
+    );
+
+    let mut results = Vec::new();
+    for preset in &presets {
+        for (sample, &audio_secs) in samples.iter().zip(&durations) {
+            let sample_results =
+                bench_sample(&config_data, &configs, sample, audio_secs, preset).await;
+            for result in &sample_results {
+                match (&result.error, result.wer) {
+                    (Some(error), _) => eprintln!(
+                        "  {} [{}] {}: failed: {}",
+                        result.model,
+                        result.preset,
+                        result.file,
+                        error.lines().next().unwrap_or_default()
+                    ),
+                    (None, Some(wer)) => eprintln!(
+                        "  {} [{}] {}: WER {:.1}%",
+                        result.model,
+                        result.preset,
+                        result.file,
+                        wer * 100.0
+                    ),
+                    (None, None) => {}
+                }
+            }
+            results.extend(sample_results);
+        }
+    }
+
+    let summary = bench::summarize(&results);
+    println!();
+    print!("{}", bench::format_table(&summary));
+
+    if let Some(path) = &options.json {
+        let report = BenchReport {
+            summary,
+            samples: results,
+        };
+        std::fs::write(path, serde_json::to_string_pretty(&report)?)
+            .map_err(|e| anyhow::anyhow!("Failed to write {}: {e}", path.display()))?;
+        println!();
+        println!("Results written to {}", path.display());
+    }
+
+    tracing::info!("Benchmark finished");
+    Ok(())
+}
+
+/// Encodes one sample with a preset and transcribes it with every model concurrently.
+///
+/// Failures are reported in the results rather than returned, so that one broken sample
+/// or model doesn't end the benchmark.
+async fn bench_sample(
+    config_data: &config::OsttConfig,
+    configs: &[TranscriptionConfig],
+    sample: &Sample,
+    audio_secs: Option<f64>,
+    preset: &str,
+) -> Vec<SampleResult> {
+    let failed = |config: &TranscriptionConfig, upload_bytes: u64, error: String| SampleResult {
+        model: config.model.id().to_string(),
+        preset: preset.to_string(),
+        file: sample.name(),
+        upload_bytes,
+        latency_ms: None,
+        wer: None,
+        cer: None,
+        transcript: None,
+        error: Some(error),
+        counts: None,
+    };
+
+    let format = match bench::preset_format(preset, sample) {
+        Ok(format) => format,
+        Err(e) => return configs.iter().map(|c| failed(c, 0, e.to_string())).collect(),
+    };
+    let upload_path = std::env::temp_dir().join(format!(
+        "ostt-bench-{}.{}",
+        std::process::id(),
+        format.extension()
+    ));
+    if let Err(e) = bench::encode_sample(sample, preset, &upload_path) {
+        let error = format!("Encoding failed: {e}");
+        return configs.iter().map(|c| failed(c, 0, error.clone())).collect();
+    }
+    let upload_bytes = std::fs::metadata(&upload_path).map(|m| m.len()).unwrap_or_default();
+
+    let transcriptions = configs.iter().map(|config| {
+        let upload_path = &upload_path;
+        async move {
+            // Transcoding to another format would benchmark a different preset
+            if !config.model.accepts_format(format) {
+                let error = format!("Model doesn't accept {} audio", format.extension());
+                return failed(config, upload_bytes, error);
+            }
+
+            let started = Instant::now();
+            let result = transcription::transcribe(config, upload_path).await;
+            let latency = started.elapsed();
+            record(config_data, config, &result, audio_secs, upload_bytes, latency);
+
+            match result {
+                Ok(response) => {
+                    let counts = ErrorCounts::new(&sample.reference, &response.text);
+                    SampleResult {
+                        model: config.model.id().to_string(),
+                        preset: preset.to_string(),
+                        file: sample.name(),
+                        upload_bytes,
+                        latency_ms: Some(latency.as_millis() as u64),
+                        wer: Some(counts.word_error_rate()),
+                        cer: Some(counts.character_error_rate()),
+                        transcript: Some(response.text),
+                        error: None,
+                        counts: Some(counts),
+                    }
+                }
+                Err(e) => failed(config, upload_bytes, e.to_string()),
+            }
+        }
+    });
+    let results = join_all(transcriptions).await;
+
+    if let Err(e) = std::fs::remove_file(&upload_path) {
+        tracing::debug!("Failed to remove benchmark upload file: {}", e);
+    }
+    results
+}
+
+/// Records a benchmark transcription's attempt and, if it succeeded, its usage.
+fn record(
+    config_data: &config::OsttConfig,
+    config: &TranscriptionConfig,
+    result: &anyhow::Result<transcription::TranscriptionResponse>,
+    audio_secs: Option<f64>,
+    bytes: u64,
+    latency: std::time::Duration,
+) {
+    let mut history_manager = match data_dir().and_then(|dir| HistoryManager::new(&dir)) {
+        Ok(history_manager) => history_manager,
+        Err(e) => {
+            tracing::warn!("Failed to open history: {}", e);
+            return;
+        }
+    };
+    let model = &config.model;
+    if let Err(e) = history_manager.record_attempt(model.id(), result.is_ok(), latency) {
+        tracing::warn!("Failed to record transcription attempt: {}", e);
+    }
+    if let Ok(response) = result {
+        let audio_secs = audio_secs.or(response.duration);
+        let record = UsageRecord::now(&config_data.usage, model, audio_secs, bytes, latency);
+        if let Err(e) = history_manager.record_usage(&record) {
+            tracing::warn!("Failed to record usage: {}", e);
+        }
+    }
+}
@@ -0,0 +1,298 @@
+//! Compare transcription models on one recording.
+//!
+//! Sends the same audio file to several models at once and shows their transcripts side by
+//! side with latency, estimated cost and a word-level diff.
+
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::Instant;
+
+use super::record::{budget_overrun, data_dir, load_keywords, split_recording, Recording};
+use crate::clipboard::copy_to_clipboard;
+use crate::compare::{CompareViewer, Comparison, ComparisonResult};
+use crate::config;
+use crate::config::file::BudgetAction;
+use crate::history::{usage, HistoryManager, UsageRecord};
+use crate::recording::{encode, AudioFormat};
+use crate::transcription::{
+    chunking, discovery, language, AudioChunk, Cleanup, ProviderRegistry, TranscriptionConfig,
+};
+
+/// Options of `ostt compare`.
+#[derive(Debug, Clone, Default)]
+pub struct CompareOptions {
+    /// Audio file to transcribe
+    pub file: PathBuf,
+    /// Model IDs to compare instead of the configured ones
+    pub models: Vec<String>,
+}
+
+/// Transcribes an audio file with several models concurrently and shows the results.
+///
+/// The models are those given with `--models`, else `[transcription] compare_models`, else
+/// the selected model followed by the fallback models. Models that can't be used (unknown,
+/// no API key) are shown with the reason. Files above a model's upload or duration limit
+/// are split into chunks as for recordings, and the combined cost is checked against the
+/// monthly budget before anything is uploaded. Each transcription's usage is recorded, and
+/// the transcript chosen in the viewer is copied to the clipboard.
+///
+/// # Errors
+/// - If the configuration cannot be loaded
+/// - If the audio file doesn't exist
+/// - If fewer than two models are configured for the comparison
+/// - If the comparison would exceed the monthly budget and `budget_action` is "block"
+/// - If the terminal UI fails
+pub async fn handle_compare(options: CompareOptions) -> Result<(), anyhow::Error> {
+    tracing::info!("=== ostt Model Comparison ===");
+
+    let config_data = config::OsttConfig::load()
+        .map_err(|e| anyhow::anyhow!("Failed to load configuration: {e}"))?;
+    if !options.file.is_file() {
+        return Err(anyhow::anyhow!("Audio file not found: {}", options.file.display()));
+    }
+
+    let model_ids = comparison_models(&options.models, &config_data);
+    if model_ids.len() < 2 {
+        return Err(anyhow::anyhow!(
+            "Comparing needs at least two models: pass --models or set compare_models in [transcription]"
+        ));
+    }
+
+    let registry = discovery::registry(&config_data.providers, &data_dir()?);
+    let language = language::parse(&config_data.transcription.language)?;
+    let keywords = load_keywords()?;
+
+    let mut columns = Vec::with_capacity(model_ids.len());
+    let mut configs = Vec::with_capacity(model_ids.len());
+    for (index, model_id) in model_ids.iter().enumerate() {
+        match model_config(&registry, &config_data, model_id, &keywords) {
+            Ok(config) => {
+                configs.push((
+                    index,
+                    config
+                        .with_language(language.clone())
+                        .with_translate(config_data.transcription.translate),
+                ));
+                columns.push(Comparison {
+                    model_id: model_id.clone(),
+                    result: ComparisonResult::Pending,
+                });
+            }
+            Err(e) => columns.push(Comparison {
+                model_id: model_id.clone(),
+                result: ComparisonResult::Failed(e.to_string()),
+            }),
+        }
+    }
+
+    // The samples give the length the cost is estimated from, and let long files be split
+    let audio = encode::read_audio(&options.file)
+        .map_err(|e| tracing::warn!("Failed to decode audio, its cost is unknown: {}", e))
+        .ok();
+    let audio_secs = audio
+        .as_ref()
+        .map(|(samples, sample_rate)| samples.len() as f64 / *sample_rate as f64);
+
+    let estimate = audio_secs.map_or(0.0, |secs| {
+        configs
+            .iter()
+            .filter_map(|(_, config)| usage::cost(&config_data.usage, &config.model, secs))
+            .sum()
+    });
+    let budget_warning = match budget_overrun(&config_data.usage, estimate) {
+        Some(message) if config_data.usage.budget_action == BudgetAction::Block => {
+            tracing::error!("Not uploading file for comparison: {}", message);
+            return Err(anyhow::anyhow!(
+                "{message}. Raise monthly_budget or set budget_action = \"warn\" in [usage] to compare anyway."
+            ));
+        }
+        Some(message) => {
+            tracing::warn!("{}", message);
+            Some(message)
+        }
+        None => None,
+    };
+
+    let chunks = split_file(&options.file, audio.as_ref(), &configs);
+    let chunk_files: Vec<PathBuf> = match chunks.as_slice() {
+        [_] => Vec::new(),
+        chunks => chunks.iter().map(|chunk| chunk.path.clone()).collect(),
+    };
+    let bytes: u64 = chunks
+        .iter()
+        .filter_map(|chunk| std::fs::metadata(&chunk.path).ok())
+        .map(|metadata| metadata.len())
+        .sum();
+    let chunks = Arc::new(chunks);
+    let concurrency = config_data.transcription.chunk_concurrency;
+
+    let (sender, mut receiver) = tokio::sync::mpsc::unbounded_channel();
+    let cleanup = Cleanup::new();
+    let mut tasks = Vec::with_capacity(configs.len());
+    for (index, config) in configs {
+        let config = config.with_cleanup(cleanup.clone());
+        let sender = sender.clone();
+        let chunks = Arc::clone(&chunks);
+        let usage_settings = config_data.usage.clone();
+        tasks.push(tokio::spawn(async move {
+            let started = Instant::now();
+            let result = chunking::transcribe_chunks(&config, &chunks, concurrency).await;
+            let latency = started.elapsed();
+
+            let model = &config.model;
+            let audio_secs = audio_secs.or_else(|| result.as_ref().ok()?.duration);
+            match data_dir().and_then(|dir| HistoryManager::new(&dir)) {
+                Ok(mut history_manager) => {
+                    let success = result.is_ok();
+                    if let Err(e) = history_manager.record_attempt(model.id(), success, latency) {
+                        tracing::warn!("Failed to record transcription attempt: {}", e);
+                    }
+                    if result.is_ok() {
+                        let record =
+                            UsageRecord::now(&usage_settings, model, audio_secs, bytes, latency);
+                        if let Err(e) = history_manager.record_usage(&record) {
+                            tracing::warn!("Failed to record usage: {}", e);
+                        }
+                    }
+                }
+                Err(e) => tracing::warn!("Failed to open history: {}", e),
+            }
+
+            let result = match result {
+                Ok(response) => ComparisonResult::Done {
+                    text: response.text,
+                    latency,
+                    cost: audio_secs.and_then(|secs| usage::cost(&usage_settings, model, secs)),
+                },
+                Err(e) => {
+                    tracing::warn!("Comparison with '{}' failed: {}", model.id(), e);
+                    ComparisonResult::Failed(e.to_string())
+                }
+            };
+            // The viewer may already be closed
+            let _ = sender.send((index, result));
+        }));
+    }
+
+    let mut viewer = CompareViewer::new(columns)?.with_warning(budget_warning);
+    let selected_text = viewer.run(&mut receiver)?;
+    drop(viewer);
+
+    // Don't wait for models the user didn't wait for
+    for task in &tasks {
+        task.abort();
+    }
+    for task in tasks {
+        let _ = task.await;
+    }
+    // Jobs of the cancelled models are deleted here
+    cleanup.run().await;
+    for path in &chunk_files {
+        if let Err(e) = std::fs::remove_file(path) {
+            tracing::debug!("Failed to remove chunk file {}: {}", path.display(), e);
+        }
+    }
+
+    if let Some(text) = selected_text {
+        copy_to_clipboard(&text)?;
+        tracing::info!("Compared transcription copied to clipboard");
+    }
+
+    tracing::info!("Comparison closed");
+    Ok(())
+}
+
+/// Splits a file that exceeds the upload size or duration limit of any of the compared
+/// models into chunks, so that every model transcribes the same chunks.
+///
+/// Files that couldn't be decoded or are of an unknown format are uploaded whole.
+fn split_file(
+    path: &Path,
+    audio: Option<&(Vec<i16>, u32)>,
+    configs: &[(usize, TranscriptionConfig)],
+) -> Vec<AudioChunk> {
+    let whole = vec![AudioChunk::whole(path)];
+    let (Some((samples, sample_rate)), Some(format)) = (audio, AudioFormat::from_path(path))
+    else {
+        return whole;
+    };
+
+    let configs: Vec<_> = configs.iter().map(|(_, config)| config.clone()).collect();
+    let recording = Recording {
+        path,
+        samples,
+        sample_rate: *sample_rate,
+        format: format.default_encoding(),
+        silence_secs: 0.0,
+    };
+    split_recording(&configs, &recording).unwrap_or_else(|e| {
+        tracing::warn!("Failed to split long file, uploading it whole: {}", e);
+        whole
+    })
+}
+
+/// Returns the IDs of the models to compare, without duplicates.
+///
+/// These are the `models` given on the command line, else `[transcription] compare_models`,
+/// else the selected model followed by the fallback models.
+pub(crate) fn comparison_models(
+    models: &[String],
+    config_data: &config::OsttConfig,
+) -> Vec<String> {
+    let models = if !models.is_empty() {
+        models.to_vec()
+    } else if !config_data.transcription.compare_models.is_empty() {
+        config_data.transcription.compare_models.clone()
+    } else {
+        config::get_selected_model()
+            .ok()
+            .flatten()
+            .into_iter()
+            .chain(config_data.transcription.fallback.iter().cloned())
+            .collect()
+    };
+
+    let mut unique = Vec::with_capacity(models.len());
+    for model in models {
+        if !unique.contains(&model) {
+            unique.push(model);
+        }
+    }
+    unique
+}
+
+/// Builds the transcription configuration for one model of a comparison or benchmark.
+///
+/// # Errors
+/// - If the model is unknown
+/// - If the model's provider needs an API key and none is saved
+pub(crate) fn model_config(
+    registry: &ProviderRegistry,
+    config_data: &config::OsttConfig,
+    model_id: &str,
+    keywords: &[String],
+) -> anyhow::Result<TranscriptionConfig> {
+    let (model, provider) = registry
+        .find_model(model_id)
+        .and_then(|m| registry.provider_for(&m).cloned().map(|p| (m, p)))
+        .ok_or_else(|| anyhow::anyhow!("Unknown model '{model_id}'"))?;
+
+    let api_key = match config::get_api_key(provider.id())? {
+        Some(key) => key,
+        None if !provider.capabilities().requires_api_key => String::new(),
+        None => {
+            return Err(anyhow::anyhow!(
+                "No API key for {}. Please run 'ostt auth'",
+                provider.name()
+            ))
+        }
+    };
+
+    Ok(TranscriptionConfig::new(
+        model,
+        api_key,
+        keywords.to_vec(),
+        config_data.providers.clone(),
+        config_data.network.clone(),
+    ))
+}
@@ -2,14 +2,18 @@
 //!
 //! Displays and manages transcription history with copy-to-clipboard functionality.
 
-use crate::history::{HistoryManager, HistoryViewer};
+use super::compare::{handle_compare, CompareOptions};
+use crate::history::{HistoryAction, HistoryManager, HistoryViewer};
 use crate::clipboard::copy_to_clipboard;
 
 /// Displays the transcription history viewer with copy-to-clipboard functionality.
 ///
+/// Entries with a kept recording can be opened in a model comparison.
+///
 /// # Errors
 /// - If data directory cannot be determined
 /// - If history manager fails to load transcriptions
+/// - If the model comparison fails
 pub async fn handle_history() -> Result<(), anyhow::Error> {
     tracing::info!("=== ostt History Viewer ===");
 
@@ -30,10 +34,18 @@ pub async fn handle_history() -> Result<(), anyhow::Error> {
     let mut viewer = HistoryViewer::new(entries)?;
 
     match viewer.run()? {
-        Some(selected_text) => {
+        Some(HistoryAction::Copy(selected_text)) => {
             copy_to_clipboard(&selected_text)?;
             tracing::info!("Selected transcription copied to clipboard");
         }
+        Some(HistoryAction::Compare(file)) => {
+            drop(viewer);
+            handle_compare(CompareOptions {
+                file,
+                models: Vec::new(),
+            })
+            .await?;
+        }
         None => {
             tracing::info!("History viewer exited without selection");
         }
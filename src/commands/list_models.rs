@@ -0,0 +1,54 @@
+//! List available transcription providers and models.
+
+use super::record::data_dir;
+use crate::config;
+use crate::transcription::{discovery, ProviderRegistry};
+
+/// Lists all registered providers and their models, marking the selected model.
+///
+/// Includes custom providers defined in the configuration file. A configuration file that
+/// cannot be loaded is treated as having no custom providers. Discovered models are listed
+/// from the cache that `ostt auth` fills, without asking the providers.
+pub fn handle_list_models() -> Result<(), anyhow::Error> {
+    let providers_config = config::OsttConfig::load()
+        .map(|c| c.providers)
+        .unwrap_or_default();
+    let registry = match data_dir() {
+        Ok(dir) => discovery::registry(&providers_config, &dir),
+        Err(_) => ProviderRegistry::from_config(&providers_config),
+    };
+    let selected_model = config::get_selected_model().ok().flatten();
+
+    println!();
+    println!(" ┏┓┏╋╋ ");
+    println!(" ┗┛┛┗┗ ");
+    println!();
+    println!("Available transcription models:");
+    println!();
+
+    for provider in registry.providers() {
+        let capabilities = provider.capabilities();
+        let key_indicator = if !capabilities.requires_api_key {
+            " (no API key required)"
+        } else if config::get_api_key(provider.id()).ok().flatten().is_some() {
+            " (API key saved)"
+        } else {
+            ""
+        };
+
+        println!("  {} [{}]{}", provider.name(), provider.id(), key_indicator);
+        for model in registry.provider_models(provider.as_ref()) {
+            let is_selected = selected_model.as_deref() == Some(model.id());
+            let selected_indicator = if is_selected { " [SELECTED]" } else { "" };
+            println!(
+                "    {:<30} {}{}",
+                model.id(),
+                model.description(),
+                selected_indicator
+            );
+        }
+        println!();
+    }
+
+    Ok(())
+}
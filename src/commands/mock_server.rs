@@ -0,0 +1,54 @@
+//! Run the offline mock transcription server.
+//!
+//! The server is only built with the `mock-server` cargo feature.
+
+#[cfg(feature = "mock-server")]
+use crate::transcription::mock::{MockServer, PROVIDERS};
+
+/// Port the mock server listens on unless `--port` is given
+pub const DEFAULT_PORT: u16 = 8787;
+
+/// Serves the mock provider APIs on localhost until interrupted with Ctrl+C.
+///
+/// Prints the environment variables that point ostt at the mock server.
+///
+/// # Errors
+/// - If the port cannot be bound
+#[cfg(feature = "mock-server")]
+pub async fn handle_mock_server(port: u16) -> Result<(), anyhow::Error> {
+    let server = MockServer::start(&format!("127.0.0.1:{port}")).await?;
+
+    println!();
+    println!(" ┏┓┏╋╋ ");
+    println!(" ┗┛┛┗┗ ");
+    println!();
+    println!("Mock transcription server listening on {}", server.url());
+    println!();
+    println!("Point ostt at it from another terminal:");
+    println!();
+    for (provider_id, _) in PROVIDERS {
+        if let Some(base_url) = server.base_url(provider_id) {
+            println!(
+                "  export OSTT_{}_BASE_URL={base_url}",
+                provider_id.to_ascii_uppercase()
+            );
+        }
+    }
+    println!();
+    println!("Any API key is accepted. Use \"invalid\", \"rate-limited\" or \"server-error\"");
+    println!("as the key to simulate failures. Press Ctrl+C to stop.");
+
+    tokio::signal::ctrl_c().await?;
+    Ok(())
+}
+
+/// Explains how to get the mock server in builds without it.
+///
+/// # Errors
+/// - Always, since the server isn't built in
+#[cfg(not(feature = "mock-server"))]
+pub async fn handle_mock_server(_port: u16) -> Result<(), anyhow::Error> {
+    Err(anyhow::anyhow!(
+        "This build of ostt has no mock server. Build it with `cargo build --features mock-server`."
+    ))
+}
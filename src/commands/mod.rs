@@ -7,23 +7,37 @@
 //! - `auth`: Provider + model selection and API key management (unified flow)
 //! - `record`: Audio recording with optional transcription
 //! - `history`: Transcription history viewer
+//! - `compare`: Side-by-side comparison of models on one recording
 //! - `keywords`: Keyword management for transcription
 //! - `config`: Open configuration file in user's preferred editor
 //! - `list_devices`: List available audio input devices
+//! - `list_models`: List available transcription providers and models
 //! - `logs`: Display recent log entries
+//! - `mock_server`: Serve offline imitations of the provider APIs for testing
+//! - `usage`: Usage and cost report
 
 pub mod auth;
 pub mod record;
 pub mod history;
+pub mod compare;
+pub mod bench;
 pub mod keywords;
 pub mod config;
 pub mod list_devices;
+pub mod list_models;
 pub mod logs;
+pub mod mock_server;
+pub mod usage;
 
 pub use auth::handle_auth;
-pub use record::handle_record;
+pub use record::{handle_record, RecordOptions};
 pub use history::handle_history;
+pub use compare::{handle_compare, CompareOptions};
+pub use bench::{handle_bench, BenchOptions};
 pub use keywords::handle_keywords;
 pub use config::handle_config;
 pub use list_devices::handle_list_devices;
+pub use list_models::handle_list_models;
 pub use logs::handle_logs;
+pub use mock_server::handle_mock_server;
+pub use usage::handle_usage;
@@ -1,22 +1,69 @@
 //! Audio recording and transcription.
 //!
 //! Handles audio recording with real-time waveform visualization, optional transcription,
-//! and history management. Supports external triggers via SIGUSR1 signal.
+//! and history management. Supports external triggers via SIGUSR1 signal, and stopping
+//! on silence or after a maximum length (`[vad]`).
+//!
+//! Every transcription's usage is recorded for `ostt usage`, including the silence removed
+//! with `trim_silence`; with a monthly budget in `[usage]`, recordings that would exceed it
+//! are flagged or not uploaded.
 
 use crate::clipboard::copy_to_clipboard;
 use crate::config;
-use crate::history::HistoryManager;
-use crate::recording::{AudioRecorder, OsttTui, RecordingCommand};
-use crate::transcription::TranscriptionAnimation;
+use crate::config::file::BudgetAction;
+use crate::history::{usage, HistoryManager, UsageRecord};
+use crate::recording::{
+    encode, find_ffmpeg, AudioFormat, AudioRecorder, OsttTui, RecordingCommand,
+    VoiceActivityDetector,
+};
+use crate::transcription::{
+    chunking, discovery, language, AudioChunk, Cleanup, LiveSession, ProviderRegistry,
+    TranscriptionAnimation, TranscriptionConfig, TranscriptionModel, TranscriptionResponse,
+    TranscriptionStatus,
+};
 use crate::ui::ErrorScreen;
 use dirs;
 use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+/// How long to wait for Deepgram to deliver the final live results after recording stops
+const LIVE_FINISH_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Share of a model's upload size or duration limit that chunks of a long recording aim
+/// for. Encoded size isn't exactly proportional to duration, and chunks may overlap, so
+/// chunks are planned with some headroom.
+const CHUNK_SIZE_MARGIN: f64 = 0.9;
+
+/// A finished recording: the encoded file plus the samples it was made from, which are
+/// needed to split recordings that exceed a model's upload limit.
+pub(crate) struct Recording<'a> {
+    /// Encoded recording
+    pub(crate) path: &'a Path,
+    /// 16-bit mono PCM samples
+    pub(crate) samples: &'a [i16],
+    /// Sample rate of `samples`
+    pub(crate) sample_rate: u32,
+    /// ffmpeg format the recording was encoded with
+    pub(crate) format: &'a str,
+    /// Seconds of silence removed before encoding
+    pub(crate) silence_secs: f64,
+}
+
+/// Per-run options of `ostt record`, overriding the configuration file.
+#[derive(Debug, Clone, Default)]
+pub struct RecordOptions {
+    /// Spoken language ("auto" or an ISO 639-1 code) instead of `[transcription] language`
+    pub language: Option<String>,
+    /// Translate to English, regardless of `[transcription] translate`
+    pub translate: bool,
+}
 
 /// Handles audio recording and optional transcription.
 ///
 /// Records audio with real-time waveform visualization, optionally transcribes the recording,
 /// and saves to history. Supports external triggers via SIGUSR1 signal.
-pub async fn handle_record() -> Result<(), anyhow::Error> {
+pub async fn handle_record(options: RecordOptions) -> Result<(), anyhow::Error> {
     tracing::info!("=== ostt Audio Recorder Started ===");
 
     let config_data = match config::OsttConfig::load() {
@@ -42,8 +89,102 @@ pub async fn handle_record() -> Result<(), anyhow::Error> {
         config_data.audio.reference_level_db
     );
 
+    let language_setting = options
+        .language
+        .as_deref()
+        .unwrap_or(&config_data.transcription.language);
+    let language = match language::parse(language_setting) {
+        Ok(language) => language,
+        Err(err) => {
+            tracing::error!("Invalid language setting: {}", err);
+            let mut error_screen = ErrorScreen::new()?;
+            error_screen.show_error(&format!("Configuration Error:\n\n{err}"))?;
+            error_screen.cleanup()?;
+            return Err(err);
+        }
+    };
+    tracing::info!("Language: {}", language.as_deref().unwrap_or(language::AUTO));
+
+    // Get the selected model from secrets (stored when user runs 'ostt auth')
+    let selected_model_id = config::get_selected_model().ok().flatten();
+    let registry = discovery::registry(&config_data.providers, &data_dir()?);
+
+    // Record in a format the selected model accepts; fallback models that don't accept it
+    // get a transcoded copy
+    let configured_format = match AudioFormat::from_output_format(&config_data.audio.output_format) {
+        Ok(format) => format,
+        Err(err) => {
+            tracing::error!("Invalid output format: {}", err);
+            let mut error_screen = ErrorScreen::new()?;
+            error_screen.show_error(&format!("Configuration Error:\n\n{err}"))?;
+            error_screen.cleanup()?;
+            return Err(err);
+        }
+    };
+    let selected_model = selected_model_id.as_deref().and_then(|id| registry.find_model(id));
+    let (audio_format, output_format) = match &selected_model {
+        Some(model) if !model.accepts_format(configured_format) => {
+            let format = model.preferred_format();
+            tracing::info!(
+                "{} doesn't accept {}, recording as {} instead",
+                model.id(),
+                configured_format,
+                format
+            );
+            (format, format.default_encoding())
+        }
+        _ => (configured_format, config_data.audio.output_format.as_str()),
+    };
+    // Without ffmpeg, record in a format ostt encodes itself instead of failing once the
+    // recording is done
+    let (audio_format, output_format) = if encode::needs_ffmpeg(output_format)
+        && find_ffmpeg().is_err()
+    {
+        let format = AudioFormat::BUILTIN
+            .iter()
+            .copied()
+            .find(|format| selected_model.as_ref().is_none_or(|m| m.accepts_format(*format)))
+            .unwrap_or(AudioFormat::Wav);
+        tracing::warn!(
+            "ffmpeg not found, recording as {} instead of '{}'",
+            format,
+            output_format
+        );
+        (format, format.default_encoding())
+    } else {
+        (audio_format, output_format)
+    };
+
     let mut audio_recorder = AudioRecorder::new(config_data.audio.sample_rate, config_data.audio.device.clone());
 
+    if config_data.vad.trim_silence {
+        audio_recorder.set_silence_trimming(config_data.vad.clone());
+    }
+
+    let mut translate = options.translate || config_data.transcription.translate;
+
+    // Live streaming gets its own copy of the captured samples. Deepgram can't translate,
+    // so translated recordings are always uploaded after recording. Audio isn't streamed
+    // once a blocking budget is used up; the batch path then reports it.
+    let budget_blocked = config_data.usage.budget_action == BudgetAction::Block
+        && selected_model.is_some()
+        && budget_overrun(&config_data.usage, 0.0).is_some();
+    let live_config = if translate || budget_blocked {
+        None
+    } else {
+        live_transcription_config(
+            &config_data,
+            &registry,
+            selected_model_id.as_deref(),
+            language.as_deref(),
+        )
+    };
+    let live_samples = live_config.as_ref().map(|_| {
+        let (sender, receiver) = tokio::sync::mpsc::unbounded_channel();
+        audio_recorder.set_sample_sink(sender);
+        receiver
+    });
+
     if let Err(e) = audio_recorder.start_recording() {
         tracing::error!("Failed to start recording: {}", e);
         let error_message = format!(
@@ -57,12 +198,34 @@ pub async fn handle_record() -> Result<(), anyhow::Error> {
     }
 
     let actual_sample_rate = audio_recorder.get_sample_rate();
+
+    let live_session = live_config.zip(live_samples).map(|(live_config, samples)| {
+        tracing::info!("Starting live transcription");
+        LiveSession::start(
+            live_config,
+            &config_data.providers.deepgram.streaming_url,
+            actual_sample_rate,
+            samples,
+        )
+    });
     let mut tui = OsttTui::new(
         actual_sample_rate,
         config_data.audio.peak_volume_threshold,
         config_data.audio.reference_level_db,
     )
     .map_err(|e| anyhow::anyhow!("Failed to initialize UI: {e}"))?;
+    tui.translate = translate;
+
+    // With auto-stop, the recording ends like an external trigger once the speaker is quiet
+    let mut vad = config_data
+        .vad
+        .auto_stop
+        .then(|| VoiceActivityDetector::new(&config_data.vad, actual_sample_rate));
+    let max_duration = Some(Duration::from_secs(config_data.vad.max_duration_secs))
+        .filter(|max| !max.is_zero());
+    let max_samples =
+        max_duration.map(|max| (max.as_secs_f64() * actual_sample_rate as f64) as usize);
+    tui.max_duration = max_duration;
 
     let term = std::sync::Arc::new(std::sync::atomic::AtomicBool::new(false));
     let term_clone = term.clone();
@@ -91,7 +254,25 @@ pub async fn handle_record() -> Result<(), anyhow::Error> {
                     tracing::debug!("Recording: {:.1}s recorded", duration_secs);
                 }
 
+                if let Some(session) = &live_session {
+                    tui.set_live_transcript(session.is_active().then(|| session.transcript()));
+                }
+
                 let samples = audio_recorder.get_samples();
+                if let Some(vad) = &mut vad {
+                    vad.update(&samples);
+                    tui.vad_state = Some(vad.state());
+                    if vad.should_stop() {
+                        tracing::info!("Silence detected: transcribing");
+                        should_transcribe = true;
+                        break;
+                    }
+                }
+                if max_samples.is_some_and(|max| samples.len() >= max) {
+                    tracing::info!("Maximum recording length reached: transcribing");
+                    should_transcribe = true;
+                    break;
+                }
                 tui.render_waveform(&samples)
                     .map_err(|e| anyhow::anyhow!("Render failed: {e}"))?;
             }
@@ -109,6 +290,14 @@ pub async fn handle_record() -> Result<(), anyhow::Error> {
                 tui.render_waveform(&samples)
                     .map_err(|e| anyhow::anyhow!("Render failed: {e}"))?;
             }
+            Ok(RecordingCommand::ToggleTranslate) => {
+                translate = !translate;
+                tui.translate = translate;
+                tracing::info!("Translation to English {}", if translate { "on" } else { "off" });
+                let samples = audio_recorder.get_samples();
+                tui.render_waveform(&samples)
+                    .map_err(|e| anyhow::anyhow!("Render failed: {e}"))?;
+            }
             Err(e) => {
                 tracing::error!("Input handling error: {}", e);
                 return Err(anyhow::anyhow!("Input handling error: {e}"));
@@ -117,44 +306,97 @@ pub async fn handle_record() -> Result<(), anyhow::Error> {
     }
 
     tracing::debug!("Stopping recording and saving audio...");
-    let codec = config_data
-        .audio
-        .output_format
-        .split_whitespace()
-        .next()
-        .unwrap_or("mp3");
-    let extension = match codec {
-        "libopus" => "ogg",
-        "libvorbis" => "ogg",
-        "flac" => "flac",
-        "aac" => "m4a",
-        "pcm_s16le" => "wav",
-        _ => codec,
-    };
 
     // Save to temp directory with ostt-recording prefix
     let temp_dir = std::env::temp_dir();
-    let filename = format!("ostt-recording.{extension}");
+    let filename = format!("ostt-recording.{}", audio_format.extension());
     let filepath = temp_dir.join(&filename);
 
+    // Encoding can take a moment with ffmpeg, so the transcription screen shows it
+    let mut animation = TranscriptionAnimation::new(80);
+    if should_transcribe {
+        animation.set_status(TranscriptionStatus::Encoding.message());
+        if let Err(e) = tui.render_transcription_animation(&mut animation) {
+            tracing::warn!("Failed to render animation: {}", e);
+        }
+    }
+
+    // Live streaming sends everything recorded, including silence that is trimmed below
+    let recorded_samples = audio_recorder.sample_count();
     audio_recorder
-        .stop_recording(Some(filepath.clone()), &config_data.audio.output_format)
+        .stop_recording(Some(filepath.clone()), output_format)
         .map_err(|e| {
             tracing::error!("Failed to save recording: {}", e);
             e
         })?;
 
-    if should_transcribe {
-        // Get the selected model from secrets (stored when user runs 'ostt auth')
-        let selected_model_id = config::get_selected_model().ok().flatten();
+    // The live transcript is complete as soon as Deepgram flushes the last results;
+    // if the stream failed or translation was switched on, the recorded file is
+    // transcribed in batch mode instead
+    let mut live_transcribed = false;
+    if let (true, false, Some(session), Some(model_id)) = (
+        should_transcribe,
+        translate,
+        live_session,
+        selected_model_id.as_deref(),
+    ) {
+        let started = std::time::Instant::now();
+        let result = session.finish(LIVE_FINISH_TIMEOUT).await;
+        let latency = started.elapsed();
+        let mut history_manager = HistoryManager::new(&data_dir()?)?;
+        if let Err(e) = history_manager.record_attempt(model_id, result.is_ok(), latency) {
+            tracing::warn!("Failed to record transcription attempt: {}", e);
+        }
+        match result {
+            Ok(mut response) => {
+                // Live results don't report a language; record the requested one, if any
+                response.language = response.language.or_else(|| language.clone());
+                deliver_transcription(
+                    &mut history_manager,
+                    &response,
+                    model_id,
+                    false,
+                    &filepath,
+                    config_data.audio.keep_recordings,
+                );
+                if let Some(model) = &selected_model {
+                    // Streamed audio is 16-bit PCM
+                    record_usage(
+                        &mut history_manager,
+                        &config_data.usage,
+                        model,
+                        recorded_samples as f64 / actual_sample_rate as f64,
+                        0.0,
+                        recorded_samples as u64 * 2,
+                        latency,
+                    );
+                }
+                live_transcribed = true;
+            }
+            Err(e) => {
+                tracing::warn!("Live transcription unavailable, falling back to batch: {}", e);
+            }
+        }
+    }
 
+    if should_transcribe && !live_transcribed {
         if let Some(model_id) = selected_model_id {
-            let filepath_str = filepath.to_string_lossy().to_string();
+            let samples = audio_recorder.samples();
+            let recording = Recording {
+                path: &filepath,
+                samples: &samples,
+                sample_rate: actual_sample_rate,
+                format: output_format,
+                silence_secs: audio_recorder.silence_removed_secs(),
+            };
             if let Err(e) = transcribe_recording_with_animation(
                 &mut tui,
+                &mut animation,
                 &config_data,
                 &model_id,
-                &filepath_str,
+                language.as_deref(),
+                translate,
+                &recording,
             )
             .await
             {
@@ -179,20 +421,32 @@ pub async fn handle_record() -> Result<(), anyhow::Error> {
 
 /// Transcribes an audio recording with animated progress indicator.
 ///
+/// The selected model is tried first, followed by the `[transcription] fallback` models.
+/// Every attempt is recorded in the history database, and the transcription is saved with
+/// the model that produced it. Esc, 'q' or Ctrl+C on the animation screen cancels the
+/// transcription and offers to keep the recording.
+///
 /// # Errors
 /// - If the model ID is invalid
 /// - If no API key is configured for the provider
-/// - If transcription fails
+/// - If every model in the fallback chain fails
 async fn transcribe_recording_with_animation(
     tui: &mut OsttTui,
+    animation: &mut TranscriptionAnimation,
     config_data: &config::OsttConfig,
     model_id: &str,
-    audio_filename: &str,
+    language: Option<&str>,
+    translate: bool,
+    recording: &Recording<'_>,
 ) -> anyhow::Result<()> {
     use crate::transcription;
 
-    let model = match transcription::TranscriptionModel::from_id(model_id) {
-        Some(m) => m,
+    let registry = transcription::discovery::registry(&config_data.providers, &data_dir()?);
+    let model_and_provider = registry
+        .find_model(model_id)
+        .and_then(|m| registry.provider_for(&m).cloned().map(|p| (m, p)));
+    let (model, provider) = match model_and_provider {
+        Some(found) => found,
         None => {
             tui.cleanup().ok();
             let mut error_screen = ErrorScreen::new()?;
@@ -202,10 +456,9 @@ async fn transcribe_recording_with_animation(
         }
     };
 
-    let provider = model.provider();
-
     let api_key = match config::get_api_key(provider.id())? {
         Some(key) => key,
+        None if !provider.capabilities().requires_api_key => String::new(),
         None => {
             tui.cleanup().ok();
             let mut error_screen = ErrorScreen::new()?;
@@ -221,45 +474,176 @@ async fn transcribe_recording_with_animation(
         }
     };
 
-    // Load keywords
-    let config_dir = dirs::home_dir()
-        .ok_or_else(|| anyhow::anyhow!("Could not determine home directory"))?
-        .join(".config")
-        .join("ostt");
-    let keywords_file = config_dir.join("keywords.txt");
-    let keywords = if keywords_file.exists() {
-        let content = fs::read_to_string(&keywords_file)?;
-        content
-            .lines()
-            .map(|line| line.trim().to_string())
-            .filter(|line| !line.is_empty())
-            .collect()
-    } else {
-        Vec::new()
-    };
+    let keywords = load_keywords()?;
 
-    let transcription_config = transcription::TranscriptionConfig::new(
+    let mut chain = vec![transcription::TranscriptionConfig::new(
         model,
         api_key,
-        keywords,
+        keywords.clone(),
         config_data.providers.clone(),
-    );
+        config_data.network.clone(),
+    )
+    .with_language(language.map(str::to_string))
+    .with_translate(translate)];
+
+    let check = transcription::api::check_translation(&chain[0], &registry)
+        .and_then(|()| transcription::api::check_language(&chain[0]));
+    if let Err(e) = check {
+        tui.cleanup().ok();
+        let mut error_screen = ErrorScreen::new()?;
+        error_screen.show_error(&format!("Error: {e}"))?;
+        error_screen.cleanup()?;
+        return Err(e);
+    }
+
+    // Fallback models each use their own provider's key; unusable entries are skipped
+    for fallback_id in &config_data.transcription.fallback {
+        if chain.iter().any(|c| c.model.id() == fallback_id) {
+            continue;
+        }
+        let Some((fallback_model, fallback_provider)) = registry
+            .find_model(fallback_id)
+            .and_then(|m| registry.provider_for(&m).cloned().map(|p| (m, p)))
+        else {
+            tracing::warn!("Skipping unknown fallback model '{}'", fallback_id);
+            continue;
+        };
+        if translate && !fallback_model.supports_translation() {
+            tracing::warn!(
+                "Skipping fallback model '{}': it cannot translate to English",
+                fallback_id
+            );
+            continue;
+        }
+        if let Some(code) = language.filter(|code| {
+            !fallback_model.capabilities().supports_language(code)
+        }) {
+            tracing::warn!(
+                "Skipping fallback model '{}': it doesn't transcribe '{}'",
+                fallback_id,
+                code
+            );
+            continue;
+        }
+        let fallback_key = match config::get_api_key(fallback_provider.id()).ok().flatten() {
+            Some(key) => key,
+            None if !fallback_provider.capabilities().requires_api_key => String::new(),
+            None => {
+                tracing::warn!(
+                    "Skipping fallback model '{}': no API key for {}",
+                    fallback_id,
+                    fallback_provider.name()
+                );
+                continue;
+            }
+        };
+        chain.push(
+            transcription::TranscriptionConfig::new(
+                fallback_model,
+                fallback_key,
+                keywords.clone(),
+                config_data.providers.clone(),
+                config_data.network.clone(),
+            )
+            .with_language(language.map(str::to_string))
+            .with_translate(translate),
+        );
+    }
+
+    let mut history_manager = HistoryManager::new(&data_dir()?)?;
+
+    if config_data.transcription.prefer_fastest && chain.len() > 1 {
+        match history_manager.model_stats() {
+            Ok(stats) => transcription::fallback::order_by_performance(&mut chain, &stats),
+            Err(e) => tracing::warn!("Failed to load model statistics: {}", e),
+        }
+    }
+
+    let audio_secs = recording.samples.len() as f64 / recording.sample_rate as f64;
+    let estimate = usage::cost(&config_data.usage, &chain[0].model, audio_secs).unwrap_or(0.0);
+    let budget_warning = match budget_overrun(&config_data.usage, estimate) {
+        Some(message) if config_data.usage.budget_action == BudgetAction::Block => {
+            tracing::error!("Not uploading recording: {}", message);
+            tui.cleanup().ok();
+            let mut error_screen = ErrorScreen::new()?;
+            error_screen.show_error(&format!(
+                "Error: {message}.\n\nRaise monthly_budget or set budget_action = \"warn\" in [usage] to transcribe anyway."
+            ))?;
+            error_screen.cleanup()?;
+            return Err(anyhow::anyhow!(message));
+        }
+        Some(message) => {
+            tracing::warn!("{}", message);
+            Some(message)
+        }
+        None => None,
+    };
 
     tracing::debug!(
-        "Starting transcription with model '{}' for file '{}'",
-        model_id,
-        audio_filename
+        "Starting transcription with models [{}] for file '{}'",
+        chain
+            .iter()
+            .map(|c| c.model.id())
+            .collect::<Vec<_>>()
+            .join(", "),
+        recording.path.display()
     );
 
-    let mut animation = TranscriptionAnimation::new(80);
-
-    let filename = audio_filename.to_string();
+    // The first model's provider is shown until a status names another one
+    animation.set_provider(
+        registry
+            .provider_for(&chain[0].model)
+            .map(|provider| provider.name().to_string()),
+    );
+    let chunks = match split_recording(&chain, recording) {
+        Ok(chunks) => chunks,
+        Err(e) => {
+            tracing::warn!("Failed to split long recording, uploading it whole: {}", e);
+            vec![AudioChunk::whole(recording.path)]
+        }
+    };
+    let concurrency = config_data.transcription.chunk_concurrency;
+    let upload_bytes: u64 = chunks
+        .iter()
+        .filter_map(|chunk| fs::metadata(&chunk.path).ok())
+        .map(|metadata| metadata.len())
+        .sum();
+
+    animation.set_status(budget_warning.clone());
+    let (progress, mut progress_rx) = transcription::ProgressReporter::channel();
+    let cleanup = Cleanup::new();
+    let chain: Vec<_> = chain
+        .into_iter()
+        .map(|config| config.with_progress(progress.clone()).with_cleanup(cleanup.clone()))
+        .collect();
+
+    let chunk_files: Vec<PathBuf> = match chunks.as_slice() {
+        [_] => Vec::new(),
+        chunks => chunks.iter().map(|chunk| chunk.path.clone()).collect(),
+    };
     let transcription_handle = tokio::spawn(async move {
-        transcription::transcribe(&transcription_config, filename.as_ref()).await
+        let mut attempts = Vec::new();
+        let result = transcription::transcribe_with_fallback(
+            &chain,
+            &chunks,
+            concurrency,
+            &mut attempts,
+        )
+        .await;
+        (result, attempts)
     });
 
+    let mut cancelled = false;
     loop {
-        if let Err(e) = tui.render_transcription_animation(&mut animation) {
+        if progress_rx.has_changed().unwrap_or(false) {
+            let status = progress_rx.borrow_and_update().clone();
+            if let Some(provider) = status.provider() {
+                animation.set_provider(Some(provider.to_string()));
+            }
+            animation.set_status(status.message().or_else(|| budget_warning.clone()));
+        }
+
+        if let Err(e) = tui.render_transcription_animation(animation) {
             tracing::warn!("Failed to render animation: {}", e);
         }
 
@@ -267,36 +651,80 @@ async fn transcribe_recording_with_animation(
             break;
         }
 
-        tokio::time::sleep(std::time::Duration::from_millis(50)).await;
+        match tui.poll_cancel() {
+            Ok(true) => {
+                cancelled = true;
+                break;
+            }
+            Ok(false) => {}
+            Err(e) => {
+                tracing::warn!("Failed to read input: {}", e);
+                tokio::time::sleep(std::time::Duration::from_millis(50)).await;
+            }
+        }
     }
 
-    match transcription_handle.await {
-        Ok(Ok(text)) => {
-            tracing::info!("Transcription completed: {}", text);
-
-            let data_dir = dirs::home_dir()
-                .ok_or_else(|| anyhow::anyhow!("Could not determine home directory"))?
-                .join(".local")
-                .join("share")
-                .join("ostt");
-
-            let mut history_manager = HistoryManager::new(&data_dir)?;
-            if let Err(e) = history_manager.save_transcription(&text) {
-                tracing::warn!("Failed to save transcription to history: {}", e);
+    if cancelled {
+        // Dropping the request futures closes their connections, cancelling the uploads
+        transcription_handle.abort();
+        let _ = transcription_handle.await;
+        // Jobs the providers had already created are deleted here
+        cleanup.run().await;
+        tracing::info!("Transcription cancelled");
+        for path in &chunk_files {
+            if let Err(e) = fs::remove_file(path) {
+                tracing::debug!("Failed to remove chunk file {}: {}", path.display(), e);
             }
+        }
+        return offer_to_keep_recording(tui, animation, &history_manager, recording.path);
+    }
 
-            match copy_to_clipboard(&text) {
-                Ok(_) => {
-                    tracing::debug!("Transcribed text copied to clipboard");
-                }
-                Err(e) => {
-                    tracing::warn!("Failed to copy to clipboard: {}", e);
-                }
+    let outcome = transcription_handle.await.map(|(result, attempts)| {
+        let latency = attempts
+            .iter()
+            .rfind(|attempt| attempt.error.is_none())
+            .map(|attempt| attempt.latency)
+            .unwrap_or_default();
+        for attempt in &attempts {
+            if let Err(e) = history_manager.record_attempt(
+                &attempt.model_id,
+                attempt.error.is_none(),
+                attempt.latency,
+            ) {
+                tracing::warn!("Failed to record transcription attempt: {}", e);
             }
+        }
+        (result, latency)
+    });
 
+    for path in &chunk_files {
+        if let Err(e) = fs::remove_file(path) {
+            tracing::debug!("Failed to remove chunk file {}: {}", path.display(), e);
+        }
+    }
+
+    match outcome {
+        Ok((Ok(transcript), latency)) => {
+            deliver_transcription(
+                &mut history_manager,
+                &transcript.response,
+                transcript.model.id(),
+                translate,
+                recording.path,
+                config_data.audio.keep_recordings,
+            );
+            record_usage(
+                &mut history_manager,
+                &config_data.usage,
+                &transcript.model,
+                audio_secs,
+                recording.silence_secs,
+                upload_bytes,
+                latency,
+            );
             Ok(())
         }
-        Ok(Err(e)) => {
+        Ok((Err(e), _)) => {
             tracing::error!("Transcription failed: {}", e);
             tui.cleanup().ok();
             let mut error_screen = ErrorScreen::new()?;
@@ -314,3 +742,257 @@ async fn transcribe_recording_with_animation(
         }
     }
 }
+
+/// Asks whether to keep the recording of a cancelled transcription, and keeps it if so.
+///
+/// The kept recording's path is printed after leaving the recording screen.
+///
+/// # Errors
+/// - If input cannot be read
+/// - If the recording cannot be kept
+fn offer_to_keep_recording(
+    tui: &mut OsttTui,
+    animation: &mut TranscriptionAnimation,
+    history_manager: &HistoryManager,
+    recording: &Path,
+) -> anyhow::Result<()> {
+    animation.set_status(Some(
+        "Transcription cancelled. Keep the recording for later? [y/n]".to_string(),
+    ));
+    let keep = loop {
+        if let Err(e) = tui.render_transcription_animation(animation) {
+            tracing::warn!("Failed to render animation: {}", e);
+        }
+        match tui.poll_confirmation() {
+            Ok(Some(keep)) => break keep,
+            Ok(None) => {}
+            Err(e) => return Err(anyhow::anyhow!("Input handling error: {e}")),
+        }
+    };
+    if !keep {
+        return Ok(());
+    }
+
+    let kept = history_manager.keep_cancelled_recording(recording)?;
+    tracing::info!("Kept cancelled recording at {}", kept.display());
+    tui.cleanup().ok();
+    println!("Recording kept at {}", kept.display());
+    Ok(())
+}
+
+/// Splits a recording that exceeds the upload size or duration limit of a model in the
+/// chain into chunks.
+///
+/// The chunk length is derived from the strictest limits in the chain and the encoded size
+/// of the whole recording. Recordings within every limit are returned as a single chunk.
+///
+/// # Errors
+/// - If the recording's size cannot be read
+/// - If a chunk cannot be encoded
+pub(crate) fn split_recording(
+    chain: &[TranscriptionConfig],
+    recording: &Recording<'_>,
+) -> anyhow::Result<Vec<AudioChunk>> {
+    let whole = vec![AudioChunk::whole(recording.path)];
+    if recording.samples.is_empty() {
+        return Ok(whole);
+    }
+    let size = fs::metadata(recording.path)?.len();
+    let duration = recording.samples.len() as f64 / recording.sample_rate as f64;
+
+    let mut max_chunk_secs = f64::INFINITY;
+    if let Some(limit) = chain.iter().filter_map(|c| c.model.max_upload_bytes()).min() {
+        if size > limit {
+            max_chunk_secs = duration * (limit as f64 * CHUNK_SIZE_MARGIN) / size as f64;
+        }
+    }
+    if let Some(limit) = chain
+        .iter()
+        .filter_map(|c| c.model.capabilities().max_duration_secs)
+        .min()
+    {
+        if duration > limit as f64 {
+            max_chunk_secs = max_chunk_secs.min(limit as f64 * CHUNK_SIZE_MARGIN);
+        }
+    }
+    if max_chunk_secs.is_infinite() {
+        return Ok(whole);
+    }
+
+    let ranges = chunking::plan_chunks(recording.samples, recording.sample_rate, max_chunk_secs);
+    tracing::info!(
+        "Recording is {} bytes and {:.0} seconds, above a model's limits: splitting into {} chunks",
+        size,
+        duration,
+        ranges.len()
+    );
+
+    let extension = recording
+        .path
+        .extension()
+        .map(|ext| ext.to_string_lossy().to_string())
+        .unwrap_or_default();
+    let mut previous_end = 0;
+    let mut chunks = Vec::with_capacity(ranges.len());
+    for (index, range) in ranges.iter().enumerate() {
+        let path = std::env::temp_dir().join(format!("ostt-recording-{}.{extension}", index + 1));
+        encode(
+            &recording.samples[range.clone()],
+            recording.sample_rate,
+            &path,
+            recording.format,
+        )?;
+        chunks.push(AudioChunk::new(path, range, previous_end, recording.sample_rate));
+        previous_end = range.end;
+    }
+    Ok(chunks)
+}
+
+/// Saves a finished transcription to history and copies it to the clipboard.
+///
+/// With `keep_recordings` above 0, a copy of the recording is kept with the entry.
+fn deliver_transcription(
+    history_manager: &mut HistoryManager,
+    response: &TranscriptionResponse,
+    model_id: &str,
+    translated: bool,
+    recording: &Path,
+    keep_recordings: usize,
+) {
+    let text = &response.text;
+    tracing::info!("Transcription completed with '{}': {}", model_id, text);
+    if let Some(confidence) = response.confidence {
+        tracing::debug!("Transcription confidence: {:.2}", confidence);
+    }
+
+    let kept = (keep_recordings > 0)
+        .then(|| history_manager.keep_recording(recording, keep_recordings))
+        .and_then(|result| {
+            result
+                .map_err(|e| tracing::warn!("Failed to keep recording: {}", e))
+                .ok()
+        });
+
+    if let Err(e) =
+        history_manager.save_transcription(response, model_id, translated, kept.as_deref())
+    {
+        tracing::warn!("Failed to save transcription to history: {}", e);
+    }
+
+    match copy_to_clipboard(text) {
+        Ok(_) => {
+            tracing::debug!("Transcribed text copied to clipboard");
+        }
+        Err(e) => {
+            tracing::warn!("Failed to copy to clipboard: {}", e);
+        }
+    }
+}
+
+/// Returns a description of how transcriptions costing `estimate` in total would exceed the
+/// monthly budget, if a budget is set and they would.
+///
+/// Usage that cannot be read doesn't count against the budget.
+pub(crate) fn budget_overrun(settings: &config::UsageSettings, estimate: f64) -> Option<String> {
+    settings.monthly_budget?;
+    let records = data_dir()
+        .and_then(|dir| HistoryManager::new(&dir))
+        .and_then(|mut history_manager| history_manager.get_all_usage())
+        .map_err(|e| tracing::warn!("Failed to load usage for the budget check: {}", e))
+        .ok()?;
+    let spent = usage::month_to_date(&records, chrono::Local::now());
+    usage::check_budget(settings, spent, estimate)
+}
+
+/// Records the audio length, removed silence, upload size, latency and cost of a finished
+/// transcription.
+fn record_usage(
+    history_manager: &mut HistoryManager,
+    settings: &config::UsageSettings,
+    model: &TranscriptionModel,
+    audio_secs: f64,
+    silence_secs: f64,
+    bytes: u64,
+    latency: Duration,
+) {
+    let record = UsageRecord::now(settings, model, Some(audio_secs), bytes, latency)
+        .with_silence_secs(silence_secs);
+    if let Err(e) = history_manager.record_usage(&record) {
+        tracing::warn!("Failed to record usage: {}", e);
+    }
+}
+
+/// Builds the configuration for a live streaming session, if streaming applies.
+///
+/// Streaming is used when the selected model belongs to Deepgram, `streaming` is enabled in
+/// `[providers.deepgram]` and an API key is available.
+fn live_transcription_config(
+    config_data: &config::OsttConfig,
+    registry: &ProviderRegistry,
+    model_id: Option<&str>,
+    language: Option<&str>,
+) -> Option<TranscriptionConfig> {
+    if !config_data.providers.deepgram.streaming {
+        return None;
+    }
+
+    let model = registry.find_model(model_id?)?;
+    if model.provider_id() != "deepgram" {
+        return None;
+    }
+
+    let Some(api_key) = config::get_api_key(model.provider_id()).ok().flatten() else {
+        tracing::warn!("Live streaming disabled: no Deepgram API key");
+        return None;
+    };
+    let keywords = load_keywords()
+        .map_err(|e| tracing::warn!("Failed to load keywords: {}", e))
+        .unwrap_or_default();
+
+    Some(
+        TranscriptionConfig::new(
+            model,
+            api_key,
+            keywords,
+            config_data.providers.clone(),
+            config_data.network.clone(),
+        )
+        .with_language(language.map(str::to_string)),
+    )
+}
+
+/// Loads the keywords used to improve transcription accuracy.
+///
+/// # Errors
+/// - If the home directory cannot be determined
+/// - If the keywords file exists but cannot be read
+pub(crate) fn load_keywords() -> anyhow::Result<Vec<String>> {
+    let config_dir = dirs::home_dir()
+        .ok_or_else(|| anyhow::anyhow!("Could not determine home directory"))?
+        .join(".config")
+        .join("ostt");
+    let keywords_file = config_dir.join("keywords.txt");
+    let keywords = if keywords_file.exists() {
+        let content = fs::read_to_string(&keywords_file)?;
+        content
+            .lines()
+            .map(|line| line.trim().to_string())
+            .filter(|line| !line.is_empty())
+            .collect()
+    } else {
+        Vec::new()
+    };
+    Ok(keywords)
+}
+
+/// Returns the ostt data directory holding the history database.
+///
+/// # Errors
+/// - If the home directory cannot be determined
+pub(crate) fn data_dir() -> anyhow::Result<std::path::PathBuf> {
+    Ok(dirs::home_dir()
+        .ok_or_else(|| anyhow::anyhow!("Could not determine home directory"))?
+        .join(".local")
+        .join("share")
+        .join("ostt"))
+}
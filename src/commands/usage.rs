@@ -0,0 +1,120 @@
+//! Usage and cost report.
+//!
+//! Summarises the recorded transcriptions by month, by day and by model, and compares this
+//! month's spend with the budget from `[usage]`.
+
+use chrono::Local;
+
+use crate::config;
+use crate::history::usage::{self, UsageTotals};
+use crate::history::HistoryManager;
+
+/// Number of most recent days listed in the daily breakdown
+const RECENT_DAYS: usize = 30;
+
+/// Prints the usage report.
+///
+/// Costs use the prices in effect when each transcription was made. A configuration file
+/// that cannot be loaded is treated as having no budget.
+///
+/// # Errors
+/// - If the home directory cannot be determined
+/// - If the history database cannot be read
+pub fn handle_usage() -> Result<(), anyhow::Error> {
+    let settings = config::OsttConfig::load()
+        .map(|c| c.usage)
+        .unwrap_or_default();
+
+    let data_dir = dirs::home_dir()
+        .ok_or_else(|| anyhow::anyhow!("Could not determine home directory"))?
+        .join(".local")
+        .join("share")
+        .join("ostt");
+    let mut history_manager = HistoryManager::new(&data_dir)?;
+    let records = history_manager.get_all_usage()?;
+
+    println!();
+    println!(" ┏┓┏╋╋ ");
+    println!(" ┗┛┛┗┗ ");
+    println!();
+
+    if records.is_empty() {
+        println!("No usage recorded yet. Usage is recorded for every transcription.");
+        return Ok(());
+    }
+
+    let spent = usage::month_to_date(&records, Local::now());
+    match settings.monthly_budget {
+        Some(budget) => println!(
+            "This month: {} of {} budget ({:.0}%)",
+            usage::format_cost(spent),
+            usage::format_cost(budget),
+            if budget > 0.0 { spent / budget * 100.0 } else { 100.0 }
+        ),
+        None => println!("This month: {}", usage::format_cost(spent)),
+    }
+
+    println!();
+    println!("By month:");
+    print_header("Month");
+    let by_month = usage::summarize(&records, |r| r.created_at.format("%Y-%m").to_string());
+    for (month, totals) in by_month.iter().rev() {
+        print_row(month, totals);
+    }
+
+    println!();
+    println!("By day (last {RECENT_DAYS} days with usage):");
+    print_header("Day");
+    let by_day = usage::summarize(&records, |r| r.created_at.format("%Y-%m-%d").to_string());
+    for (day, totals) in by_day.iter().rev().take(RECENT_DAYS) {
+        print_row(day, totals);
+    }
+
+    println!();
+    println!("By model:");
+    print_header("Model");
+    let by_model = usage::summarize(&records, |r| format!("{} [{}]", r.model, r.provider));
+    for (model, totals) in &by_model {
+        print_row(model, totals);
+    }
+
+    if by_model.values().any(|totals| totals.unpriced > 0) {
+        println!();
+        println!("* Includes transcriptions with no known price; set one in [usage.pricing].");
+    }
+    if by_model.values().any(|totals| totals.silence_secs > 0.0) {
+        println!();
+        println!("Trimmed minutes of silence were removed before upload and not billed.");
+    }
+    println!();
+
+    Ok(())
+}
+
+/// Prints the column headings of a breakdown
+fn print_header(label: &str) {
+    println!(
+        "  {:<36} {:>6} {:>9} {:>9} {:>9} {:>9} {:>10}",
+        label, "Count", "Minutes", "Trimmed", "MB", "Latency", "Cost"
+    );
+}
+
+/// Prints one line of a breakdown
+fn print_row(label: &str, totals: &UsageTotals) {
+    let latency = totals
+        .average_latency()
+        .map(|latency| format!("{:.1}s", latency.as_secs_f64()))
+        .unwrap_or_default();
+    let unpriced = if totals.unpriced > 0 { "*" } else { "" };
+    println!(
+        "  {:<36} {:>6} {:>9.1} {:>9.1} {:>9.2} {:>9} {:>10}{}",
+        label,
+        totals.transcriptions,
+        totals.audio_secs / 60.0,
+        totals.silence_secs / 60.0,
+        totals.bytes as f64 / (1024.0 * 1024.0),
+        latency,
+        usage::format_cost(totals.cost),
+        unpriced
+    );
+}
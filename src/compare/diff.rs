@@ -0,0 +1,255 @@
+//! Word-level alignment of transcripts.
+//!
+//! Transcripts are compared word by word after normalisation, so differences in case and
+//! punctuation ("Hello," vs "hello") don't count as disagreements. The alignment is a
+//! minimum edit distance alignment (Levenshtein), the same one word and character error
+//! rates are based on.
+
+/// One step of an alignment between a reference and a hypothesis sequence.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Edit {
+    /// `reference[i]` equals `hypothesis[j]`
+    Match(usize, usize),
+    /// `reference[i]` was replaced by `hypothesis[j]`
+    Substitute(usize, usize),
+    /// `reference[i]` is missing from the hypothesis
+    Delete(usize),
+    /// `hypothesis[j]` is not in the reference
+    Insert(usize),
+}
+
+/// Aligns two sequences with the fewest substitutions, deletions and insertions.
+///
+/// The edits are returned in order; every element of both sequences appears in exactly
+/// one edit.
+pub fn align<T: PartialEq>(reference: &[T], hypothesis: &[T]) -> Vec<Edit> {
+    let columns = hypothesis.len() + 1;
+    // distances[i * columns + j] = edit distance between reference[..i] and hypothesis[..j]
+    let mut distances = vec![0u32; (reference.len() + 1) * columns];
+    for (i, row) in distances.chunks_exact_mut(columns).enumerate() {
+        row[0] = i as u32;
+    }
+    for (j, distance) in distances[..columns].iter_mut().enumerate() {
+        *distance = j as u32;
+    }
+    for i in 1..=reference.len() {
+        for j in 1..=hypothesis.len() {
+            let substitution = u32::from(reference[i - 1] != hypothesis[j - 1]);
+            distances[i * columns + j] = (distances[(i - 1) * columns + j - 1] + substitution)
+                .min(distances[(i - 1) * columns + j] + 1)
+                .min(distances[i * columns + j - 1] + 1);
+        }
+    }
+
+    let mut edits = Vec::with_capacity(reference.len().max(hypothesis.len()));
+    let (mut i, mut j) = (reference.len(), hypothesis.len());
+    while i > 0 || j > 0 {
+        let distance = distances[i * columns + j];
+        if i > 0 && j > 0 {
+            let same = reference[i - 1] == hypothesis[j - 1];
+            if distances[(i - 1) * columns + j - 1] + u32::from(!same) == distance {
+                edits.push(if same {
+                    Edit::Match(i - 1, j - 1)
+                } else {
+                    Edit::Substitute(i - 1, j - 1)
+                });
+                i -= 1;
+                j -= 1;
+                continue;
+            }
+        }
+        if i > 0 && distances[(i - 1) * columns + j] + 1 == distance {
+            edits.push(Edit::Delete(i - 1));
+            i -= 1;
+        } else {
+            edits.push(Edit::Insert(j - 1));
+            j -= 1;
+        }
+    }
+    edits.reverse();
+    edits
+}
+
+/// Returns the edit distance between two sequences.
+///
+/// Unlike [`align`], only keeps two rows of the distance table, so it is cheap enough for
+/// character-level comparisons of long transcripts.
+pub fn distance<T: PartialEq>(reference: &[T], hypothesis: &[T]) -> usize {
+    let mut previous: Vec<usize> = (0..=hypothesis.len()).collect();
+    let mut current = vec![0; hypothesis.len() + 1];
+    for (i, reference_item) in reference.iter().enumerate() {
+        current[0] = i + 1;
+        for (j, hypothesis_item) in hypothesis.iter().enumerate() {
+            let substitution = usize::from(reference_item != hypothesis_item);
+            current[j + 1] = (previous[j] + substitution)
+                .min(previous[j + 1] + 1)
+                .min(current[j] + 1);
+        }
+        std::mem::swap(&mut previous, &mut current);
+    }
+    previous[hypothesis.len()]
+}
+
+/// Number of edits in an alignment that aren't matches
+pub fn errors(edits: &[Edit]) -> usize {
+    edits
+        .iter()
+        .filter(|edit| !matches!(edit, Edit::Match(..)))
+        .count()
+}
+
+/// Normalises a word for comparison: lowercase, without punctuation.
+pub fn normalize(word: &str) -> String {
+    word.chars()
+        .filter(|c| c.is_alphanumeric() || *c == '\'')
+        .flat_map(char::to_lowercase)
+        .collect()
+}
+
+/// Splits a transcript into normalised words
+pub fn words(text: &str) -> Vec<String> {
+    text.split_whitespace().map(normalize).collect()
+}
+
+/// Word-by-word comparison of two transcripts.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct WordDiff {
+    /// For every whitespace-separated word of the reference, whether the hypothesis has it
+    pub reference: Vec<bool>,
+    /// For every whitespace-separated word of the hypothesis, whether the reference has it
+    pub hypothesis: Vec<bool>,
+    /// Number of substituted, deleted and inserted words
+    pub errors: usize,
+}
+
+/// Compares two transcripts word by word.
+pub fn word_diff(reference: &str, hypothesis: &str) -> WordDiff {
+    let reference = words(reference);
+    let hypothesis = words(hypothesis);
+    let edits = align(&reference, &hypothesis);
+
+    let mut diff = WordDiff {
+        reference: vec![false; reference.len()],
+        hypothesis: vec![false; hypothesis.len()],
+        errors: errors(&edits),
+    };
+    for edit in edits {
+        if let Edit::Match(i, j) = edit {
+            diff.reference[i] = true;
+            diff.hypothesis[j] = true;
+        }
+    }
+    diff
+}
+
+/// Word and character errors of a transcript against a reference transcript.
+///
+/// Counts rather than rates, so that results over several transcripts can be added up
+/// before computing the error rates of the whole corpus.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct ErrorCounts {
+    /// Substituted, deleted and inserted words
+    pub word_errors: usize,
+    /// Number of words in the reference
+    pub words: usize,
+    /// Substituted, deleted and inserted characters
+    pub char_errors: usize,
+    /// Number of characters in the reference
+    pub chars: usize,
+}
+
+impl ErrorCounts {
+    /// Compares a transcript with its reference after normalising both.
+    ///
+    /// Tokens without letters or digits, such as dashes, aren't words and are left out.
+    /// Characters are compared on the normalised words joined by single spaces.
+    pub fn new(reference: &str, hypothesis: &str) -> Self {
+        let spoken = |text: &str| -> Vec<String> {
+            words(text).into_iter().filter(|word| !word.is_empty()).collect()
+        };
+        let reference_words = spoken(reference);
+        let hypothesis_words = spoken(hypothesis);
+        let reference_chars: Vec<char> = reference_words.join(" ").chars().collect();
+        let hypothesis_chars: Vec<char> = hypothesis_words.join(" ").chars().collect();
+        Self {
+            word_errors: distance(&reference_words, &hypothesis_words),
+            words: reference_words.len(),
+            char_errors: distance(&reference_chars, &hypothesis_chars),
+            chars: reference_chars.len(),
+        }
+    }
+
+    /// Adds the counts of another transcript
+    pub fn add(&mut self, other: ErrorCounts) {
+        self.word_errors += other.word_errors;
+        self.words += other.words;
+        self.char_errors += other.char_errors;
+        self.chars += other.chars;
+    }
+
+    /// Word error rate (0.0 = perfect; can exceed 1.0 with many insertions)
+    pub fn word_error_rate(&self) -> f64 {
+        rate(self.word_errors, self.words)
+    }
+
+    /// Character error rate (0.0 = perfect)
+    pub fn character_error_rate(&self) -> f64 {
+        rate(self.char_errors, self.chars)
+    }
+}
+
+/// Errors per reference unit; an empty reference only scores 0 against an empty transcript
+fn rate(errors: usize, total: usize) -> f64 {
+    match (errors, total) {
+        (0, _) => 0.0,
+        (_, 0) => 1.0,
+        (errors, total) => errors as f64 / total as f64,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn aligns_words_ignoring_case_and_punctuation() {
+        let edits = align(&["a", "b", "c", "d"], &["a", "x", "c", "e", "d"]);
+        assert_eq!(
+            edits,
+            vec![
+                Edit::Match(0, 0),
+                Edit::Substitute(1, 1),
+                Edit::Match(2, 2),
+                Edit::Insert(3),
+                Edit::Match(3, 4),
+            ]
+        );
+        assert_eq!(errors(&edits), 2);
+        assert_eq!(errors(&align::<char>(&[], &['a', 'b'])), 2);
+
+        let diff = word_diff("Hello, world. It's nice.", "hello world it's very nice");
+        assert_eq!(diff.reference, vec![true, true, true, true]);
+        assert_eq!(diff.hypothesis, vec![true, true, true, false, true]);
+        assert_eq!(diff.errors, 1);
+    }
+
+    #[test]
+    fn computes_error_rates() {
+        let kitten: Vec<char> = "kitten".chars().collect();
+        let sitting: Vec<char> = "sitting".chars().collect();
+        assert_eq!(distance(&kitten, &sitting), 3);
+
+        let counts = ErrorCounts::new("The cat sat on the mat.", "the cat sat on a mat");
+        assert_eq!((counts.word_errors, counts.words), (1, 6));
+        assert_eq!((counts.char_errors, counts.chars), (3, 22));
+
+        let mut total = counts;
+        total.add(ErrorCounts::new("hello there", "hello there"));
+        assert_eq!(total.word_error_rate(), 1.0 / 8.0);
+        assert_eq!(ErrorCounts::new("", "").word_error_rate(), 0.0);
+
+        let counts = ErrorCounts::new("Hello — world…", "hello - world ...");
+        assert_eq!((counts.word_errors, counts.words), (0, 2));
+        assert_eq!((counts.char_errors, counts.chars), (0, 11));
+    }
+}
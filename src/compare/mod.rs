@@ -0,0 +1,39 @@
+//! Side-by-side comparison of transcription models.
+//!
+//! The same recording is sent to several models at once; their transcripts are shown next
+//! to each other with latency, estimated cost and the words they disagree on. The word
+//! alignment also provides the error rates used by `ostt bench`.
+
+pub mod diff;
+pub mod ui;
+
+pub use ui::CompareViewer;
+
+use std::time::Duration;
+
+/// Outcome of one model in a comparison.
+#[derive(Debug, Clone, PartialEq)]
+pub enum ComparisonResult {
+    /// The model hasn't answered yet
+    Pending,
+    /// The model returned a transcript
+    Done {
+        /// The transcribed text
+        text: String,
+        /// How long the model took
+        latency: Duration,
+        /// Estimated cost in USD (`None` if the price or audio length is unknown)
+        cost: Option<f64>,
+    },
+    /// The model couldn't be used or the transcription failed
+    Failed(String),
+}
+
+/// One model taking part in a comparison.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Comparison {
+    /// ID of the model
+    pub model_id: String,
+    /// Current outcome
+    pub result: ComparisonResult,
+}
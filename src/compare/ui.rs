@@ -0,0 +1,320 @@
+//! Interactive terminal UI for comparing transcription models.
+//!
+//! Shows one column per model, filled in as results arrive. Words a transcript doesn't
+//! share with the reference (the first finished transcript) are highlighted; in the
+//! reference itself, words that any other transcript disagrees with are highlighted.
+
+use super::diff::{self, WordDiff};
+use super::{Comparison, ComparisonResult};
+use crate::history::usage::format_cost;
+use anyhow::Result;
+use crossterm::{
+    event::{self, Event, KeyCode, KeyEventKind},
+    execute,
+    terminal::{disable_raw_mode, enable_raw_mode, EnterAlternateScreen, LeaveAlternateScreen},
+};
+use ratatui::{
+    prelude::*,
+    widgets::{Block, Borders, Padding, Paragraph, Wrap},
+};
+use std::io::{self, Stdout};
+use std::time::{Duration, Instant};
+use tokio::sync::mpsc::UnboundedReceiver;
+
+const BG: Color = Color::Rgb(0, 0, 0);
+const FG: Color = Color::Rgb(255, 255, 255);
+const DIM_FG: Color = Color::Rgb(100, 100, 100);
+const DIFF_FG: Color = Color::Rgb(255, 200, 0);
+const ERROR_FG: Color = Color::Rgb(255, 90, 90);
+const HELP_FG: Color = Color::Rgb(100, 100, 100);
+
+/// Interactive side-by-side view of a model comparison.
+pub struct CompareViewer {
+    terminal: Terminal<CrosstermBackend<Stdout>>,
+    columns: Vec<Comparison>,
+    selected: usize,
+    scroll: u16,
+    started: Instant,
+    notification: Option<(String, Instant)>,
+    warning: Option<String>,
+}
+
+impl CompareViewer {
+    /// Creates a viewer for the given models, which are usually all still pending.
+    pub fn new(columns: Vec<Comparison>) -> Result<Self> {
+        enable_raw_mode()?;
+        let mut stdout = io::stdout();
+        execute!(stdout, EnterAlternateScreen)?;
+
+        let backend = CrosstermBackend::new(stdout);
+        let terminal = Terminal::new(backend)?;
+
+        Ok(Self {
+            terminal,
+            columns,
+            selected: 0,
+            scroll: 0,
+            started: Instant::now(),
+            notification: None,
+            warning: None,
+        })
+    }
+
+    /// Shows a warning, such as an exceeded budget, above the columns
+    pub fn with_warning(mut self, warning: Option<String>) -> Self {
+        self.warning = warning;
+        self
+    }
+
+    /// Runs the viewer until the user exits, applying results as they arrive.
+    ///
+    /// `results` delivers the outcome of the model at a column index.
+    ///
+    /// # Returns
+    /// The transcript the user chose to copy, if any
+    pub fn run(
+        &mut self,
+        results: &mut UnboundedReceiver<(usize, ComparisonResult)>,
+    ) -> Result<Option<String>> {
+        tracing::info!("Comparison viewer started with {} models", self.columns.len());
+
+        let mut selected_text: Option<String> = None;
+
+        loop {
+            while let Ok((index, result)) = results.try_recv() {
+                if let Some(column) = self.columns.get_mut(index) {
+                    column.result = result;
+                }
+            }
+
+            self.draw()?;
+
+            if let Some((_, start_time)) = self.notification {
+                if start_time.elapsed() >= Duration::from_millis(500) {
+                    self.notification = None;
+                    if selected_text.is_some() {
+                        break;
+                    }
+                }
+            }
+
+            if event::poll(Duration::from_millis(50))? {
+                if let Event::Key(key) = event::read()? {
+                    if key.kind != KeyEventKind::Press {
+                        continue;
+                    }
+                    match key.code {
+                        KeyCode::Char('q') | KeyCode::Esc => break,
+                        KeyCode::Left | KeyCode::BackTab => {
+                            self.selected = self.selected.saturating_sub(1);
+                        }
+                        KeyCode::Right | KeyCode::Tab => {
+                            self.selected = (self.selected + 1).min(self.columns.len() - 1);
+                        }
+                        KeyCode::Up => self.scroll = self.scroll.saturating_sub(1),
+                        KeyCode::Down => self.scroll = self.scroll.saturating_add(1),
+                        KeyCode::Enter => {
+                            if let ComparisonResult::Done { text, .. } =
+                                &self.columns[self.selected].result
+                            {
+                                selected_text = Some(text.clone());
+                                self.notification =
+                                    Some(("Copied to clipboard!".to_string(), Instant::now()));
+                            }
+                        }
+                        _ => {}
+                    }
+                }
+            }
+        }
+
+        self.cleanup()?;
+        Ok(selected_text)
+    }
+
+    /// Renders the current state of the comparison.
+    fn draw(&mut self) -> Result<()> {
+        let notification = self.notification.clone();
+        let warning = self.warning.clone();
+        let columns = &self.columns;
+        let selected = self.selected;
+        let scroll = self.scroll;
+        let elapsed = self.started.elapsed();
+
+        // The first finished transcript is the reference the others are compared with
+        let reference = columns
+            .iter()
+            .position(|c| matches!(c.result, ComparisonResult::Done { .. }));
+        let diffs: Vec<Option<WordDiff>> = columns
+            .iter()
+            .enumerate()
+            .map(|(index, column)| match (reference, &column.result) {
+                (Some(reference), ComparisonResult::Done { text, .. }) if reference != index => {
+                    Some(diff::word_diff(done_text(&columns[reference]), text))
+                }
+                _ => None,
+            })
+            .collect();
+
+        self.terminal.draw(|frame| {
+            let area = frame.area();
+
+            let padding_block = Block::default()
+                .padding(Padding::uniform(1))
+                .style(Style::default().bg(BG));
+            frame.render_widget(&padding_block, area);
+            let padded_area = padding_block.inner(area);
+
+            let [header_area, columns_area, footer_area] = Layout::vertical([
+                Constraint::Length(3),
+                Constraint::Min(0),
+                Constraint::Length(1),
+            ])
+            .areas(padded_area);
+
+            let header = Paragraph::new(" ┏┓┏╋╋ \n ┗┛┛┗┗ \n")
+                .style(Style::default().fg(FG))
+                .alignment(Alignment::Left);
+            frame.render_widget(header, header_area);
+            if let Some(warning) = warning {
+                let warning = Paragraph::new(warning)
+                    .style(Style::default().fg(DIFF_FG))
+                    .alignment(Alignment::Right);
+                frame.render_widget(warning, header_area);
+            }
+
+            let column_areas = Layout::horizontal(
+                columns.iter().map(|_| Constraint::Ratio(1, columns.len() as u32)),
+            )
+            .split(columns_area);
+
+            for (index, column) in columns.iter().enumerate() {
+                let border = if index == selected { FG } else { DIM_FG };
+                let block = Block::default()
+                    .title(format!(" {} ", column.model_id))
+                    .borders(Borders::ALL)
+                    .border_style(Style::default().fg(border))
+                    .padding(Padding::horizontal(1));
+
+                let lines = match &column.result {
+                    ComparisonResult::Pending => vec![Line::styled(
+                        format!("Transcribing… {}s", elapsed.as_secs()),
+                        Style::default().fg(DIM_FG),
+                    )],
+                    ComparisonResult::Failed(error) => {
+                        vec![Line::styled(error.clone(), Style::default().fg(ERROR_FG))]
+                    }
+                    ComparisonResult::Done { text, latency, cost } => {
+                        let mut summary = format!("{:.1}s", latency.as_secs_f64());
+                        summary.push_str(&match cost {
+                            Some(cost) => format!(" · {}", format_cost(*cost)),
+                            None => " · cost unknown".to_string(),
+                        });
+                        let matched = match &diffs[index] {
+                            Some(diff) => {
+                                let plural = if diff.errors == 1 { "" } else { "s" };
+                                summary.push_str(&format!(" · {} difference{plural}", diff.errors));
+                                diff.hypothesis.clone()
+                            }
+                            None => {
+                                summary.push_str(" · reference");
+                                // A reference word is matched if every other transcript has it
+                                let mut matched = vec![true; text.split_whitespace().count()];
+                                for diff in diffs.iter().flatten() {
+                                    for (word, found) in matched.iter_mut().zip(&diff.reference) {
+                                        *word &= *found;
+                                    }
+                                }
+                                matched
+                            }
+                        };
+                        vec![
+                            Line::styled(summary, Style::default().fg(DIM_FG)),
+                            Line::default(),
+                            highlighted(text, &matched),
+                        ]
+                    }
+                };
+
+                let paragraph = Paragraph::new(lines)
+                    .block(block)
+                    .wrap(Wrap { trim: false })
+                    .scroll((scroll, 0))
+                    .style(Style::default().fg(FG).bg(BG));
+                frame.render_widget(paragraph, column_areas[index]);
+            }
+
+            let help_text = "←→ select, ↑↓ scroll, ↵ copy, esc/q exit";
+            let help_paragraph = Paragraph::new(help_text)
+                .alignment(Alignment::Center)
+                .style(Style::default().fg(HELP_FG));
+            frame.render_widget(help_paragraph, footer_area);
+
+            if let Some((message, _)) = notification {
+                Self::render_notification(frame, area, &message);
+            }
+        })?;
+
+        Ok(())
+    }
+
+    /// Renders a centered notification modal.
+    fn render_notification(frame: &mut Frame, screen_area: Rect, message: &str) {
+        let modal_width = (message.len() as u16).saturating_add(4);
+        let modal_height = 3;
+
+        let modal_area = Rect {
+            x: screen_area.x + (screen_area.width.saturating_sub(modal_width)) / 2,
+            y: screen_area.y + (screen_area.height.saturating_sub(modal_height)) / 2,
+            width: modal_width.min(screen_area.width),
+            height: modal_height,
+        };
+
+        let modal_block = Block::default()
+            .borders(Borders::ALL)
+            .style(Style::default().bg(Color::Green).fg(Color::Black));
+        frame.render_widget(&modal_block, modal_area);
+
+        let inner_area = modal_block.inner(modal_area);
+        let notification_text = Paragraph::new(message)
+            .style(Style::default().bg(Color::Green).fg(Color::Black))
+            .alignment(Alignment::Center);
+        frame.render_widget(notification_text, inner_area);
+    }
+
+    /// Cleans up terminal and restores normal mode.
+    fn cleanup(&mut self) -> Result<()> {
+        disable_raw_mode()?;
+        execute!(self.terminal.backend_mut(), LeaveAlternateScreen)?;
+        self.terminal.show_cursor()?;
+        tracing::debug!("Comparison viewer terminal cleanup complete");
+        Ok(())
+    }
+}
+
+impl Drop for CompareViewer {
+    fn drop(&mut self) {
+        let _ = self.cleanup();
+    }
+}
+
+/// Returns the transcript of a finished column (empty for unfinished ones)
+fn done_text(column: &Comparison) -> &str {
+    match &column.result {
+        ComparisonResult::Done { text, .. } => text,
+        _ => "",
+    }
+}
+
+/// Builds a line of the transcript's words, highlighting the unmatched ones
+fn highlighted<'a>(text: &'a str, matched: &[bool]) -> Line<'a> {
+    let mut spans = Vec::new();
+    for (index, word) in text.split_whitespace().enumerate() {
+        if index > 0 {
+            spans.push(Span::raw(" "));
+        }
+        let color = if matched.get(index).copied().unwrap_or(true) { FG } else { DIFF_FG };
+        spans.push(Span::styled(word, Style::default().fg(color)));
+    }
+    Line::from(spans)
+}
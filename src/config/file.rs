@@ -4,9 +4,12 @@
 //! Configuration is stored in the user's config directory.
 
 use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
 use std::fs;
 use std::path::PathBuf;
 
+use crate::recording::AudioFormat;
+
 /// Audio recording and processing configuration.
 #[derive(Debug, Serialize, Deserialize)]
 pub struct AudioConfig {
@@ -26,6 +29,10 @@ pub struct AudioConfig {
     /// Output audio format string: "codec [ffmpeg_options]" (e.g., "mp3 -ab 16k -ar 12000")
     #[serde(default = "default_output_format")]
     pub output_format: String,
+    /// Number of recent recordings kept alongside the history, e.g. to compare models on
+    /// them later (0 = recordings are not kept)
+    #[serde(default)]
+    pub keep_recordings: usize,
 }
 
 fn default_output_format() -> String {
@@ -40,6 +47,71 @@ fn default_reference_level_db() -> i8 {
     -20
 }
 
+/// Voice activity detection (`[vad]`).
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct VadConfig {
+    /// Stop recording and transcribe after `silence_secs` of silence
+    #[serde(default)]
+    pub auto_stop: bool,
+    /// Seconds of silence after which the recording stops
+    #[serde(default = "default_silence_secs")]
+    pub silence_secs: f64,
+    /// Start the silence timer only once speech was heard
+    #[serde(default = "default_wait_for_speech")]
+    pub wait_for_speech: bool,
+    /// Level in dBFS from which audio counts as speech
+    #[serde(default = "default_threshold_db")]
+    pub threshold_db: f64,
+    /// Stop recording and transcribe after this many seconds, with or without
+    /// `auto_stop` (0 = no limit)
+    #[serde(default)]
+    pub max_duration_secs: u64,
+    /// Remove leading and trailing silence and shorten long pauses before encoding
+    #[serde(default)]
+    pub trim_silence: bool,
+    /// Pauses longer than this many seconds are shortened when trimming silence
+    #[serde(default = "default_max_silence_secs")]
+    pub max_silence_secs: f64,
+    /// Seconds of silence long pauses are shortened to
+    #[serde(default = "default_silence_gap_secs")]
+    pub silence_gap_secs: f64,
+}
+
+fn default_silence_secs() -> f64 {
+    2.0
+}
+
+fn default_wait_for_speech() -> bool {
+    true
+}
+
+fn default_threshold_db() -> f64 {
+    -45.0
+}
+
+fn default_max_silence_secs() -> f64 {
+    1.0
+}
+
+fn default_silence_gap_secs() -> f64 {
+    0.3
+}
+
+impl Default for VadConfig {
+    fn default() -> Self {
+        Self {
+            auto_stop: false,
+            silence_secs: default_silence_secs(),
+            wait_for_speech: default_wait_for_speech(),
+            threshold_db: default_threshold_db(),
+            max_duration_secs: 0,
+            trim_silence: false,
+            max_silence_secs: default_max_silence_secs(),
+            silence_gap_secs: default_silence_gap_secs(),
+        }
+    }
+}
+
 /// Deepgram API configuration.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct DeepgramConfig {
@@ -73,12 +145,35 @@ pub struct DeepgramConfig {
     /// Opt out from Deepgram Model Improvement Program
     #[serde(default)]
     pub mip_opt_out: bool,
+    /// Recognize speakers and label each turn of the transcript
+    #[serde(default)]
+    pub diarize: bool,
+    /// Prefix of each speaker turn; `{speaker}` is replaced by the speaker number (from 1)
+    #[serde(default = "default_speaker_label")]
+    pub speaker_label: String,
+    /// Stream audio to Deepgram while recording and show the transcript live
+    #[serde(default)]
+    pub streaming: bool,
+    /// WebSocket endpoint used for live streaming
+    #[serde(default = "default_streaming_url")]
+    pub streaming_url: String,
+    /// API base URL for uploads, e.g. of a proxy or the mock server (the public API if unset)
+    #[serde(default)]
+    pub base_url: Option<String>,
 }
 
 fn default_utt_split() -> f64 {
     0.8
 }
 
+fn default_speaker_label() -> String {
+    "Speaker {speaker}: ".to_string()
+}
+
+fn default_streaming_url() -> String {
+    "wss://api.deepgram.com/v1/listen".to_string()
+}
+
 impl Default for DeepgramConfig {
     fn default() -> Self {
         Self {
@@ -92,15 +187,231 @@ impl Default for DeepgramConfig {
             utterances: false,
             utt_split: default_utt_split(),
             mip_opt_out: false,
+            diarize: false,
+            speaker_label: default_speaker_label(),
+            streaming: false,
+            streaming_url: default_streaming_url(),
+            base_url: None,
         }
     }
 }
 
-/// OpenAI API configuration.
+/// Request options of the Whisper-style APIs (`[providers.openai]`, `[providers.groq]` and
+/// `[providers.deepinfra]`).
 #[derive(Debug, Clone, Default, Serialize, Deserialize)]
-pub struct OpenAiConfig {
-    // Currently no additional parameters beyond what's in API
-    // Add here as OpenAI features become configurable
+pub struct WhisperApiConfig {
+    /// API base URL, e.g. of a proxy or the mock server (the public API if unset)
+    #[serde(default)]
+    pub base_url: Option<String>,
+    /// Sampling temperature between 0 and 1; higher values are more random (API default if unset)
+    #[serde(default)]
+    pub temperature: Option<f32>,
+    /// Text sent as the prompt ahead of the keywords, e.g. spelling or style hints
+    #[serde(default)]
+    pub prompt: String,
+    /// Response format requested from the API (chosen per model if unset)
+    #[serde(default)]
+    pub response_format: Option<ResponseFormat>,
+    /// Timestamps requested with `verbose_json` (segments and words if unset)
+    #[serde(default)]
+    pub timestamp_granularities: Option<Vec<TimestampGranularity>>,
+}
+
+/// Response format of the Whisper-style APIs.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ResponseFormat {
+    /// Text only, as JSON
+    Json,
+    /// Text with language, duration and timestamps, as JSON
+    VerboseJson,
+    /// Plain text
+    Text,
+    /// SubRip subtitles
+    Srt,
+    /// WebVTT subtitles
+    Vtt,
+}
+
+impl ResponseFormat {
+    /// Value sent in the `response_format` field
+    pub fn as_str(self) -> &'static str {
+        match self {
+            ResponseFormat::Json => "json",
+            ResponseFormat::VerboseJson => "verbose_json",
+            ResponseFormat::Text => "text",
+            ResponseFormat::Srt => "srt",
+            ResponseFormat::Vtt => "vtt",
+        }
+    }
+
+    /// Whether the response body is JSON (the other formats return the transcript as is)
+    pub fn is_json(self) -> bool {
+        matches!(self, ResponseFormat::Json | ResponseFormat::VerboseJson)
+    }
+}
+
+/// Timing detail requested with `verbose_json`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum TimestampGranularity {
+    /// Segment (sentence) timestamps
+    Segment,
+    /// Word timestamps; adds latency on some APIs
+    Word,
+}
+
+impl TimestampGranularity {
+    /// Value sent in the `timestamp_granularities[]` field
+    pub fn as_str(self) -> &'static str {
+        match self {
+            TimestampGranularity::Segment => "segment",
+            TimestampGranularity::Word => "word",
+        }
+    }
+}
+
+/// AssemblyAI configuration (`[providers.assemblyai]`).
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AssemblyAiConfig {
+    /// API base URL, e.g. of a proxy or the mock server (the public API if unset)
+    #[serde(default)]
+    pub base_url: Option<String>,
+    /// How strongly the keywords are boosted
+    #[serde(default)]
+    pub boost_param: BoostParam,
+    /// Recognize speakers and label each turn of the transcript
+    #[serde(default)]
+    pub speaker_labels: bool,
+    /// Prefix of each speaker turn; `{speaker}` is replaced by the speaker number (from 1)
+    #[serde(default = "default_speaker_label")]
+    pub speaker_label: String,
+}
+
+impl Default for AssemblyAiConfig {
+    fn default() -> Self {
+        Self {
+            base_url: None,
+            boost_param: BoostParam::default(),
+            speaker_labels: false,
+            speaker_label: default_speaker_label(),
+        }
+    }
+}
+
+/// Weight of AssemblyAI's word boost.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum BoostParam {
+    /// Slight preference for the keywords
+    Low,
+    /// AssemblyAI's default weight
+    #[default]
+    Default,
+    /// Strong preference for the keywords
+    High,
+}
+
+impl BoostParam {
+    /// Value sent in the `boost_param` field
+    pub fn as_str(self) -> &'static str {
+        match self {
+            BoostParam::Low => "low",
+            BoostParam::Default => "default",
+            BoostParam::High => "high",
+        }
+    }
+}
+
+/// Local whisper.cpp backend configuration (`[providers.local]`).
+///
+/// Only used when ostt is built with the `local-whisper` feature.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LocalWhisperConfig {
+    /// Path to a GGML/GGUF Whisper model file ("~" expands to the home directory)
+    #[serde(default = "default_local_model_path")]
+    pub model_path: String,
+    /// Number of CPU threads to use (0 = number of available cores)
+    #[serde(default)]
+    pub threads: u16,
+}
+
+fn default_local_model_path() -> String {
+    "~/.local/share/ostt/models/ggml-base.bin".to_string()
+}
+
+impl Default for LocalWhisperConfig {
+    fn default() -> Self {
+        Self {
+            model_path: default_local_model_path(),
+            threads: 0,
+        }
+    }
+}
+
+/// How a custom provider expects the API key to be sent.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum CustomAuthStyle {
+    /// `Authorization: Bearer <key>` (OpenAI style)
+    #[default]
+    Bearer,
+    /// Raw key in the header named by `auth_header`
+    Header,
+    /// No authentication (typical for local servers)
+    None,
+}
+
+/// Custom OpenAI-compatible provider configuration (`[providers.custom.<name>]`).
+///
+/// Allows pointing ostt at self-hosted servers such as faster-whisper-server or LocalAI
+/// that expose the OpenAI `/audio/transcriptions` multipart API.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct CustomProviderConfig {
+    /// Base URL of the API, e.g. "http://localhost:8000/v1"
+    pub base_url: String,
+    /// Model name sent to the server
+    pub model: String,
+    /// How the API key is sent ("bearer", "header" or "none")
+    #[serde(default)]
+    pub auth: CustomAuthStyle,
+    /// Header name used when `auth = "header"`
+    #[serde(default = "default_auth_header")]
+    pub auth_header: String,
+    /// Multipart field name for the audio file
+    #[serde(default = "default_file_field")]
+    pub file_field: String,
+    /// Multipart field name for the model name
+    #[serde(default = "default_model_field")]
+    pub model_field: String,
+    /// Multipart field name for the keywords prompt (empty to disable)
+    #[serde(default = "default_prompt_field")]
+    pub prompt_field: String,
+    /// Largest upload the server accepts in megabytes; longer recordings are split
+    #[serde(default)]
+    pub max_upload_mb: Option<u64>,
+    /// Audio formats the server accepts (empty for any); other recordings are transcoded
+    #[serde(default)]
+    pub formats: Vec<AudioFormat>,
+    /// Languages the model transcribes as ISO 639-1 codes (empty for any)
+    #[serde(default)]
+    pub languages: Vec<String>,
+}
+
+fn default_auth_header() -> String {
+    "X-API-Key".to_string()
+}
+
+fn default_file_field() -> String {
+    "file".to_string()
+}
+
+fn default_model_field() -> String {
+    "model".to_string()
+}
+
+fn default_prompt_field() -> String {
+    "prompt".to_string()
 }
 
 /// Provider-specific configuration
@@ -111,7 +422,13 @@ pub enum ProviderConfig {
     Deepgram(DeepgramConfig),
     /// OpenAI provider configuration
     #[serde(rename = "openai")]
-    OpenAi(OpenAiConfig),
+    OpenAi(WhisperApiConfig),
+    /// Groq provider configuration
+    #[serde(rename = "groq")]
+    Groq(WhisperApiConfig),
+    /// DeepInfra provider configuration
+    #[serde(rename = "deepinfra")]
+    DeepInfra(WhisperApiConfig),
 }
 
 /// All provider configurations
@@ -120,7 +437,159 @@ pub struct ProvidersConfig {
     #[serde(default)]
     pub deepgram: DeepgramConfig,
     #[serde(default)]
-    pub openai: OpenAiConfig,
+    pub openai: WhisperApiConfig,
+    #[serde(default)]
+    pub groq: WhisperApiConfig,
+    #[serde(default)]
+    pub deepinfra: WhisperApiConfig,
+    #[serde(default)]
+    pub assemblyai: AssemblyAiConfig,
+    #[serde(default)]
+    pub local: LocalWhisperConfig,
+    /// User-defined OpenAI-compatible providers, keyed by name
+    #[serde(default)]
+    pub custom: BTreeMap<String, CustomProviderConfig>,
+}
+
+impl ProvidersConfig {
+    /// Applies the `OSTT_<PROVIDER>_BASE_URL` environment variables (e.g.
+    /// `OSTT_OPENAI_BASE_URL`), which take precedence over the configured `base_url`s.
+    pub fn apply_base_url_overrides(&mut self) {
+        let base_urls = [
+            ("openai", &mut self.openai.base_url),
+            ("groq", &mut self.groq.base_url),
+            ("deepinfra", &mut self.deepinfra.base_url),
+            ("deepgram", &mut self.deepgram.base_url),
+            ("assemblyai", &mut self.assemblyai.base_url),
+        ];
+        for (provider_id, base_url) in base_urls {
+            let variable = format!("OSTT_{}_BASE_URL", provider_id.to_ascii_uppercase());
+            if let Some(url) = std::env::var(&variable).ok().filter(|url| !url.trim().is_empty()) {
+                *base_url = Some(url);
+            }
+        }
+    }
+}
+
+/// General transcription behaviour (`[transcription]`).
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TranscriptionSettings {
+    /// Model IDs to try, in order, when the selected model fails
+    #[serde(default)]
+    pub fallback: Vec<String>,
+    /// Try the model with the best recent latency and success record first
+    #[serde(default)]
+    pub prefer_fastest: bool,
+    /// How many chunks of a long recording are uploaded at the same time (1 = sequential)
+    #[serde(default = "default_chunk_concurrency")]
+    pub chunk_concurrency: usize,
+    /// Spoken language as an ISO 639-1 code (e.g. "de"), or "auto" to detect it
+    #[serde(default = "default_language")]
+    pub language: String,
+    /// Translate speech to English instead of transcribing it (toggle with 't' while recording)
+    #[serde(default)]
+    pub translate: bool,
+    /// Model IDs `ostt compare` runs side by side (default: the selected and fallback models)
+    #[serde(default)]
+    pub compare_models: Vec<String>,
+}
+
+fn default_language() -> String {
+    "auto".to_string()
+}
+
+fn default_chunk_concurrency() -> usize {
+    2
+}
+
+impl Default for TranscriptionSettings {
+    fn default() -> Self {
+        Self {
+            fallback: Vec::new(),
+            prefer_fastest: false,
+            chunk_concurrency: default_chunk_concurrency(),
+            language: default_language(),
+            translate: false,
+            compare_models: Vec::new(),
+        }
+    }
+}
+
+/// Network configuration for transcription API requests (`[network]`).
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct NetworkConfig {
+    /// Seconds to wait for a connection to the API server
+    #[serde(default = "default_connect_timeout_secs")]
+    pub connect_timeout_secs: u64,
+    /// Seconds the API gets to respond; uploads get extra time according to their size
+    #[serde(default = "default_request_timeout_secs")]
+    pub request_timeout_secs: u64,
+    /// How many times a failed request is retried (0 disables retries)
+    #[serde(default = "default_max_retries")]
+    pub max_retries: u32,
+    /// Delay before the first retry in milliseconds (doubled on each further retry)
+    #[serde(default = "default_initial_backoff_ms")]
+    pub initial_backoff_ms: u64,
+    /// Upper bound for the delay between retries in milliseconds
+    #[serde(default = "default_max_backoff_ms")]
+    pub max_backoff_ms: u64,
+}
+
+fn default_connect_timeout_secs() -> u64 {
+    10
+}
+
+fn default_request_timeout_secs() -> u64 {
+    120
+}
+
+fn default_max_retries() -> u32 {
+    3
+}
+
+fn default_initial_backoff_ms() -> u64 {
+    500
+}
+
+fn default_max_backoff_ms() -> u64 {
+    8000
+}
+
+impl Default for NetworkConfig {
+    fn default() -> Self {
+        Self {
+            connect_timeout_secs: default_connect_timeout_secs(),
+            request_timeout_secs: default_request_timeout_secs(),
+            max_retries: default_max_retries(),
+            initial_backoff_ms: default_initial_backoff_ms(),
+            max_backoff_ms: default_max_backoff_ms(),
+        }
+    }
+}
+
+/// Usage tracking and spending limits (`[usage]`).
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct UsageSettings {
+    /// Monthly spending limit in USD (no limit if unset)
+    #[serde(default)]
+    pub monthly_budget: Option<f64>,
+    /// What happens when a transcription would exceed the monthly budget
+    #[serde(default)]
+    pub budget_action: BudgetAction,
+    /// Price per audio minute in USD by model ID, overriding the built-in list prices
+    #[serde(default)]
+    pub pricing: BTreeMap<String, f64>,
+}
+
+/// Reaction to a transcription that would exceed the monthly budget.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum BudgetAction {
+    /// Transcribe anyway and show a warning
+    #[default]
+    Warn,
+    /// Don't upload the recording
+    Block,
 }
 
 /// Complete application configuration.
@@ -129,11 +598,21 @@ pub struct OsttConfig {
     pub audio: AudioConfig,
     #[serde(default)]
     pub providers: ProvidersConfig,
+    #[serde(default)]
+    pub transcription: TranscriptionSettings,
+    #[serde(default)]
+    pub network: NetworkConfig,
+    #[serde(default)]
+    pub usage: UsageSettings,
+    #[serde(default)]
+    pub vad: VadConfig,
 }
 
 impl OsttConfig {
     /// Loads configuration from the user's config directory.
     ///
+    /// `OSTT_<PROVIDER>_BASE_URL` environment variables override the providers' `base_url`s.
+    ///
     /// # Errors
     /// - If the config directory cannot be determined
     /// - If the config file cannot be read
@@ -141,7 +620,8 @@ impl OsttConfig {
     pub fn load() -> Result<Self, Box<dyn std::error::Error>> {
         let config_path = get_config_path()?;
         let config_content = fs::read_to_string(&config_path)?;
-        let config: OsttConfig = toml::from_str(&config_content)?;
+        let mut config: OsttConfig = toml::from_str(&config_content)?;
+        config.providers.apply_base_url_overrides();
         Ok(config)
     }
 
@@ -168,8 +648,13 @@ impl OsttConfig {
                 peak_volume_threshold: default_peak_volume_threshold(),
                 reference_level_db: default_reference_level_db(),
                 output_format: default_output_format(),
+                keep_recordings: 0,
             },
             providers: ProvidersConfig::default(),
+            transcription: TranscriptionSettings::default(),
+            network: NetworkConfig::default(),
+            usage: UsageSettings::default(),
+            vad: VadConfig::default(),
         }
     }
 }
@@ -2,10 +2,12 @@
 //!
 //! Manages persistent storage of all transcriptions with SQLite,
 //! and provides an interactive terminal UI for browsing and selecting
-//! past transcriptions.
+//! past transcriptions. Usage records stored alongside them back cost reports and the
+//! monthly budget.
 
 pub mod storage;
 pub mod ui;
+pub mod usage;
 
-pub use storage::{HistoryManager, TranscriptionEntry};
-pub use ui::HistoryViewer;
+pub use storage::{HistoryManager, ModelStats, TranscriptionEntry, UsageRecord};
+pub use ui::{HistoryAction, HistoryViewer};
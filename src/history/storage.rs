@@ -1,13 +1,27 @@
 //! Transcription history storage and retrieval using SQLite.
 //!
 //! Manages persistent storage of all transcriptions with timestamps,
-//! and provides querying capabilities for the history viewer.
+//! and provides querying capabilities for the history viewer. Also records per-attempt
+//! latencies for fallback ordering and per-transcription usage for cost reporting.
 
 use anyhow::Result;
 use chrono::{DateTime, Local};
 use rusqlite::OptionalExtension;
 use rusqlite::{params, Connection};
+use std::collections::HashMap;
 use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+use crate::transcription::TranscriptionResponse;
+
+/// Number of recent attempts per model considered for latency statistics
+const STATS_WINDOW: usize = 20;
+
+/// Directory below the data directory holding kept recordings
+const RECORDINGS_DIR: &str = "recordings";
+
+/// Subdirectory of the recordings directory for recordings of cancelled transcriptions
+const CANCELLED_DIR: &str = "cancelled";
 
 /// A single transcription entry in the history.
 #[derive(Debug, Clone)]
@@ -18,6 +32,51 @@ pub struct TranscriptionEntry {
     pub text: String,
     /// When this transcription was created
     pub created_at: DateTime<Local>,
+    /// ID of the model that produced the text (unknown for entries saved by older versions)
+    pub model: Option<String>,
+    /// Detected or requested language code (unknown for older entries and some providers)
+    pub language: Option<String>,
+    /// Whether the speech was translated to English rather than transcribed
+    pub translated: bool,
+    /// Segments, word timings, confidence and language, if the provider returned any
+    pub details: Option<TranscriptionResponse>,
+    /// Kept copy of the recording (see `keep_recordings`), if any. The file may since have
+    /// been removed to make room for newer recordings.
+    pub audio_path: Option<PathBuf>,
+}
+
+/// Recent performance of a transcription model, derived from recorded attempts.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ModelStats {
+    /// Number of attempts considered
+    pub attempts: u32,
+    /// Number of those attempts that succeeded
+    pub successes: u32,
+    /// Average latency of the successful attempts
+    pub average_latency: Option<Duration>,
+    /// Whether the most recent attempt succeeded
+    pub last_succeeded: bool,
+}
+
+/// Audio sent to a provider for one successful transcription.
+#[derive(Debug, Clone, PartialEq)]
+pub struct UsageRecord {
+    /// When the transcription was made
+    pub created_at: DateTime<Local>,
+    /// ID of the model that produced the text
+    pub model: String,
+    /// ID of the provider the audio was sent to
+    pub provider: String,
+    /// Length of the audio sent, in seconds
+    pub audio_secs: f64,
+    /// Silence removed from the recording before it was sent, in seconds
+    pub silence_secs: f64,
+    /// Size of the audio uploaded (or streamed) to the provider
+    pub bytes: u64,
+    /// How long the provider took to return the transcription
+    pub latency: Duration,
+    /// Cost in USD (`None` if the model's price or the audio's length is unknown)
+    pub cost: Option<f64>,
 }
 
 /// Manages the transcription history database.
@@ -65,6 +124,39 @@ impl HistoryManager {
                 [],
             )?;
 
+            // Databases created by older versions lack the newer columns
+            ensure_column(&connection, "transcriptions", "model", "TEXT")?;
+            ensure_column(&connection, "transcriptions", "details", "TEXT")?;
+            ensure_column(&connection, "transcriptions", "language", "TEXT")?;
+            ensure_column(&connection, "transcriptions", "translated", "INTEGER NOT NULL DEFAULT 0")?;
+            ensure_column(&connection, "transcriptions", "audio_path", "TEXT")?;
+
+            connection.execute(
+                "CREATE TABLE IF NOT EXISTS attempts (
+                    id INTEGER PRIMARY KEY AUTOINCREMENT,
+                    model TEXT NOT NULL,
+                    success INTEGER NOT NULL,
+                    latency_ms INTEGER NOT NULL,
+                    created_at TEXT NOT NULL
+                )",
+                [],
+            )?;
+
+            connection.execute(
+                "CREATE TABLE IF NOT EXISTS usage (
+                    id INTEGER PRIMARY KEY AUTOINCREMENT,
+                    model TEXT NOT NULL,
+                    provider TEXT NOT NULL,
+                    audio_secs REAL NOT NULL,
+                    bytes INTEGER NOT NULL,
+                    latency_ms INTEGER NOT NULL,
+                    cost REAL,
+                    created_at TEXT NOT NULL
+                )",
+                [],
+            )?;
+            ensure_column(&connection, "usage", "silence_secs", "REAL NOT NULL DEFAULT 0")?;
+
             self.connection = Some(connection);
         }
 
@@ -73,26 +165,256 @@ impl HistoryManager {
 
     /// Saves a new transcription to the history database.
     ///
+    /// The text is stored as is; segments, words, confidence and language are stored as
+    /// JSON alongside it when the provider returned any.
+    ///
     /// # Arguments
-    /// * `text` - The transcribed text to save
+    /// * `response` - The transcription to save
+    /// * `model` - ID of the model that produced the text
+    /// * `translated` - Whether the text is a translation to English
+    /// * `audio_path` - Kept copy of the recording, if any (see [`Self::keep_recording`])
     ///
     /// # Errors
     /// - If database connection fails
+    /// - If the details cannot be serialized
     /// - If insertion fails
-    pub fn save_transcription(&mut self, text: &str) -> Result<()> {
+    pub fn save_transcription(
+        &mut self,
+        response: &TranscriptionResponse,
+        model: &str,
+        translated: bool,
+        audio_path: Option<&Path>,
+    ) -> Result<()> {
+        let details = if response.has_details() {
+            Some(serde_json::to_string(response)?)
+        } else {
+            None
+        };
+
         let connection = self.get_connection()?;
         let now = Local::now();
         let timestamp = now.to_rfc3339();
 
+        let audio_path = audio_path.map(|path| path.to_string_lossy().to_string());
+
         connection.execute(
-            "INSERT INTO transcriptions (text, created_at) VALUES (?1, ?2)",
-            params![text, timestamp],
+            "INSERT INTO transcriptions
+                 (text, created_at, model, details, language, translated, audio_path)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)",
+            params![
+                response.text,
+                timestamp,
+                model,
+                details,
+                response.language,
+                translated,
+                audio_path
+            ],
         )?;
 
         tracing::info!("Transcription saved to history");
         Ok(())
     }
 
+    /// Copies a recording into the data directory so it can be transcribed again later.
+    ///
+    /// Only the `keep` most recent recordings are kept; older ones are deleted.
+    ///
+    /// # Returns
+    /// The path of the kept copy
+    ///
+    /// # Errors
+    /// - If the recordings directory cannot be created
+    /// - If the recording cannot be copied
+    pub fn keep_recording(&self, recording: &Path, keep: usize) -> Result<PathBuf> {
+        let directory = self.recordings_dir();
+        let kept = copy_recording(recording, &directory)?;
+
+        let mut recordings: Vec<PathBuf> = std::fs::read_dir(&directory)?
+            .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+            .filter(|path| path.is_file())
+            .collect();
+        recordings.sort();
+        let excess = recordings.len().saturating_sub(keep.max(1));
+        for old in &recordings[..excess] {
+            if let Err(e) = std::fs::remove_file(old) {
+                tracing::debug!("Failed to remove old recording {}: {}", old.display(), e);
+            }
+        }
+
+        Ok(kept)
+    }
+
+    /// Copies the recording of a cancelled transcription into the data directory.
+    ///
+    /// Cancelled recordings are kept in their own directory and never deleted, so that
+    /// `keep_recordings` doesn't remove them.
+    ///
+    /// # Returns
+    /// The path of the kept copy
+    ///
+    /// # Errors
+    /// - If the directory cannot be created
+    /// - If the recording cannot be copied
+    pub fn keep_cancelled_recording(&self, recording: &Path) -> Result<PathBuf> {
+        copy_recording(recording, &self.recordings_dir().join(CANCELLED_DIR))
+    }
+
+    /// Directory of kept recordings, next to the database
+    fn recordings_dir(&self) -> PathBuf {
+        self.database_path
+            .parent()
+            .unwrap_or(Path::new("."))
+            .join(RECORDINGS_DIR)
+    }
+
+    /// Records the outcome of a single transcription attempt.
+    ///
+    /// # Arguments
+    /// * `model` - ID of the model that was tried
+    /// * `success` - Whether the attempt produced a transcription
+    /// * `latency` - How long the attempt took
+    ///
+    /// # Errors
+    /// - If database connection fails
+    /// - If insertion fails
+    pub fn record_attempt(&mut self, model: &str, success: bool, latency: Duration) -> Result<()> {
+        let connection = self.get_connection()?;
+        let timestamp = Local::now().to_rfc3339();
+
+        connection.execute(
+            "INSERT INTO attempts (model, success, latency_ms, created_at) VALUES (?1, ?2, ?3, ?4)",
+            params![model, success, latency.as_millis() as i64, timestamp],
+        )?;
+
+        Ok(())
+    }
+
+    /// Records the audio duration, upload size, latency and cost of a transcription.
+    ///
+    /// # Errors
+    /// - If database connection fails
+    /// - If insertion fails
+    pub fn record_usage(&mut self, record: &UsageRecord) -> Result<()> {
+        let connection = self.get_connection()?;
+
+        connection.execute(
+            "INSERT INTO usage
+                 (model, provider, audio_secs, bytes, latency_ms, cost, created_at, silence_secs)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8)",
+            params![
+                record.model,
+                record.provider,
+                record.audio_secs,
+                record.bytes as i64,
+                record.latency.as_millis() as i64,
+                record.cost,
+                record.created_at.to_rfc3339(),
+                record.silence_secs
+            ],
+        )?;
+
+        Ok(())
+    }
+
+    /// Retrieves all usage records ordered from oldest to newest.
+    ///
+    /// # Errors
+    /// - If database connection fails
+    /// - If query execution fails
+    /// - If timestamp parsing fails
+    pub fn get_all_usage(&mut self) -> Result<Vec<UsageRecord>> {
+        let connection = self.get_connection()?;
+
+        let mut statement = connection.prepare(
+            "SELECT model, provider, audio_secs, bytes, latency_ms, cost, created_at, silence_secs FROM usage ORDER BY id",
+        )?;
+
+        let records = statement
+            .query_map([], |row| {
+                let timestamp_str = row.get::<_, String>(6)?;
+                let created_at = DateTime::parse_from_rfc3339(&timestamp_str)
+                    .map(|dt| dt.with_timezone(&Local))
+                    .map_err(|_| {
+                        rusqlite::Error::InvalidParameterName(
+                            "Invalid timestamp format".to_string(),
+                        )
+                    })?;
+
+                Ok(UsageRecord {
+                    created_at,
+                    model: row.get(0)?,
+                    provider: row.get(1)?,
+                    audio_secs: row.get(2)?,
+                    silence_secs: row.get(7)?,
+                    bytes: row.get::<_, i64>(3)?.max(0) as u64,
+                    latency: Duration::from_millis(row.get::<_, i64>(4)?.max(0) as u64),
+                    cost: row.get(5)?,
+                })
+            })?
+            .collect::<Result<Vec<_>, _>>()?;
+
+        Ok(records)
+    }
+
+    /// Returns recent performance statistics for every model with recorded attempts.
+    ///
+    /// Only the most recent attempts of each model are considered, so that a provider's
+    /// outage or slowdown from weeks ago doesn't count against it today.
+    ///
+    /// # Errors
+    /// - If database connection fails
+    /// - If query execution fails
+    pub fn model_stats(&mut self) -> Result<HashMap<String, ModelStats>> {
+        let connection = self.get_connection()?;
+
+        let mut statement = connection.prepare(
+            "SELECT model, success, latency_ms FROM attempts ORDER BY id DESC",
+        )?;
+
+        let rows = statement.query_map([], |row| {
+            Ok((
+                row.get::<_, String>(0)?,
+                row.get::<_, bool>(1)?,
+                row.get::<_, i64>(2)?,
+            ))
+        })?;
+
+        let mut recent: HashMap<String, Vec<(bool, i64)>> = HashMap::new();
+        for row in rows {
+            let (model, success, latency_ms) = row?;
+            let attempts = recent.entry(model).or_default();
+            if attempts.len() < STATS_WINDOW {
+                attempts.push((success, latency_ms));
+            }
+        }
+
+        let stats = recent
+            .into_iter()
+            .map(|(model, attempts)| {
+                let latencies: Vec<i64> = attempts
+                    .iter()
+                    .filter(|(success, _)| *success)
+                    .map(|(_, latency_ms)| *latency_ms)
+                    .collect();
+                let average_latency = (!latencies.is_empty()).then(|| {
+                    let total: i64 = latencies.iter().sum();
+                    Duration::from_millis((total / latencies.len() as i64).max(0) as u64)
+                });
+
+                let stats = ModelStats {
+                    attempts: attempts.len() as u32,
+                    successes: latencies.len() as u32,
+                    average_latency,
+                    last_succeeded: attempts.first().is_some_and(|(success, _)| *success),
+                };
+                (model, stats)
+            })
+            .collect();
+
+        Ok(stats)
+    }
+
     /// Retrieves all transcriptions ordered by most recent first.
     ///
     /// # Errors
@@ -103,7 +425,7 @@ impl HistoryManager {
         let connection = self.get_connection()?;
 
         let mut statement = connection.prepare(
-            "SELECT id, text, created_at FROM transcriptions ORDER BY created_at DESC",
+            "SELECT id, text, created_at, model, details, language, translated, audio_path FROM transcriptions ORDER BY created_at DESC",
         )?;
 
         let entries = statement
@@ -111,6 +433,11 @@ impl HistoryManager {
                 let id = row.get::<_, i64>(0)?;
                 let text = row.get::<_, String>(1)?;
                 let timestamp_str = row.get::<_, String>(2)?;
+                let model = row.get::<_, Option<String>>(3)?;
+                let details = parse_details(row.get::<_, Option<String>>(4)?);
+                let language = row.get::<_, Option<String>>(5)?;
+                let translated = row.get::<_, bool>(6)?;
+                let audio_path = row.get::<_, Option<String>>(7)?.map(PathBuf::from);
 
                 let created_at = DateTime::parse_from_rfc3339(&timestamp_str)
                     .map(|dt| dt.with_timezone(&Local))
@@ -124,6 +451,11 @@ impl HistoryManager {
                     id,
                     text,
                     created_at,
+                    model,
+                    language,
+                    translated,
+                    details,
+                    audio_path,
                 })
             })?
             .collect::<Result<Vec<_>, _>>()?;
@@ -144,13 +476,20 @@ impl HistoryManager {
         let connection = self.get_connection()?;
 
         let mut statement = connection
-            .prepare("SELECT id, text, created_at FROM transcriptions WHERE id = ?1")?;
+            .prepare(
+            "SELECT id, text, created_at, model, details, language, translated, audio_path FROM transcriptions WHERE id = ?1",
+        )?;
 
         let entry = statement
             .query_row(params![id], |row| {
                 let entry_id = row.get::<_, i64>(0)?;
                 let text = row.get::<_, String>(1)?;
                 let timestamp_str = row.get::<_, String>(2)?;
+                let model = row.get::<_, Option<String>>(3)?;
+                let details = parse_details(row.get::<_, Option<String>>(4)?);
+                let language = row.get::<_, Option<String>>(5)?;
+                let translated = row.get::<_, bool>(6)?;
+                let audio_path = row.get::<_, Option<String>>(7)?.map(PathBuf::from);
 
                 let created_at = DateTime::parse_from_rfc3339(&timestamp_str)
                     .map(|dt| dt.with_timezone(&Local))
@@ -164,6 +503,11 @@ impl HistoryManager {
                     id: entry_id,
                     text,
                     created_at,
+                    model,
+                    language,
+                    translated,
+                    details,
+                    audio_path,
                 })
             })
             .optional()?;
@@ -171,3 +515,47 @@ impl HistoryManager {
         Ok(entry)
     }
 }
+
+/// Adds a column to an existing table unless it is already there.
+///
+/// # Errors
+/// - If the table schema cannot be read or altered
+fn ensure_column(connection: &Connection, table: &str, column: &str, kind: &str) -> Result<()> {
+    let exists = connection
+        .prepare(&format!(
+            "SELECT 1 FROM pragma_table_info('{table}') WHERE name = ?1"
+        ))?
+        .exists(params![column])?;
+    if !exists {
+        connection.execute(&format!("ALTER TABLE {table} ADD COLUMN {column} {kind}"), [])?;
+    }
+    Ok(())
+}
+
+/// Parses stored transcription details, ignoring entries written in an unknown format
+fn parse_details(json: Option<String>) -> Option<TranscriptionResponse> {
+    let json = json?;
+    serde_json::from_str(&json)
+        .map_err(|e| tracing::warn!("Ignoring unreadable transcription details: {}", e))
+        .ok()
+}
+
+/// Copies a recording into a directory under a timestamped name.
+///
+/// # Errors
+/// - If the directory cannot be created
+/// - If the recording cannot be copied
+fn copy_recording(recording: &Path, directory: &Path) -> Result<PathBuf> {
+    std::fs::create_dir_all(directory)?;
+    let extension = recording
+        .extension()
+        .map(|ext| ext.to_string_lossy().to_string())
+        .unwrap_or_default();
+    // Timestamped names sort from oldest to newest
+    let copy = directory.join(format!(
+        "{}.{extension}",
+        Local::now().format("%Y%m%d-%H%M%S-%3f")
+    ));
+    std::fs::copy(recording, &copy)?;
+    Ok(copy)
+}
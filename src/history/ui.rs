@@ -1,7 +1,8 @@
 //! Interactive terminal UI for viewing transcription history.
 //!
 //! Provides a scrollable list of transcriptions with keyboard navigation,
-//! mouse support, selection, and clipboard integration.
+//! mouse support, selection, and clipboard integration. Entries with a kept recording can
+//! be sent to a model comparison.
 
 use crate::history::TranscriptionEntry;
 use anyhow::Result;
@@ -17,6 +18,7 @@ use ratatui::{
     widgets::{Block, Borders, HighlightSpacing, List, ListItem, ListState, Padding, Paragraph},
 };
 use std::io::{self, Stdout};
+use std::path::PathBuf;
 use std::time::{Duration, Instant};
 
 const BG: Color = Color::Rgb(0, 0, 0);
@@ -59,7 +61,10 @@ impl HistoryViewer {
     }
 
     /// Runs the interactive history viewer loop.
-    pub fn run(&mut self) -> Result<Option<String>> {
+    ///
+    /// # Returns
+    /// What the user chose to do with an entry, if anything
+    pub fn run(&mut self) -> Result<Option<HistoryAction>> {
         if self.entries.is_empty() {
             self.cleanup()?;
             return Ok(None);
@@ -68,6 +73,7 @@ impl HistoryViewer {
         tracing::info!("History viewer started with {} entries", self.entries.len());
 
         let mut selected_text: Option<String> = None;
+        let mut compare: Option<PathBuf> = None;
 
         loop {
             self.draw()?;
@@ -103,6 +109,10 @@ impl HistoryViewer {
                                     self.notification =
                                         Some(("Copied to clipboard!".to_string(), Instant::now()));
                                 }
+                                InputAction::Compare(path) => {
+                                    compare = Some(path);
+                                    break;
+                                }
                             }
                         }
                     }
@@ -115,7 +125,9 @@ impl HistoryViewer {
         }
 
         self.cleanup()?;
-        Ok(selected_text)
+        Ok(compare
+            .map(HistoryAction::Compare)
+            .or(selected_text.map(HistoryAction::Copy)))
     }
 
     /// Handles keyboard input.
@@ -141,6 +153,20 @@ impl HistoryViewer {
                     None
                 }
             }
+            KeyCode::Char('c') => {
+                let idx = self.list_state.selected()?;
+                match &self.entries[idx].audio_path {
+                    Some(path) if path.exists() => {
+                        tracing::info!("Entry sent to model comparison");
+                        Some(InputAction::Compare(path.clone()))
+                    }
+                    _ => {
+                        self.notification =
+                            Some(("No recording kept for this entry".to_string(), Instant::now()));
+                        None
+                    }
+                }
+            }
             _ => None,
         }
     }
@@ -200,10 +226,19 @@ impl HistoryViewer {
                 .entries
                 .iter()
                 .map(|entry| {
-                    let timestamp = Line::styled(
-                        entry.created_at.format("%Y-%m-%d %H:%M:%S").to_string(),
-                        Style::default().fg(TIMESTAMP_FG),
-                    );
+                    let mut header = entry.created_at.format("%Y-%m-%d %H:%M:%S").to_string();
+                    if let Some(model) = &entry.model {
+                        header.push_str(&format!("  {model}"));
+                    }
+                    match (&entry.language, entry.translated) {
+                        (Some(language), true) if language != "en" => {
+                            header.push_str(&format!("  [{language} → en]"))
+                        }
+                        (_, true) => header.push_str("  [→ en]"),
+                        (Some(language), false) => header.push_str(&format!("  [{language}]")),
+                        (None, false) => {}
+                    }
+                    let timestamp = Line::styled(header, Style::default().fg(TIMESTAMP_FG));
                     let text = Line::styled(entry.text.clone(), Style::default().fg(FG));
                     ListItem::new(vec![timestamp, text])
                 })
@@ -224,7 +259,7 @@ impl HistoryViewer {
             frame.render_stateful_widget(list, list_area, &mut self.list_state);
 
             // Render help footer
-            let help_text = "↑↓ select, ↵ copy, esc/q exit";
+            let help_text = "↑↓ select, ↵ copy, c compare models, esc/q exit";
             let help_paragraph = Paragraph::new(help_text)
                 .alignment(Alignment::Center)
                 .style(Style::default().fg(HELP_FG));
@@ -282,10 +317,20 @@ impl HistoryViewer {
     }
 }
 
+/// What the user chose to do with a history entry.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum HistoryAction {
+    /// Copy the transcription text to the clipboard
+    Copy(String),
+    /// Compare models on the entry's kept recording
+    Compare(PathBuf),
+}
+
 /// Actions that can result from user input.
 enum InputAction {
     Exit,
     Select(String),
+    Compare(PathBuf),
 }
 
 impl Drop for HistoryViewer {
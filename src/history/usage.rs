@@ -0,0 +1,205 @@
+//! Usage accounting: transcription prices, spend summaries and the monthly budget.
+//!
+//! Costs are estimated from the length of the recording and the model's price per audio
+//! minute. Providers bill slightly differently (minimum durations, rounding), so the
+//! figures are an estimate of the invoice rather than a copy of it.
+
+use chrono::{DateTime, Datelike, Local};
+use std::collections::BTreeMap;
+use std::time::Duration;
+
+use super::UsageRecord;
+use crate::config::file::BudgetAction;
+use crate::config::UsageSettings;
+use crate::transcription::TranscriptionModel;
+
+/// Totals over a group of usage records.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct UsageTotals {
+    /// Number of transcriptions
+    pub transcriptions: u32,
+    /// Total length of the audio sent in seconds
+    pub audio_secs: f64,
+    /// Total silence removed before sending, in seconds
+    pub silence_secs: f64,
+    /// Total size of the audio sent to providers
+    pub bytes: u64,
+    /// Sum of the provider latencies
+    pub latency: Duration,
+    /// Total cost in USD of the transcriptions with a known price
+    pub cost: f64,
+    /// Number of transcriptions whose cost is unknown (no price or unknown audio length)
+    pub unpriced: u32,
+}
+
+impl UsageTotals {
+    /// Adds a record to the totals
+    pub fn add(&mut self, record: &UsageRecord) {
+        self.transcriptions += 1;
+        self.audio_secs += record.audio_secs;
+        self.silence_secs += record.silence_secs;
+        self.bytes += record.bytes;
+        self.latency += record.latency;
+        match record.cost {
+            Some(cost) => self.cost += cost,
+            None => self.unpriced += 1,
+        }
+    }
+
+    /// Returns the average provider latency, if there are any transcriptions
+    pub fn average_latency(&self) -> Option<Duration> {
+        (self.transcriptions > 0).then(|| self.latency / self.transcriptions)
+    }
+}
+
+impl UsageRecord {
+    /// Creates a record of a transcription finished just now, priced with the current settings
+    ///
+    /// Audio of unknown length is recorded as 0 seconds with an unknown cost.
+    pub fn now(
+        settings: &UsageSettings,
+        model: &TranscriptionModel,
+        audio_secs: Option<f64>,
+        bytes: u64,
+        latency: Duration,
+    ) -> Self {
+        Self {
+            created_at: Local::now(),
+            model: model.id().to_string(),
+            provider: model.provider_id().to_string(),
+            audio_secs: audio_secs.unwrap_or_default(),
+            silence_secs: 0.0,
+            bytes,
+            latency,
+            cost: audio_secs.and_then(|secs| cost(settings, model, secs)),
+        }
+    }
+
+    /// Sets how much silence was removed from the recording before it was sent
+    pub fn with_silence_secs(mut self, silence_secs: f64) -> Self {
+        self.silence_secs = silence_secs;
+        self
+    }
+}
+
+/// Returns the price in USD per audio minute of a model.
+///
+/// Prices set in `[usage.pricing]` take precedence over the model's list price.
+pub fn price_per_minute(settings: &UsageSettings, model: &TranscriptionModel) -> Option<f64> {
+    settings
+        .pricing
+        .get(model.id())
+        .copied()
+        .or_else(|| model.price_per_minute())
+}
+
+/// Returns the cost in USD of transcribing `audio_secs` seconds with a model, if its
+/// price is known.
+pub fn cost(settings: &UsageSettings, model: &TranscriptionModel, audio_secs: f64) -> Option<f64> {
+    price_per_minute(settings, model).map(|price| price * audio_secs / 60.0)
+}
+
+/// Groups records by a key (e.g. day, month or model) and totals each group.
+pub fn summarize<K: Ord>(
+    records: &[UsageRecord],
+    key: impl Fn(&UsageRecord) -> K,
+) -> BTreeMap<K, UsageTotals> {
+    let mut groups: BTreeMap<K, UsageTotals> = BTreeMap::new();
+    for record in records {
+        groups.entry(key(record)).or_default().add(record);
+    }
+    groups
+}
+
+/// Returns the total cost in USD of the records made in the same calendar month as `now`.
+pub fn month_to_date(records: &[UsageRecord], now: DateTime<Local>) -> f64 {
+    records
+        .iter()
+        .filter(|r| r.created_at.year() == now.year() && r.created_at.month() == now.month())
+        .filter_map(|r| r.cost)
+        .sum()
+}
+
+/// Checks whether a transcription costing `estimate` fits in what is left of the monthly
+/// budget.
+///
+/// Returns a description of the overrun, or `None` if no budget is set or it isn't
+/// exceeded.
+pub fn check_budget(settings: &UsageSettings, spent: f64, estimate: f64) -> Option<String> {
+    let budget = settings.monthly_budget?;
+    if spent + estimate <= budget {
+        return None;
+    }
+
+    let verb = match settings.budget_action {
+        BudgetAction::Warn => "exceeded",
+        BudgetAction::Block => "reached",
+    };
+    Some(format!(
+        "Monthly budget of {} {verb}: {} spent this month, this would cost about {}",
+        format_cost(budget),
+        format_cost(spent),
+        format_cost(estimate)
+    ))
+}
+
+/// Formats a USD amount, with more precision for amounts below a dollar.
+pub fn format_cost(cost: f64) -> String {
+    if cost < 1.0 {
+        format!("${cost:.4}")
+    } else {
+        format!("${cost:.2}")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    fn record(model: &str, month: u32, audio_secs: f64, cost: Option<f64>) -> UsageRecord {
+        UsageRecord {
+            created_at: Local.with_ymd_and_hms(2025, month, 10, 12, 0, 0).unwrap(),
+            model: model.to_string(),
+            provider: "openai".to_string(),
+            audio_secs,
+            silence_secs: 0.0,
+            bytes: 1000,
+            latency: Duration::from_millis(800),
+            cost,
+        }
+    }
+
+    #[test]
+    fn prices_summarizes_and_enforces_budget() {
+        let mut settings = UsageSettings::default();
+        let model = TranscriptionModel::new("whisper", "openai", "whisper-1", "Whisper")
+            .with_price_per_minute(0.006);
+        assert_eq!(cost(&settings, &model, 30.0), Some(0.003));
+        settings.pricing.insert("whisper".to_string(), 0.01);
+        assert_eq!(cost(&settings, &model, 60.0), Some(0.01));
+        let latency = Duration::from_secs(1);
+        assert_eq!(UsageRecord::now(&settings, &model, Some(60.0), 0, latency).cost, Some(0.01));
+        assert_eq!(UsageRecord::now(&settings, &model, None, 0, latency).cost, None);
+
+        let records = vec![
+            record("whisper", 4, 60.0, Some(0.5)),
+            record("whisper", 5, 120.0, Some(1.5)),
+            record("my-model", 5, 30.0, None),
+        ];
+        let by_model = summarize(&records, |r| r.model.clone());
+        assert_eq!(by_model["whisper"].transcriptions, 2);
+        assert_eq!(by_model["whisper"].cost, 2.0);
+        assert_eq!(by_model["my-model"].unpriced, 1);
+        assert_eq!(by_model["whisper"].average_latency(), Some(Duration::from_millis(800)));
+
+        let now = Local.with_ymd_and_hms(2025, 5, 20, 9, 0, 0).unwrap();
+        let spent = month_to_date(&records, now);
+        assert_eq!(spent, 1.5);
+
+        assert_eq!(check_budget(&settings, spent, 0.5), None);
+        settings.monthly_budget = Some(2.0);
+        assert_eq!(check_budget(&settings, spent, 0.4), None);
+        assert!(check_budget(&settings, spent, 0.6).is_some());
+    }
+}
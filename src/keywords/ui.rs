@@ -88,6 +88,8 @@ impl KeywordsViewer {
                         break;
                     }
                 }
+                // Kept as a nested `if` like the key handling above
+                #[allow(clippy::collapsible_match)]
                 Event::Mouse(mouse) => {
                     if !self.input_mode {
                         match mouse.kind {
@@ -8,13 +8,16 @@
 //! - Maintain a searchable history of all transcriptions
 //! - Configure and authenticate with any supported transcription provider
 //! - Select from available models for each provider
+//! - Compare models side by side on the same recording
 //!
 //! Built with Rust for performance and minimal dependencies, ostt provides a command-line
 //! interface for recording, provider authentication, model selection, and history browsing.
 
 pub mod app;
+pub mod bench;
 pub mod clipboard;
 pub mod commands;
+pub mod compare;
 pub mod config;
 pub mod history;
 pub mod keywords;
@@ -1,16 +1,18 @@
-//! Audio recording and format conversion module.
+//! Audio recording module.
 //!
-//! This module handles audio input device management, PCM sample capture, and
-//! format conversion using ffmpeg. Audio is captured from the system's default
-//! input device, converted to mono, and saved in the requested format.
+//! This module handles audio input device management and PCM sample capture. Audio is
+//! captured from the system's default input device, converted to mono, and saved in the
+//! requested format: WAV, FLAC and (with the `opus` feature) Opus are encoded by ostt
+//! itself, other formats with ffmpeg.
 
 use anyhow::{anyhow, Result};
 use cpal::traits::{DeviceTrait, HostTrait, StreamTrait};
-use hound::WavWriter;
-use std::path::{Path, PathBuf};
-use std::process::Command;
+use std::path::PathBuf;
 use std::sync::{Arc, Mutex};
-use super::ffmpeg::find_ffmpeg;
+use tokio::sync::mpsc::UnboundedSender;
+use super::encode::encode;
+use super::vad::compact_silence;
+use crate::config::file::VadConfig;
 
 #[cfg(target_os = "linux")]
 use std::fs::OpenOptions;
@@ -22,7 +24,8 @@ use std::os::unix::io::AsRawFd;
 /// Features:
 /// - Captures from a specified input device or system default at its native sample rate
 /// - Converts multi-channel audio to mono by averaging channels
-/// - Saves audio via ffmpeg for format flexibility
+/// - Saves audio with the built-in WAV, FLAC or Opus encoders, or ffmpeg for other formats
+/// - Optionally removes silence before saving
 /// - Automatic cleanup of temporary files
 /// - Pause and resume support
 pub struct AudioRecorder {
@@ -38,6 +41,12 @@ pub struct AudioRecorder {
     is_paused: Arc<Mutex<bool>>,
     /// Device name or "default" to use the system default device
     device_name: String,
+    /// Optional consumer of captured mono samples (e.g. live streaming transcription)
+    sample_sink: Option<UnboundedSender<Vec<i16>>>,
+    /// Settings for removing silence when the recording stops, if enabled
+    silence_trimming: Option<VadConfig>,
+    /// Number of silent samples removed when the recording stopped
+    silence_removed: usize,
 }
 
 impl AudioRecorder {
@@ -57,9 +66,29 @@ impl AudioRecorder {
             device_channels: 1,
             is_paused: Arc::new(Mutex::new(false)),
             device_name,
+            sample_sink: None,
+            silence_trimming: None,
+            silence_removed: 0,
         }
     }
 
+    /// Forwards every captured chunk of mono samples to the given channel.
+    ///
+    /// Must be called before `start_recording()`. Samples captured while paused are not
+    /// forwarded. The sender is dropped when the recording stops, which closes the channel.
+    pub fn set_sample_sink(&mut self, sink: UnboundedSender<Vec<i16>>) {
+        self.sample_sink = Some(sink);
+    }
+
+    /// Removes silence from the recording when it stops, before it is saved.
+    ///
+    /// Leading and trailing silence is trimmed and long pauses are shortened, as described
+    /// by `trim_silence` and the related settings of `[vad]`. The recorded samples are
+    /// replaced by the shortened ones.
+    pub fn set_silence_trimming(&mut self, config: VadConfig) {
+        self.silence_trimming = Some(config);
+    }
+
     /// Starts recording from the configured input device.
     ///
     /// # Errors
@@ -112,13 +141,19 @@ impl AudioRecorder {
         let samples_arc = Arc::clone(&self.samples);
         let pause_arc = Arc::clone(&self.is_paused);
         let callback_channels = num_channels;
+        let sample_sink = self.sample_sink.take();
 
         let stream = device.build_input_stream(
             &device_config.into(),
             move |data: &[i16], _: &cpal::InputCallbackInfo| {
                 let is_paused = *pause_arc.lock().unwrap();
                 if !is_paused {
-                    Self::handle_audio_callback(data, &samples_arc, callback_channels);
+                    Self::handle_audio_callback(
+                        data,
+                        &samples_arc,
+                        callback_channels,
+                        sample_sink.as_ref(),
+                    );
                 }
             },
             |err| {
@@ -137,29 +172,46 @@ impl AudioRecorder {
 
     /// Stops recording and saves audio to the specified output file.
     ///
-    /// The audio is first saved as a temporary WAV file, then converted to the
-    /// requested format using ffmpeg. The temporary file is cleaned up after conversion.
+    /// WAV, FLAC and (with the `opus` feature) Opus are encoded in-process; other formats
+    /// are written to a temporary WAV file that ffmpeg converts and that is removed
+    /// afterwards. With silence trimming enabled, silence is removed before encoding. A
+    /// recording without samples is not saved.
     ///
     /// # Arguments
     /// * `output_path` - Path where the final encoded audio will be saved
-    /// * `format` - ffmpeg codec and options, e.g., "mp3 -ab 16k -ar 12000"
+    /// * `format` - A built-in format ("wav", "flac", "opus -b:a 24k"), or an ffmpeg codec
+    ///   and options, e.g., "mp3 -ab 16k -ar 12000"
     ///
     /// # Errors
-    /// - If no samples were recorded
+    /// - If built-in encoding fails
     /// - If temporary WAV creation fails
-    /// - If ffmpeg conversion fails
+    /// - If ffmpeg is needed but not installed, or the conversion fails
     pub fn stop_recording(&mut self, output_path: Option<PathBuf>, format: &str) -> Result<()> {
         // Stop the audio stream
         self.stream = None;
 
-        let samples = self.samples.lock().unwrap().clone();
-        let sample_count = samples.len();
+        let mut samples = self.samples.lock().unwrap().clone();
 
-        if sample_count == 0 {
+        if samples.is_empty() {
             tracing::warn!("Recording stopped with no samples captured");
             return Ok(());
         }
 
+        if let Some(config) = &self.silence_trimming {
+            let compacted = compact_silence(&samples, self.sample_rate, config);
+            self.silence_removed = samples.len() - compacted.len();
+            if self.silence_removed > 0 {
+                tracing::info!(
+                    "Removed {:.2}s of silence ({:.0}% of the recording)",
+                    self.silence_removed_secs(),
+                    self.silence_removed as f64 / samples.len() as f64 * 100.0
+                );
+                *self.samples.lock().unwrap() = compacted.clone();
+                samples = compacted;
+            }
+        }
+        let sample_count = samples.len();
+
         // Calculate and log recording duration
         let duration_secs = sample_count as f32 / self.sample_rate as f32;
         tracing::info!(
@@ -171,15 +223,7 @@ impl AudioRecorder {
 
         // Save and convert to desired format
         if let Some(output_file) = output_path {
-            let temp_wav = self.create_temp_wav_path();
-
-            self.save_wav(&samples, &temp_wav)?;
-            self.convert_with_ffmpeg(&temp_wav, &output_file, format)?;
-
-            // Clean up temporary file
-            if let Err(e) = std::fs::remove_file(&temp_wav) {
-                tracing::debug!("Failed to remove temp file: {}", e);
-            }
+            encode(&samples, self.sample_rate, &output_file, format)?;
 
             // Log final file info
             let file_size = std::fs::metadata(&output_file)?.len();
@@ -196,13 +240,16 @@ impl AudioRecorder {
 
     /// Handles incoming audio data from the audio callback.
     ///
-    /// Converts multi-channel audio to mono by averaging all channels.
+    /// Converts multi-channel audio to mono by averaging all channels, appends it to the
+    /// recording and forwards it to the sample sink, if any.
     fn handle_audio_callback(
         data: &[i16],
         samples_arc: &Arc<Mutex<Vec<i16>>>,
         num_channels: usize,
+        sample_sink: Option<&UnboundedSender<Vec<i16>>>,
     ) {
         let mut samples = samples_arc.lock().unwrap();
+        let start = samples.len();
 
         match num_channels {
             1 => {
@@ -227,93 +274,12 @@ impl AudioRecorder {
                 }
             }
         }
-    }
 
-    /// Saves audio samples as a temporary WAV file.
-    ///
-    /// This creates an uncompressed PCM WAV intermediate file that will be
-    /// converted to the desired format by ffmpeg.
-    fn save_wav(&self, samples: &[i16], path: &Path) -> Result<()> {
-        let wav_spec = hound::WavSpec {
-            channels: 1,
-            sample_rate: self.sample_rate,
-            bits_per_sample: 16,
-            sample_format: hound::SampleFormat::Int,
-        };
-
-        let mut writer = WavWriter::create(path, wav_spec)?;
-
-        for &sample in samples {
-            writer.write_sample(sample)?;
+        // Only a sink needs its own copy of the new samples
+        if let Some(sink) = sample_sink {
+            // The receiver going away (e.g. a failed stream) must not affect the recording
+            let _ = sink.send(samples[start..].to_vec());
         }
-
-        writer.finalize()?;
-        tracing::debug!("Temporary WAV created: {}", path.display());
-        Ok(())
-    }
-
-    /// Converts audio using ffmpeg based on format string.
-    ///
-    /// # Arguments
-    /// * `input_wav` - Path to temporary WAV file
-    /// * `output_path` - Final output file path
-    /// * `format` - Format string: "codec [options]", e.g., "mp3 -ab 16k -ar 12000"
-    ///
-    /// The format string is parsed to extract the codec and any additional ffmpeg
-    /// arguments. Mono conversion is always enforced.
-    fn convert_with_ffmpeg(
-        &self,
-        input_wav: &Path,
-        output_path: &Path,
-        format: &str,
-    ) -> Result<()> {
-        // Parse codec and additional options from format string
-        let format_parts: Vec<&str> = format.split_whitespace().collect();
-
-        if format_parts.is_empty() {
-            return Err(anyhow!("Invalid format string: empty"));
-        }
-
-        let codec = format_parts[0];
-
-        // Find ffmpeg binary with cross-platform support
-        let ffmpeg_path = find_ffmpeg()?;
-
-        // Build ffmpeg command
-        let mut cmd = Command::new(&ffmpeg_path);
-        cmd.arg("-loglevel")
-            .arg("error")
-            .arg("-i")
-            .arg(input_wav)
-            .arg("-acodec")
-            .arg(codec)
-            .arg("-ac")
-            .arg("1") // Force mono
-            .arg("-y"); // Overwrite output
-
-        // Add any additional ffmpeg options from format string
-        for option in &format_parts[1..] {
-            cmd.arg(option);
-        }
-
-        cmd.arg(output_path);
-
-        // Execute ffmpeg
-        let output = cmd.output()?;
-
-        if output.status.success() {
-            tracing::debug!("Audio converted to {} format", codec);
-            Ok(())
-        } else {
-            let error_msg = String::from_utf8_lossy(&output.stderr);
-            tracing::error!("ffmpeg conversion failed: {}", error_msg);
-            Err(anyhow!("Audio encoding failed: {error_msg}"))
-        }
-    }
-
-    /// Creates a path for the temporary WAV file.
-    fn create_temp_wav_path(&self) -> PathBuf {
-        std::env::temp_dir().join(format!("ostt_{}.wav", std::process::id()))
     }
 
     // Getters for recorded data
@@ -328,6 +294,11 @@ impl AudioRecorder {
         self.samples.lock().unwrap().len()
     }
 
+    /// Returns how many seconds of silence were removed when the recording stopped.
+    pub fn silence_removed_secs(&self) -> f64 {
+        self.silence_removed as f64 / self.sample_rate as f64
+    }
+
     /// Returns the actual sample rate of the recording.
     pub fn sample_rate(&self) -> u32 {
         self.sample_rate
@@ -0,0 +1,392 @@
+//! Audio encoding.
+//!
+//! Writes 16-bit mono PCM samples to disk. WAV, FLAC and (with the `opus` feature) Ogg
+//! Opus are encoded in-process; any other `output_format` goes through ffmpeg. Used for
+//! the full recording as well as for the chunks of long recordings that are split before
+//! upload, and to transcode recordings for models that don't accept the recorded format.
+//! The WAV reader and the resampler here are shared by everything that needs raw samples.
+
+use anyhow::{anyhow, Result};
+use hound::WavWriter;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+use super::ffmpeg::find_ffmpeg;
+use super::flac::write_flac;
+#[cfg(feature = "opus")]
+use super::opus::write_opus;
+
+/// Zero crossings of the resampling filter on each side of its centre
+const RESAMPLE_ZERO_CROSSINGS: f64 = 8.0;
+
+/// Bitrate of the built-in Opus encoder if `output_format` doesn't set one
+#[cfg(feature = "opus")]
+const DEFAULT_OPUS_BITRATE: u32 = 24_000;
+
+/// An encoder built into ostt.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Builtin {
+    Wav,
+    Flac,
+    #[cfg(feature = "opus")]
+    Opus {
+        /// Bits per second
+        bitrate: u32,
+    },
+}
+
+impl Builtin {
+    /// Returns the built-in encoder of an `output_format` setting, if it has one.
+    ///
+    /// `wav`, `pcm_s16le` and `flac` without options and `opus` with an optional
+    /// `-b:a <bitrate>` are built in; anything else, such as `flac -ar 16000`, is passed to
+    /// ffmpeg.
+    fn from_output_format(format: &str) -> Option<Self> {
+        let parts: Vec<&str> = format.split_whitespace().collect();
+        match parts.as_slice() {
+            ["wav"] | ["pcm_s16le"] => Some(Builtin::Wav),
+            ["flac"] => Some(Builtin::Flac),
+            #[cfg(feature = "opus")]
+            ["opus"] => Some(Builtin::Opus {
+                bitrate: DEFAULT_OPUS_BITRATE,
+            }),
+            #[cfg(feature = "opus")]
+            ["opus", "-b:a", bitrate] => {
+                parse_bitrate(bitrate).map(|bitrate| Builtin::Opus { bitrate })
+            }
+            _ => None,
+        }
+    }
+
+    /// Encodes mono samples into `output_path`
+    fn encode(self, samples: &[i16], sample_rate: u32, output_path: &Path) -> Result<()> {
+        match self {
+            Builtin::Wav => write_wav(samples, sample_rate, output_path),
+            Builtin::Flac => write_flac(samples, sample_rate, output_path),
+            #[cfg(feature = "opus")]
+            Builtin::Opus { bitrate } => write_opus(samples, sample_rate, bitrate, output_path),
+        }
+    }
+}
+
+/// Returns whether an `output_format` setting is encoded with ffmpeg.
+pub fn needs_ffmpeg(format: &str) -> bool {
+    Builtin::from_output_format(format).is_none()
+}
+
+/// Encodes mono samples into `output_path` using the given format.
+///
+/// # Arguments
+/// * `samples` - 16-bit mono PCM samples
+/// * `sample_rate` - Sample rate of `samples`
+/// * `output_path` - Path where the encoded audio will be saved
+/// * `format` - A built-in format ("wav", "flac", "opus -b:a 24k"), or an ffmpeg codec
+///   and options, e.g., "mp3 -ab 16k -ar 12000"
+///
+/// # Errors
+/// - If built-in encoding fails
+/// - If temporary WAV creation fails
+/// - If ffmpeg conversion fails
+pub fn encode(samples: &[i16], sample_rate: u32, output_path: &Path, format: &str) -> Result<()> {
+    if let Some(builtin) = Builtin::from_output_format(format) {
+        return builtin.encode(samples, sample_rate, output_path);
+    }
+
+    let temp_wav = temp_wav_path(output_path);
+    write_wav(samples, sample_rate, &temp_wav)?;
+    let result = convert_with_ffmpeg(&temp_wav, output_path, format);
+
+    // Clean up temporary file
+    if let Err(e) = std::fs::remove_file(&temp_wav) {
+        tracing::debug!("Failed to remove temp file: {}", e);
+    }
+
+    result
+}
+
+/// Converts an encoded audio file to another format.
+///
+/// WAV files going to a built-in format are converted in-process; everything else is
+/// converted with ffmpeg.
+///
+/// # Arguments
+/// * `input` - Encoded audio file in any format ffmpeg can read
+/// * `output_path` - Path where the converted audio will be saved
+/// * `format` - Built-in format or ffmpeg codec and options, e.g., "mp3 -ab 16k", or "wav"
+///
+/// # Errors
+/// - If built-in encoding fails
+/// - If ffmpeg is needed but not installed, or the conversion fails
+pub fn transcode(input: &Path, output_path: &Path, format: &str) -> Result<()> {
+    if let Some(builtin) = Builtin::from_output_format(format) {
+        if let Some((samples, sample_rate)) = read_wav(input) {
+            return builtin.encode(&samples, sample_rate, output_path);
+        }
+    }
+    let format = if format.trim() == "wav" { "pcm_s16le" } else { format };
+    convert_with_ffmpeg(input, output_path, format)
+}
+
+/// Saves audio samples as an uncompressed 16-bit PCM WAV file.
+///
+/// # Errors
+/// - If the file cannot be created or written
+pub fn write_wav(samples: &[i16], sample_rate: u32, path: &Path) -> Result<()> {
+    let wav_spec = hound::WavSpec {
+        channels: 1,
+        sample_rate,
+        bits_per_sample: 16,
+        sample_format: hound::SampleFormat::Int,
+    };
+
+    let mut writer = WavWriter::create(path, wav_spec)?;
+
+    for &sample in samples {
+        writer.write_sample(sample)?;
+    }
+
+    writer.finalize()?;
+    tracing::debug!("WAV written: {}", path.display());
+    Ok(())
+}
+
+/// Reads a WAV file as 16-bit mono samples, averaging channels.
+///
+/// Returns `None` for files that aren't readable WAV files.
+pub fn read_wav(path: &Path) -> Option<(Vec<i16>, u32)> {
+    let mut reader = hound::WavReader::open(path).ok()?;
+    let spec = reader.spec();
+    let channels = spec.channels.max(1) as usize;
+
+    let interleaved: Vec<f32> = match spec.sample_format {
+        hound::SampleFormat::Int => {
+            let scale = (1i64 << (spec.bits_per_sample - 1)) as f32;
+            reader
+                .samples::<i32>()
+                .map(|s| s.map(|v| v as f32 / scale))
+                .collect::<Result<_, _>>()
+                .ok()?
+        }
+        hound::SampleFormat::Float => reader.samples::<f32>().collect::<Result<_, _>>().ok()?,
+    };
+
+    let mono = interleaved
+        .chunks_exact(channels)
+        .map(|frame| {
+            let average = frame.iter().sum::<f32>() / channels as f32;
+            (average * i16::MAX as f32).clamp(i16::MIN as f32, i16::MAX as f32) as i16
+        })
+        .collect();
+    Some((mono, spec.sample_rate))
+}
+
+/// Reads an audio file as 16-bit mono samples.
+///
+/// WAV files are read in-process; other formats are decoded with ffmpeg.
+///
+/// # Errors
+/// - If the file isn't WAV and ffmpeg is not installed or can't decode it
+pub fn read_audio(path: &Path) -> Result<(Vec<i16>, u32)> {
+    if let Some(audio) = read_wav(path) {
+        return Ok(audio);
+    }
+
+    let temp_wav = temp_wav_path(path);
+    let result = convert_with_ffmpeg(path, &temp_wav, "pcm_s16le").and_then(|()| {
+        read_wav(&temp_wav).ok_or_else(|| anyhow!("Failed to read decoded {}", path.display()))
+    });
+    if let Err(e) = std::fs::remove_file(&temp_wav) {
+        tracing::debug!("Failed to remove temp file: {}", e);
+    }
+    result
+}
+
+/// Resamples mono samples from one sample rate to another.
+///
+/// Uses windowed-sinc interpolation with the filter cut off at the lower of the two Nyquist
+/// frequencies, so that downsampling doesn't fold frequencies the new rate can't hold back
+/// into the speech band.
+pub fn resample(samples: &[i16], from: u32, to: u32) -> Vec<i16> {
+    if from == to || samples.is_empty() {
+        return samples.to_vec();
+    }
+
+    let step = from as f64 / to as f64;
+    let cutoff = (to as f64 / from as f64).min(1.0);
+    let half_width = RESAMPLE_ZERO_CROSSINGS / cutoff;
+    let len = (samples.len() as u64 * to as u64 / from as u64) as usize;
+    (0..len)
+        .map(|i| {
+            let position = i as f64 * step;
+            let first = (position - half_width).ceil().max(0.0) as usize;
+            let last = ((position + half_width).floor() as usize).min(samples.len() - 1);
+            let (sum, weights) = samples[first..=last].iter().enumerate().fold(
+                (0.0, 0.0),
+                |(sum, weights), (offset, &sample)| {
+                    let distance = (first + offset) as f64 - position;
+                    let weight = sinc(distance * cutoff) * hann(distance / half_width);
+                    (sum + sample as f64 * weight, weights + weight)
+                },
+            );
+            // Dividing by the weights keeps the gain at 1, also where the filter is cut
+            // short at the ends of the recording
+            let value = if weights > 0.0 { sum / weights } else { 0.0 };
+            value.round().clamp(i16::MIN as f64, i16::MAX as f64) as i16
+        })
+        .collect()
+}
+
+/// Normalized sinc function
+fn sinc(x: f64) -> f64 {
+    if x == 0.0 {
+        1.0
+    } else {
+        (std::f64::consts::PI * x).sin() / (std::f64::consts::PI * x)
+    }
+}
+
+/// Hann window over -1..1
+fn hann(x: f64) -> f64 {
+    if x.abs() >= 1.0 {
+        0.0
+    } else {
+        0.5 * (1.0 + (std::f64::consts::PI * x).cos())
+    }
+}
+
+/// Parses an ffmpeg-style bitrate such as "24k" or "24000" into bits per second
+#[cfg(feature = "opus")]
+fn parse_bitrate(bitrate: &str) -> Option<u32> {
+    match bitrate.strip_suffix(['k', 'K']) {
+        Some(kilobits) => kilobits.parse::<u32>().ok()?.checked_mul(1000),
+        None => bitrate.parse().ok(),
+    }
+}
+
+/// Converts audio using ffmpeg based on format string.
+///
+/// # Arguments
+/// * `input` - Path to the source audio, usually a temporary WAV file
+/// * `output_path` - Final output file path
+/// * `format` - Format string: "codec [options]", e.g., "mp3 -ab 16k -ar 12000"
+///
+/// The format string is parsed to extract the codec and any additional ffmpeg
+/// arguments. Mono conversion is always enforced.
+fn convert_with_ffmpeg(input: &Path, output_path: &Path, format: &str) -> Result<()> {
+    // Parse codec and additional options from format string
+    let format_parts: Vec<&str> = format.split_whitespace().collect();
+
+    if format_parts.is_empty() {
+        return Err(anyhow!("Invalid format string: empty"));
+    }
+
+    // ffmpeg's own Opus encoder is experimental; "opus" means libopus as for the built-in one
+    let codec = match format_parts[0] {
+        "opus" => "libopus",
+        codec => codec,
+    };
+
+    // Find ffmpeg binary with cross-platform support
+    let ffmpeg_path = find_ffmpeg()?;
+
+    // Build ffmpeg command
+    let mut cmd = Command::new(&ffmpeg_path);
+    cmd.arg("-loglevel")
+        .arg("error")
+        .arg("-i")
+        .arg(input)
+        .arg("-acodec")
+        .arg(codec)
+        .arg("-ac")
+        .arg("1") // Force mono
+        .arg("-y"); // Overwrite output
+
+    // Add any additional ffmpeg options from format string
+    for option in &format_parts[1..] {
+        cmd.arg(option);
+    }
+
+    cmd.arg(output_path);
+
+    // Execute ffmpeg
+    let output = cmd.output()?;
+
+    if output.status.success() {
+        tracing::debug!("Audio converted to {} format", codec);
+        Ok(())
+    } else {
+        let error_msg = String::from_utf8_lossy(&output.stderr);
+        tracing::error!("ffmpeg conversion failed: {}", error_msg);
+        Err(anyhow!("Audio encoding failed: {error_msg}"))
+    }
+}
+
+/// Creates a path for the intermediate WAV file of an encoding, unique per output file.
+fn temp_wav_path(output_path: &Path) -> PathBuf {
+    let stem = output_path
+        .file_stem()
+        .map(|stem| stem.to_string_lossy().to_string())
+        .unwrap_or_default();
+    std::env::temp_dir().join(format!("ostt_{}_{stem}.wav", std::process::id()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn encodes_builtin_formats_without_ffmpeg() {
+        assert!(!needs_ffmpeg("wav"));
+        assert!(!needs_ffmpeg("flac"));
+        assert!(needs_ffmpeg("flac -ar 16000"));
+        assert!(needs_ffmpeg("mp3 -ab 16k"));
+
+        let dir = std::env::temp_dir().join(format!("ostt-encode-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let samples: Vec<i16> =
+            (0..8000).map(|i| ((i as f64 * 0.1).sin() * 8000.0) as i16).collect();
+        let wav = dir.join("in.wav");
+        encode(&samples, 16000, &wav, "wav").unwrap();
+        let (read, sample_rate) = read_wav(&wav).unwrap();
+        assert_eq!((read.len(), sample_rate), (samples.len(), 16000));
+
+        let flac = dir.join("out.flac");
+        transcode(&wav, &flac, "flac").unwrap();
+        assert_eq!(&std::fs::read(&flac).unwrap()[..4], b"fLaC");
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn resamples_without_aliasing() {
+        let sine = |frequency: f64, rate: u32, secs: f64| -> Vec<i16> {
+            let len = (rate as f64 * secs) as usize;
+            (0..len)
+                .map(|i| {
+                    let t = i as f64 / rate as f64;
+                    ((2.0 * std::f64::consts::PI * frequency * t).sin() * 10000.0) as i16
+                })
+                .collect()
+        };
+        // Root mean square away from the ends, where the filter is cut short
+        let rms = |samples: &[i16]| {
+            let middle = &samples[samples.len() / 4..samples.len() * 3 / 4];
+            let power = middle.iter().map(|&s| (s as f64).powi(2)).sum::<f64>();
+            (power / middle.len() as f64).sqrt()
+        };
+
+        assert_eq!(resample(&[1, 2, 3], 16000, 16000), [1, 2, 3]);
+        assert!(resample(&[5000; 4410], 44100, 16000).iter().all(|&s| s == 5000));
+
+        // Speech frequencies keep their level, whether going down or up
+        let speech = resample(&sine(1000.0, 48000, 1.0), 48000, 16000);
+        assert_eq!(speech.len(), 16000);
+        assert!((rms(&speech) / 7071.0 - 1.0).abs() < 0.02);
+        let upsampled = resample(&sine(1000.0, 44100, 1.0), 44100, 48000);
+        assert_eq!(upsampled.len(), 48000);
+        assert!((rms(&upsampled) / 7071.0 - 1.0).abs() < 0.02);
+
+        // 12 kHz can't be held at 16 kHz and mustn't come back as a 4 kHz tone
+        let aliased = resample(&sine(12000.0, 48000, 1.0), 48000, 16000);
+        assert!(rms(&aliased) < 100.0);
+    }
+}
@@ -0,0 +1,152 @@
+//! Audio file formats.
+//!
+//! `output_format` in `[audio]` is an ffmpeg codec followed by options ("mp3 -ab 16k").
+//! `AudioFormat` is the container that codec produces: it determines the file extension of
+//! the recording and the MIME type it is uploaded with, and lets models declare which
+//! formats their API accepts. The formats in [`AudioFormat::BUILTIN`] are encoded without
+//! ffmpeg.
+
+use anyhow::{anyhow, Result};
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::path::Path;
+
+/// A container format a recording can be encoded to.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum AudioFormat {
+    /// MPEG-1 Layer III
+    Mp3,
+    /// Opus in an Ogg container
+    Opus,
+    /// Vorbis in an Ogg container
+    Vorbis,
+    /// Free Lossless Audio Codec
+    Flac,
+    /// AAC in an MPEG-4 container
+    Aac,
+    /// Uncompressed 16-bit PCM
+    Wav,
+}
+
+impl AudioFormat {
+    /// Every format ostt can encode to
+    pub const ALL: [AudioFormat; 6] = [
+        AudioFormat::Mp3,
+        AudioFormat::Opus,
+        AudioFormat::Vorbis,
+        AudioFormat::Flac,
+        AudioFormat::Aac,
+        AudioFormat::Wav,
+    ];
+
+    /// Formats ostt encodes without ffmpeg, smallest first
+    #[cfg(feature = "opus")]
+    pub const BUILTIN: &'static [AudioFormat] =
+        &[AudioFormat::Opus, AudioFormat::Flac, AudioFormat::Wav];
+    /// Formats ostt encodes without ffmpeg, smallest first
+    #[cfg(not(feature = "opus"))]
+    pub const BUILTIN: &'static [AudioFormat] = &[AudioFormat::Flac, AudioFormat::Wav];
+
+    /// Determines the format produced by an `output_format` setting from its codec.
+    ///
+    /// # Errors
+    /// - If the setting is empty or names a codec ostt doesn't know the container of
+    pub fn from_output_format(output_format: &str) -> Result<Self> {
+        let codec = output_format.split_whitespace().next().unwrap_or_default();
+        match codec {
+            "mp3" | "libmp3lame" => Ok(AudioFormat::Mp3),
+            "libopus" | "opus" => Ok(AudioFormat::Opus),
+            "libvorbis" | "vorbis" => Ok(AudioFormat::Vorbis),
+            "flac" => Ok(AudioFormat::Flac),
+            "aac" => Ok(AudioFormat::Aac),
+            "wav" | "pcm_s16le" => Ok(AudioFormat::Wav),
+            "" => Err(anyhow!("Invalid output format: empty")),
+            codec => Err(anyhow!(
+                "Unsupported output format codec '{codec}'. Use one of: mp3, opus, libopus, libvorbis, flac, aac, pcm_s16le or wav."
+            )),
+        }
+    }
+
+    /// Determines the format of an audio file from its extension, if it is a known one
+    pub fn from_path(path: &Path) -> Option<Self> {
+        let extension = path.extension()?.to_str()?.to_ascii_lowercase();
+        match extension.as_str() {
+            "mp3" => Some(AudioFormat::Mp3),
+            // Ogg files are assumed to be Opus, which is what ostt writes by default for Ogg
+            "ogg" | "opus" => Some(AudioFormat::Opus),
+            "flac" => Some(AudioFormat::Flac),
+            "m4a" => Some(AudioFormat::Aac),
+            "wav" => Some(AudioFormat::Wav),
+            _ => None,
+        }
+    }
+
+    /// File extension of the format, without the dot
+    pub fn extension(self) -> &'static str {
+        match self {
+            AudioFormat::Mp3 => "mp3",
+            AudioFormat::Opus | AudioFormat::Vorbis => "ogg",
+            AudioFormat::Flac => "flac",
+            AudioFormat::Aac => "m4a",
+            AudioFormat::Wav => "wav",
+        }
+    }
+
+    /// MIME type the format is uploaded with
+    pub fn mime_type(self) -> &'static str {
+        match self {
+            AudioFormat::Mp3 => "audio/mpeg",
+            AudioFormat::Opus | AudioFormat::Vorbis => "audio/ogg",
+            AudioFormat::Flac => "audio/flac",
+            AudioFormat::Aac => "audio/mp4",
+            AudioFormat::Wav => "audio/wav",
+        }
+    }
+
+    /// `output_format` setting used when a recording has to be transcoded to this format
+    ///
+    /// FLAC, WAV and (with the `opus` feature) Opus use the built-in encoders.
+    pub fn default_encoding(self) -> &'static str {
+        match self {
+            AudioFormat::Mp3 => "mp3 -ab 32k",
+            AudioFormat::Opus => "opus -b:a 24k",
+            AudioFormat::Vorbis => "libvorbis -q:a 4",
+            AudioFormat::Flac => "flac",
+            AudioFormat::Aac => "aac -b:a 48k",
+            AudioFormat::Wav => "wav",
+        }
+    }
+}
+
+impl fmt::Display for AudioFormat {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            AudioFormat::Mp3 => "mp3",
+            AudioFormat::Opus => "opus",
+            AudioFormat::Vorbis => "vorbis",
+            AudioFormat::Flac => "flac",
+            AudioFormat::Aac => "aac",
+            AudioFormat::Wav => "wav",
+        };
+        f.write_str(name)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn maps_codecs_to_containers() {
+        let opus = AudioFormat::from_output_format("libopus -b:a 24k").unwrap();
+        assert_eq!((opus.extension(), opus.mime_type()), ("ogg", "audio/ogg"));
+        let wav = AudioFormat::from_output_format("pcm_s16le").unwrap();
+        assert_eq!((wav.extension(), wav.mime_type()), ("wav", "audio/wav"));
+        assert_eq!(AudioFormat::from_output_format("mp3 -ab 16k").unwrap(), AudioFormat::Mp3);
+        assert!(AudioFormat::from_output_format("mp4a").is_err());
+
+        assert_eq!(AudioFormat::from_path(Path::new("/tmp/a.M4A")), Some(AudioFormat::Aac));
+        assert_eq!(AudioFormat::from_path(Path::new("/tmp/a.webm")), None);
+    }
+}
@@ -4,9 +4,18 @@
 //! for the recording workflow.
 
 pub mod audio;
+pub mod encode;
 pub mod ffmpeg;
+pub mod flac;
+#[cfg(feature = "opus")]
+pub mod opus;
+pub mod format;
 pub mod ui;
+pub mod vad;
 
 pub use audio::AudioRecorder;
+pub use encode::encode;
 pub use ffmpeg::find_ffmpeg;
+pub use format::AudioFormat;
 pub use ui::{RecordingCommand, OsttTui};
+pub use vad::{VadState, VoiceActivityDetector};
@@ -0,0 +1,128 @@
+//! Built-in Opus encoder (`opus` cargo feature).
+//!
+//! Encodes 16-bit mono PCM with libopus, which is compiled into ostt, and stores the
+//! packets in an Ogg container as described in RFC 7845, so that Opus recordings need no
+//! ffmpeg. Opus takes 8, 12, 16, 24 or 48kHz input; recordings at other sample rates
+//! (such as 44.1kHz) are resampled to 48kHz first, which keeps their full bandwidth.
+
+use anyhow::{anyhow, Result};
+use audiopus::coder::Encoder;
+use audiopus::{Application, Bitrate, Channels, SampleRate};
+use ogg::writing::{PacketWriteEndInfo, PacketWriter};
+use std::borrow::Cow;
+use std::io::Write;
+use std::path::Path;
+
+use super::encode::resample;
+
+/// Length of an Opus frame in milliseconds
+const FRAME_MS: u32 = 20;
+
+/// Sample rate recordings are resampled to if Opus doesn't take theirs
+const FALLBACK_RATE: u32 = 48000;
+
+/// Rate of Ogg Opus granule positions, whatever the input rate
+const GRANULE_RATE: u64 = 48000;
+
+/// Largest packet libopus produces
+const MAX_PACKET_BYTES: usize = 4000;
+
+/// Serial number of the Ogg stream (there is only one)
+const STREAM_SERIAL: u32 = 0x6f73_7474;
+
+/// Encodes mono samples as an Ogg Opus file.
+///
+/// # Arguments
+/// * `samples` - 16-bit mono PCM samples
+/// * `sample_rate` - Sample rate of `samples`
+/// * `bitrate` - Target bitrate in bits per second
+/// * `path` - Path where the encoded audio will be saved
+///
+/// # Errors
+/// - If the encoder rejects the settings or fails
+/// - If the file cannot be created or written
+pub fn write_opus(samples: &[i16], sample_rate: u32, bitrate: u32, path: &Path) -> Result<()> {
+    let (samples, sample_rate) = match SampleRate::try_from(sample_rate as i32) {
+        Ok(_) => (Cow::Borrowed(samples), sample_rate),
+        Err(_) => {
+            tracing::debug!("Resampling {}Hz to {}Hz for Opus", sample_rate, FALLBACK_RATE);
+            (Cow::Owned(resample(samples, sample_rate, FALLBACK_RATE)), FALLBACK_RATE)
+        }
+    };
+    let opus_rate = SampleRate::try_from(sample_rate as i32)
+        .map_err(|e| anyhow!("Unsupported Opus sample rate: {e}"))?;
+
+    let mut encoder = Encoder::new(opus_rate, Channels::Mono, Application::Voip)
+        .map_err(|e| anyhow!("Failed to create Opus encoder: {e}"))?;
+    encoder
+        .set_bitrate(Bitrate::BitsPerSecond(bitrate as i32))
+        .map_err(|e| anyhow!("Invalid Opus bitrate {bitrate}: {e}"))?;
+    let granules_per_sample = GRANULE_RATE / sample_rate as u64;
+    let pre_skip = encoder.lookahead().unwrap_or(0) as u64 * granules_per_sample;
+
+    let file = std::io::BufWriter::new(std::fs::File::create(path)?);
+    let mut writer = PacketWriter::new(file);
+    writer.write_packet(
+        opus_head(pre_skip as u16, sample_rate).into_boxed_slice(),
+        STREAM_SERIAL,
+        PacketWriteEndInfo::EndPage,
+        0,
+    )?;
+    writer.write_packet(
+        opus_tags().into_boxed_slice(),
+        STREAM_SERIAL,
+        PacketWriteEndInfo::EndPage,
+        0,
+    )?;
+
+    // The last frame is padded with silence; its granule position tells players where the
+    // recording really ends
+    let frame_len = (sample_rate * FRAME_MS / 1000) as usize;
+    let end_granule = pre_skip + samples.len() as u64 * granules_per_sample;
+    let frames = samples.len().div_ceil(frame_len).max(1);
+    let mut packet = vec![0u8; MAX_PACKET_BYTES];
+    for index in 0..frames {
+        let start = (index * frame_len).min(samples.len());
+        let end = (start + frame_len).min(samples.len());
+        let mut frame = samples[start..end].to_vec();
+        frame.resize(frame_len, 0);
+
+        let len = encoder
+            .encode(&frame, &mut packet)
+            .map_err(|e| anyhow!("Opus encoding failed: {e}"))?;
+        let granule = (pre_skip + ((index + 1) * frame_len) as u64 * granules_per_sample)
+            .min(end_granule);
+        let end_info = if index + 1 == frames {
+            PacketWriteEndInfo::EndStream
+        } else {
+            PacketWriteEndInfo::NormalPacket
+        };
+        writer.write_packet(packet[..len].into(), STREAM_SERIAL, end_info, granule)?;
+    }
+
+    writer.into_inner().flush()?;
+    tracing::debug!("Opus written: {}", path.display());
+    Ok(())
+}
+
+/// Identification header of an Ogg Opus stream (RFC 7845, section 5.1)
+fn opus_head(pre_skip: u16, input_rate: u32) -> Vec<u8> {
+    let mut head = b"OpusHead".to_vec();
+    head.push(1); // Version
+    head.push(1); // Channels
+    head.extend_from_slice(&pre_skip.to_le_bytes());
+    head.extend_from_slice(&input_rate.to_le_bytes());
+    head.extend_from_slice(&0i16.to_le_bytes()); // Output gain
+    head.push(0); // Channel mapping family: mono or stereo
+    head
+}
+
+/// Comment header of an Ogg Opus stream, naming ostt as the encoder (RFC 7845, section 5.2)
+fn opus_tags() -> Vec<u8> {
+    let vendor = concat!("ostt ", env!("CARGO_PKG_VERSION"));
+    let mut tags = b"OpusTags".to_vec();
+    tags.extend_from_slice(&(vendor.len() as u32).to_le_bytes());
+    tags.extend_from_slice(vendor.as_bytes());
+    tags.extend_from_slice(&0u32.to_le_bytes()); // No user comments
+    tags
+}
@@ -11,12 +11,16 @@ use crossterm::{
 use ratatui::{
     prelude::*,
     style::{Color, Style},
-    widgets::Sparkline,
+    widgets::{Paragraph, Sparkline, Wrap},
 };
 use std::error::Error;
 use std::io::{stdout, Stdout};
 
-use crate::transcription::TranscriptionAnimation;
+use super::VadState;
+use crate::transcription::{LiveTranscript, TranscriptionAnimation};
+
+/// Number of lines reserved under the sparkline for the live transcript
+const LIVE_TRANSCRIPT_HEIGHT: u16 = 3;
 
 /// User input command during recording.
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
@@ -29,6 +33,8 @@ pub enum RecordingCommand {
     Cancel,
     /// Pause/resume recording (Space key)
     TogglePause,
+    /// Switch between transcription and translation to English ('t' key)
+    ToggleTranslate,
 }
 
 /// Terminal UI for audio recording with waveform visualization.
@@ -49,10 +55,18 @@ pub struct OsttTui {
     reference_level_db: i8,
     /// Whether recording is currently paused
     pub is_paused: bool,
+    /// Whether the recording will be translated to English instead of transcribed
+    pub translate: bool,
+    /// State of the voice activity detector, if the recording stops on silence
+    pub vad_state: Option<VadState>,
+    /// Length after which the recording stops, if limited
+    pub max_duration: Option<std::time::Duration>,
     /// Total time paused (accumulated when paused)
     pause_duration: std::time::Duration,
     /// When pause started (for calculating pause duration)
     pause_start_time: Option<std::time::Instant>,
+    /// Transcript of an active live streaming session, shown under the sparkline
+    live_transcript: Option<LiveTranscript>,
 }
 
 impl OsttTui {
@@ -96,11 +110,20 @@ impl OsttTui {
             peak_volume_threshold,
             reference_level_db,
             is_paused: false,
+            translate: false,
+            vad_state: None,
+            max_duration: None,
             pause_duration: std::time::Duration::ZERO,
             pause_start_time: None,
+            live_transcript: None,
         })
     }
 
+    /// Sets the live transcript shown under the sparkline, or hides it with `None`.
+    pub fn set_live_transcript(&mut self, transcript: Option<LiveTranscript>) {
+        self.live_transcript = transcript;
+    }
+
     /// Renders the waveform visualization with current volume and recording duration.
     ///
     /// # Errors
@@ -137,21 +160,31 @@ impl OsttTui {
 
         // Calculate these values before the draw closure to avoid borrow issues
         let is_paused = self.is_paused;
+        let translate = self.translate;
+        let vad_state = self.vad_state;
+        let max_duration = self.max_duration;
         let peak_hold = self.peak_hold;
         let last_peak = self.last_peak;
         let peak_volume_threshold = self.peak_volume_threshold;
         let recording_duration = self.get_recording_duration();
 
+        let live_transcript = self.live_transcript.clone();
+
         self.terminal.draw(|frame| {
             let area = frame.area();
 
             let footer_height = 1;
+            let live_height = if live_transcript.is_some() {
+                LIVE_TRANSCRIPT_HEIGHT.min(area.height.saturating_sub(footer_height + 2))
+            } else {
+                0
+            };
 
             let content_area = Rect {
                 x: area.x,
                 y: area.y,
                 width: area.width,
-                height: area.height.saturating_sub(footer_height),
+                height: area.height.saturating_sub(footer_height + live_height),
             };
 
             let top_area_height = content_area.height / 3 * 2;
@@ -195,6 +228,19 @@ impl OsttTui {
 
             frame.render_widget(bottom_sparkline, bottom_area);
 
+            if let Some(transcript) = &live_transcript {
+                let live_area = Rect {
+                    x: area.x,
+                    y: content_area.y + content_area.height,
+                    width: area.width,
+                    height: live_height,
+                };
+                frame.render_widget(
+                    Self::live_transcript_paragraph(transcript, live_area),
+                    live_area,
+                );
+            }
+
             let footer_area = Rect {
                 x: area.x,
                 y: area.y + area.height.saturating_sub(footer_height),
@@ -220,7 +266,14 @@ impl OsttTui {
             let duration_secs = recording_duration.as_secs();
             let minutes = duration_secs / 60;
             let secs = duration_secs % 60;
-            let duration_span = ratatui::text::Span::raw(format!("{minutes}:{secs:02}"));
+            let duration_text = match max_duration {
+                Some(max) => {
+                    let max_secs = max.as_secs();
+                    format!("{minutes}:{secs:02} (max {}:{:02})", max_secs / 60, max_secs % 60)
+                }
+                None => format!("{minutes}:{secs:02}"),
+            };
+            let duration_span = ratatui::text::Span::raw(duration_text);
 
             let peak_span = ratatui::text::Span::styled(format!("{display_peak}%"), peak_style);
 
@@ -233,14 +286,36 @@ impl OsttTui {
                 ratatui::text::Span::styled("● ", Style::default().fg(Color::Red))
             };
 
-            let help_text = ratatui::text::Line::from(vec![
+            let mut spans = vec![
                 indicator,
                 duration_span,
                 ratatui::text::Span::raw(" / "),
                 vol_span,
                 ratatui::text::Span::raw(" / "),
                 peak_span,
-            ]);
+            ];
+            if translate {
+                spans.push(ratatui::text::Span::raw(" / "));
+                spans.push(ratatui::text::Span::styled(
+                    "→ EN",
+                    Style::default().fg(Color::Cyan),
+                ));
+            }
+            if let Some(state) = vad_state.filter(|_| !is_paused) {
+                let (text, color) = match state {
+                    VadState::WaitingForSpeech => {
+                        ("waiting for speech".to_string(), Color::Rgb(120, 120, 120))
+                    }
+                    VadState::Speaking => ("auto-stop".to_string(), Color::Rgb(120, 120, 120)),
+                    VadState::StoppingIn(remaining) => (
+                        format!("stopping in {:.1}s", remaining.as_secs_f64()),
+                        Color::Yellow,
+                    ),
+                };
+                spans.push(ratatui::text::Span::raw(" / "));
+                spans.push(ratatui::text::Span::styled(text, Style::default().fg(color)));
+            }
+            let help_text = ratatui::text::Line::from(spans);
 
             let footer = ratatui::widgets::Paragraph::new(help_text).style(
                 Style::default()
@@ -254,6 +329,34 @@ impl OsttTui {
         Ok(())
     }
 
+    /// Builds the live transcript paragraph, keeping only the tail that fits the area.
+    ///
+    /// Finalized text is shown in the foreground color, the interim hypothesis dimmed.
+    fn live_transcript_paragraph(transcript: &LiveTranscript, area: Rect) -> Paragraph<'static> {
+        let capacity = (area.width as usize * area.height as usize).saturating_sub(area.width as usize / 2);
+
+        let interim: Vec<char> = transcript.interim.chars().collect();
+        let interim_len = interim.len().min(capacity);
+        let interim: String = interim[interim.len() - interim_len..].iter().collect();
+
+        let final_budget = capacity.saturating_sub(interim_len + 1);
+        let final_chars: Vec<char> = transcript.final_text.chars().collect();
+        let final_len = final_chars.len().min(final_budget);
+        let mut final_text: String = final_chars[final_chars.len() - final_len..].iter().collect();
+        if !final_text.is_empty() && !interim.is_empty() {
+            final_text.push(' ');
+        }
+
+        let line = Line::from(vec![
+            Span::styled(final_text, Style::default().fg(Color::Rgb(255, 255, 255))),
+            Span::styled(interim, Style::default().fg(Color::Rgb(120, 120, 120))),
+        ]);
+
+        Paragraph::new(line)
+            .wrap(Wrap { trim: true })
+            .style(Style::default().bg(Color::Rgb(0, 0, 0)))
+    }
+
     /// Calculates current volume in percentage and updates peak hold tracking.
     ///
     /// Converts RMS (Root Mean Square) audio samples to dBFS and normalizes to 0-100% scale
@@ -293,13 +396,15 @@ impl OsttTui {
 
     /// Processes user input and returns the appropriate recording command.
     ///
-    /// Only responds to Enter (transcribe), Escape, and 'q' (cancel) keys.
-    /// All other keys are ignored.
+    /// Responds to Enter (transcribe), Escape and 'q' (cancel), Space (pause) and
+    /// 't' (translate) keys. All other keys are ignored.
     ///
     /// # Returns
     /// - `Continue` if no key or unrecognized key was pressed
     /// - `Transcribe` if Enter was pressed
     /// - `Cancel` if Escape or 'q' was pressed
+    /// - `TogglePause` if Space was pressed
+    /// - `ToggleTranslate` if 't' was pressed
     ///
     /// # Errors
     /// - If event polling fails
@@ -324,6 +429,10 @@ impl OsttTui {
                         self.toggle_pause_state();
                         RecordingCommand::TogglePause
                     }
+                    KeyCode::Char('t') => {
+                        tracing::debug!("'t' pressed: toggling translation");
+                        RecordingCommand::ToggleTranslate
+                    }
                     _ => RecordingCommand::Continue,
                 });
             }
@@ -331,6 +440,48 @@ impl OsttTui {
         Ok(RecordingCommand::Continue)
     }
 
+    /// Checks for a key that cancels the running transcription, waiting up to 50ms.
+    ///
+    /// Escape, 'q' and Ctrl+C cancel; all other keys are ignored.
+    ///
+    /// # Errors
+    /// - If event polling fails
+    pub fn poll_cancel(&mut self) -> Result<bool, Box<dyn Error>> {
+        if event::poll(std::time::Duration::from_millis(50))? {
+            if let Event::Key(key) = event::read()? {
+                let ctrl_c = key.code == KeyCode::Char('c')
+                    && key.modifiers.contains(crossterm::event::KeyModifiers::CONTROL);
+                if matches!(key.code, KeyCode::Char('q') | KeyCode::Esc) || ctrl_c {
+                    tracing::debug!("Cancel key pressed during transcription");
+                    return Ok(true);
+                }
+            }
+        }
+        Ok(false)
+    }
+
+    /// Checks for the answer to a yes/no question, waiting up to 50ms.
+    ///
+    /// # Returns
+    /// - `Some(true)` if 'y' or Enter was pressed
+    /// - `Some(false)` if 'n', Escape or 'q' was pressed
+    /// - `None` if no answer was given yet
+    ///
+    /// # Errors
+    /// - If event polling fails
+    pub fn poll_confirmation(&mut self) -> Result<Option<bool>, Box<dyn Error>> {
+        if event::poll(std::time::Duration::from_millis(50))? {
+            if let Event::Key(key) = event::read()? {
+                return Ok(match key.code {
+                    KeyCode::Char('y') | KeyCode::Enter => Some(true),
+                    KeyCode::Char('n') | KeyCode::Char('q') | KeyCode::Esc => Some(false),
+                    _ => None,
+                });
+            }
+        }
+        Ok(None)
+    }
+
     /// Handles pause state transitions, managing pause duration tracking.
     fn toggle_pause_state(&mut self) {
         if self.is_paused {
@@ -0,0 +1,247 @@
+//! Energy-based voice activity detection.
+//!
+//! The recording is split into short frames whose RMS level is compared to a threshold
+//! in dBFS. A few loud frames in a row count as speech, so that clicks and taps don't.
+//! The detector follows the samples the recorder collects and tells the recording loop
+//! when the speaker has been silent long enough to stop; [`compact_silence`] removes
+//! silence from a finished recording before it is encoded.
+
+use std::time::Duration;
+
+use crate::config::file::VadConfig;
+
+/// Length of an analysis frame
+const FRAME: Duration = Duration::from_millis(30);
+
+/// Number of consecutive loud frames that count as speech (90ms)
+const MIN_SPEECH_FRAMES: usize = 3;
+
+/// Silence shorter than this is a normal pause between words, so no countdown is shown
+const COUNTDOWN_GRACE: Duration = Duration::from_millis(500);
+
+/// What the detector has heard so far, for the recording screen.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum VadState {
+    /// No speech yet; the silence timer starts with the first words
+    WaitingForSpeech,
+    /// Speech, or a short pause
+    Speaking,
+    /// Silence; the recording stops after the remaining time
+    StoppingIn(Duration),
+}
+
+/// Detects the end of speech in a growing recording.
+pub struct VoiceActivityDetector {
+    /// Samples per analysis frame
+    frame_len: usize,
+    /// Frame RMS level (on the i16 scale) from which a frame is loud
+    threshold: f64,
+    /// Samples of silence after which the recording stops
+    silence_len: usize,
+    /// Samples of silence after which the countdown is shown
+    grace_len: usize,
+    /// Whether the silence timer only starts after the first speech
+    wait_for_speech: bool,
+    /// Number of samples analyzed so far
+    processed: usize,
+    /// Consecutive loud frames
+    loud_frames: usize,
+    /// Whether speech was heard
+    heard_speech: bool,
+    /// Samples since the last speech
+    silent_samples: usize,
+    sample_rate: u32,
+}
+
+impl VoiceActivityDetector {
+    /// Creates a detector for a recording at the given sample rate.
+    pub fn new(config: &VadConfig, sample_rate: u32) -> Self {
+        let samples = |duration: Duration| (duration.as_secs_f64() * sample_rate as f64) as usize;
+        Self {
+            frame_len: frame_len(sample_rate),
+            threshold: threshold(config.threshold_db),
+            silence_len: samples(Duration::from_secs_f64(config.silence_secs.max(0.0))),
+            grace_len: samples(COUNTDOWN_GRACE),
+            wait_for_speech: config.wait_for_speech,
+            processed: 0,
+            loud_frames: 0,
+            heard_speech: false,
+            silent_samples: 0,
+            sample_rate,
+        }
+    }
+
+    /// Analyzes the samples recorded since the last call.
+    ///
+    /// `samples` is the whole recording so far; only complete frames are analyzed, the
+    /// rest waits for the next call.
+    pub fn update(&mut self, samples: &[i16]) {
+        while self.processed + self.frame_len <= samples.len() {
+            let frame = &samples[self.processed..self.processed + self.frame_len];
+            self.processed += self.frame_len;
+
+            if rms(frame) >= self.threshold {
+                self.loud_frames += 1;
+                if self.loud_frames >= MIN_SPEECH_FRAMES {
+                    self.heard_speech = true;
+                    self.silent_samples = 0;
+                    continue;
+                }
+            } else {
+                self.loud_frames = 0;
+            }
+            self.silent_samples += self.frame_len;
+        }
+    }
+
+    /// Returns what the detector has heard so far.
+    pub fn state(&self) -> VadState {
+        if self.wait_for_speech && !self.heard_speech {
+            return VadState::WaitingForSpeech;
+        }
+        if self.silent_samples < self.grace_len {
+            return VadState::Speaking;
+        }
+        let remaining = self.silence_len.saturating_sub(self.silent_samples);
+        VadState::StoppingIn(Duration::from_secs_f64(remaining as f64 / self.sample_rate as f64))
+    }
+
+    /// Returns whether the speaker has been silent for long enough to stop recording.
+    pub fn should_stop(&self) -> bool {
+        (self.heard_speech || !self.wait_for_speech) && self.silent_samples >= self.silence_len
+    }
+}
+
+/// Removes silence from a recording.
+///
+/// Silence before the first and after the last speech is trimmed, and pauses longer than
+/// `max_silence_secs` are shortened to `silence_gap_secs`. Half of the gap is kept next
+/// to speech, so that quiet word onsets and endings aren't cut. A recording without any
+/// speech is returned unchanged.
+pub fn compact_silence(samples: &[i16], sample_rate: u32, config: &VadConfig) -> Vec<i16> {
+    let frame_len = frame_len(sample_rate);
+    let threshold = threshold(config.threshold_db);
+    let loud: Vec<bool> = samples.chunks(frame_len).map(|frame| rms(frame) >= threshold).collect();
+    let (Some(first), Some(last)) = (loud.iter().position(|&l| l), loud.iter().rposition(|&l| l))
+    else {
+        return samples.to_vec();
+    };
+
+    let seconds = |secs: f64| (secs.max(0.0) * sample_rate as f64) as usize;
+    let gap = seconds(config.silence_gap_secs);
+    let pad = gap / 2;
+    let max_silence = seconds(config.max_silence_secs).max(gap);
+    let speech_start = first * frame_len;
+    let speech_end = ((last + 1) * frame_len).min(samples.len());
+
+    let mut compacted = Vec::with_capacity(speech_end - speech_start + gap);
+    compacted.extend_from_slice(&samples[speech_start.saturating_sub(pad)..speech_start]);
+    let mut index = first;
+    while index <= last {
+        // Runs of loud or quiet frames; quiet runs here lie between speech
+        let run_end = loud[index..=last]
+            .iter()
+            .position(|&l| l != loud[index])
+            .map_or(last + 1, |len| index + len);
+        let (start, end) = (index * frame_len, (run_end * frame_len).min(samples.len()));
+        if loud[index] || end - start <= max_silence {
+            compacted.extend_from_slice(&samples[start..end]);
+        } else {
+            compacted.extend_from_slice(&samples[start..start + pad]);
+            compacted.extend_from_slice(&samples[end - pad..end]);
+        }
+        index = run_end;
+    }
+    compacted.extend_from_slice(&samples[speech_end..(speech_end + pad).min(samples.len())]);
+    compacted
+}
+
+/// Samples per analysis frame
+fn frame_len(sample_rate: u32) -> usize {
+    ((FRAME.as_secs_f64() * sample_rate as f64) as usize).max(1)
+}
+
+/// Frame RMS level on the i16 scale corresponding to a level in dBFS
+fn threshold(threshold_db: f64) -> f64 {
+    i16::MAX as f64 * 10f64.powf(threshold_db / 20.0)
+}
+
+/// Root mean square of a frame, on the i16 scale
+fn rms(frame: &[i16]) -> f64 {
+    let sum_of_squares: f64 = frame.iter().map(|&s| (s as f64).powi(2)).sum();
+    (sum_of_squares / frame.len().max(1) as f64).sqrt()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A sine tone of the given length at about -12 dBFS
+    fn tone(samples: usize) -> Vec<i16> {
+        (0..samples)
+            .map(|i| ((i as f64 * 0.1).sin() * 8000.0) as i16)
+            .collect()
+    }
+
+    #[test]
+    fn stops_after_silence_following_speech() {
+        let config = VadConfig {
+            auto_stop: true,
+            silence_secs: 1.0,
+            ..VadConfig::default()
+        };
+        let mut detector = VoiceActivityDetector::new(&config, 16000);
+        // Lengths are multiples of the 480-sample frame
+
+        let mut recording = vec![0i16; 31680];
+        detector.update(&recording);
+        assert_eq!(detector.state(), VadState::WaitingForSpeech);
+        assert!(!detector.should_stop());
+
+        // A click is not speech
+        recording.extend(tone(480));
+        recording.extend(vec![0i16; 7680]);
+        detector.update(&recording);
+        assert_eq!(detector.state(), VadState::WaitingForSpeech);
+
+        recording.extend(tone(15840));
+        detector.update(&recording);
+        assert_eq!(detector.state(), VadState::Speaking);
+
+        recording.extend(vec![0i16; 9600]);
+        detector.update(&recording);
+        assert!(matches!(detector.state(), VadState::StoppingIn(d) if d.as_millis() == 400));
+        assert!(!detector.should_stop());
+
+        recording.extend(vec![0i16; 6720]);
+        detector.update(&recording);
+        assert!(detector.should_stop());
+    }
+
+    #[test]
+    fn compacts_long_pauses_and_trims_the_ends() {
+        let config = VadConfig {
+            trim_silence: true,
+            max_silence_secs: 1.0,
+            silence_gap_secs: 0.3,
+            ..VadConfig::default()
+        };
+        let silence = |samples: usize| vec![0i16; samples];
+        let recording: Vec<i16> = [
+            silence(32160),
+            tone(4800),
+            silence(9600),
+            tone(4800),
+            silence(48000),
+            tone(4800),
+            silence(16320),
+        ]
+        .concat();
+
+        let compacted = compact_silence(&recording, 16000, &config);
+        // The speech and the short pause stay, the long pause is shortened to 0.3s and
+        // 0.15s of silence is kept on either end
+        assert_eq!(compacted.len(), 2400 + 4800 + 9600 + 4800 + 4800 + 4800 + 2400);
+        assert_eq!(compact_silence(&silence(16000), 16000, &config).len(), 16000);
+    }
+}
@@ -1,7 +1,9 @@
 //! Animated logo loader for transcription phase.
 //!
 //! Features a simple character-by-character animation where ASCII art characters slide in from the right,
-//! assemble in the center, then slide out to the left, and the cycle repeats.
+//! assemble in the center, then slide out to the left, and the cycle repeats. Below the
+//! logo, a status line tells what the transcription is doing and a footer shows the
+//! provider and the time since the transcription started.
 
 use ratatui::prelude::*;
 use std::time::Instant;
@@ -22,6 +24,8 @@ pub struct TranscriptionAnimation {
     start_time: Instant,
     min_duration: std::time::Duration,
     frame_count: u32,
+    status: Option<String>,
+    provider: Option<String>,
 }
 
 impl TranscriptionAnimation {
@@ -33,6 +37,8 @@ impl TranscriptionAnimation {
             start_time: Instant::now(),
             min_duration: std::time::Duration::from_secs(5),
             frame_count: 0,
+            status: None,
+            provider: None,
         }
     }
 
@@ -84,6 +90,16 @@ impl TranscriptionAnimation {
         self.start_time.elapsed().as_secs_f32()
     }
 
+    /// Sets the status line shown below the logo (e.g. retry information), or clears it.
+    pub fn set_status(&mut self, status: Option<String>) {
+        self.status = status;
+    }
+
+    /// Sets the name of the provider shown in the footer, or clears it.
+    pub fn set_provider(&mut self, provider: Option<String>) {
+        self.provider = provider;
+    }
+
     /// Advances the animation to the next frame.
     pub fn update(&mut self) {
         self.frame_count = self.frame_count.wrapping_add(1);
@@ -206,5 +222,32 @@ impl TranscriptionAnimation {
                 );
             }
         }
+
+        // Status line two rows below the logo, footer with provider and elapsed time below it
+        if let Some(status) = &self.status {
+            draw_centered(frame, area, center_y + 2, status, Color::Rgb(150, 150, 150));
+        }
+        let elapsed = format!("{:.1}s", self.elapsed_secs());
+        let footer = match &self.provider {
+            Some(provider) => format!("{provider} · {elapsed}"),
+            None => elapsed,
+        };
+        draw_centered(frame, area, center_y + 4, &footer, Color::Rgb(90, 90, 90));
+    }
+}
+
+/// Draws a line of text centered in row `y` of the area, cut to the area's width
+fn draw_centered(frame: &mut Frame, area: Rect, y: u16, text: &str, color: Color) {
+    if y >= area.height {
+        return;
     }
+    let max_width = area.width.saturating_sub(2) as usize;
+    let text: String = text.chars().take(max_width).collect();
+    let x = (area.width as usize).saturating_sub(text.chars().count()) / 2;
+    frame.buffer_mut().set_string(
+        area.x + x as u16,
+        area.y + y,
+        text,
+        Style::default().fg(color),
+    );
 }
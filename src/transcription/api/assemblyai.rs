@@ -0,0 +1,455 @@
+//! AssemblyAI API implementation.
+//!
+//! Unlike the other APIs, AssemblyAI transcribes asynchronously: the audio is uploaded,
+//! a transcription job is created for the uploaded file and the job is polled until it
+//! is done. The keywords are sent as word boost. A job that is abandoned before it
+//! finishes (polling failed or timed out, or the caller cancelled the transcription and ran
+//! its cleanup) is deleted, so the recording doesn't stay on AssemblyAI's servers.
+
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+use std::time::{Duration, Instant};
+
+use super::{base_url, execute, read_audio, TranscriptionConfig};
+use crate::transcription::cleanup::Cleanup;
+use crate::transcription::http;
+use crate::transcription::language;
+use crate::transcription::model::{KeywordSupport, TranscriptionModel};
+use crate::transcription::progress::TranscriptionStatus;
+use crate::transcription::provider::{AudioUpload, Provider};
+use crate::transcription::response::{Segment, TranscriptionResponse, Word};
+
+/// Public AssemblyAI API
+const BASE_URL: &str = "https://api.assemblyai.com/v2";
+
+/// Largest file accepted by the upload endpoint
+const MAX_UPLOAD_BYTES: u64 = 2 * 1024 * 1024 * 1024;
+
+/// Delay before the first status check; doubled after each check up to `MAX_POLL_DELAY`
+const FIRST_POLL_DELAY: Duration = Duration::from_millis(250);
+
+/// Longest delay between two status checks
+const MAX_POLL_DELAY: Duration = Duration::from_secs(2);
+
+/// How long a job may take before it is given up
+const JOB_TIMEOUT: Duration = Duration::from_secs(30 * 60);
+
+/// How long deleting an abandoned job may take
+const DELETE_TIMEOUT: Duration = Duration::from_secs(5);
+
+#[derive(Debug, Deserialize)]
+struct UploadResponse {
+    upload_url: String,
+}
+
+/// Body of the job creation request
+#[derive(Debug, Serialize)]
+struct JobRequest<'a> {
+    audio_url: &'a str,
+    speech_model: &'a str,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    language_code: Option<&'a str>,
+    language_detection: bool,
+    #[serde(skip_serializing_if = "<[String]>::is_empty")]
+    word_boost: &'a [String],
+    #[serde(skip_serializing_if = "Option::is_none")]
+    boost_param: Option<&'a str>,
+    speaker_labels: bool,
+}
+
+/// State of a job, as returned when it is created and polled
+#[derive(Debug, Deserialize)]
+struct Job {
+    id: String,
+    status: JobStatus,
+    #[serde(default)]
+    error: Option<String>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+enum JobStatus {
+    Queued,
+    Processing,
+    Completed,
+    Error,
+}
+
+/// A completed job
+#[derive(Debug, Deserialize)]
+struct Transcript {
+    #[serde(default)]
+    text: Option<String>,
+    #[serde(default)]
+    words: Option<Vec<AssemblyAiWord>>,
+    #[serde(default)]
+    utterances: Option<Vec<Utterance>>,
+    #[serde(default)]
+    confidence: Option<f32>,
+    #[serde(default)]
+    language_code: Option<String>,
+    #[serde(default)]
+    audio_duration: Option<f64>,
+}
+
+/// A word; times are in milliseconds and speakers are letters ("A", "B", ...)
+#[derive(Debug, Deserialize)]
+struct AssemblyAiWord {
+    text: String,
+    start: u64,
+    end: u64,
+    #[serde(default)]
+    confidence: Option<f32>,
+    #[serde(default)]
+    speaker: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct Utterance {
+    text: String,
+    start: u64,
+    end: u64,
+    #[serde(default)]
+    confidence: Option<f32>,
+    #[serde(default)]
+    speaker: Option<String>,
+}
+
+/// Converts a speaker letter to a 0-based speaker number
+fn speaker_number(speaker: Option<&str>) -> Option<u32> {
+    let letter = speaker?.chars().next()?.to_ascii_uppercase();
+    letter
+        .is_ascii_uppercase()
+        .then(|| letter as u32 - 'A' as u32)
+}
+
+/// Builds the job creation request for an uploaded file
+fn job_request<'a>(config: &'a TranscriptionConfig, audio_url: &'a str) -> JobRequest<'a> {
+    let options = &config.providers.assemblyai;
+    let word_boost: &[String] = match config.model.capabilities().keywords {
+        KeywordSupport::Keywords => &config.keywords,
+        _ => &[],
+    };
+    JobRequest {
+        audio_url,
+        speech_model: config.model.api_model_name(),
+        language_code: config.language.as_deref().map(language::base_code),
+        language_detection: config.language.is_none(),
+        word_boost,
+        boost_param: (!word_boost.is_empty()).then(|| options.boost_param.as_str()),
+        speaker_labels: options.speaker_labels,
+    }
+}
+
+/// Parses a response body of the job endpoints.
+///
+/// # Errors
+/// - If the body is not a job
+fn parse_job(body: &[u8]) -> anyhow::Result<Job> {
+    serde_json::from_slice(body)
+        .map_err(|e| anyhow::anyhow!("Failed to parse AssemblyAI job status: {e}"))
+}
+
+/// A job that is deleted unless it is marked as finished.
+///
+/// Jobs whose polling fails or times out are deleted right away. An aborted transcription
+/// task can't delete anything itself, so the deletion is also registered with the
+/// configuration's cleanup, which the caller runs after cancelling the transcription.
+struct PendingJob {
+    client: reqwest::Client,
+    url: String,
+    api_key: String,
+    cleanup: Option<(Cleanup, u64)>,
+    finished: bool,
+}
+
+impl PendingJob {
+    fn new(
+        config: &TranscriptionConfig,
+        client: reqwest::Client,
+        base_url: &str,
+        id: &str,
+    ) -> Self {
+        let url = format!("{base_url}/transcript/{id}");
+        let api_key = config.api_key.clone();
+        let cleanup = config.cleanup.as_ref().map(|cleanup| {
+            let id = cleanup.register(delete_job(client.clone(), url.clone(), api_key.clone()));
+            (cleanup.clone(), id)
+        });
+        Self {
+            client,
+            url,
+            api_key,
+            cleanup,
+            finished: false,
+        }
+    }
+
+    /// Keeps the job on the server
+    fn finish(&mut self) {
+        self.finished = true;
+        if let Some((cleanup, id)) = &self.cleanup {
+            cleanup.remove(*id);
+        }
+    }
+
+    /// Deletes the job from the server, unless it was marked as finished
+    async fn delete(mut self) {
+        if self.finished {
+            return;
+        }
+        self.finish();
+        delete_job(self.client.clone(), self.url.clone(), self.api_key.clone()).await;
+    }
+}
+
+impl Drop for PendingJob {
+    fn drop(&mut self) {
+        match (self.finished, &self.cleanup) {
+            (true, _) => {}
+            (false, Some(_)) => {
+                tracing::info!("AssemblyAI job {} abandoned, left to the cleanup", self.url);
+            }
+            (false, None) => {
+                tracing::warn!("AssemblyAI job {} abandoned and left on the server", self.url);
+            }
+        }
+    }
+}
+
+/// Deletes a job from the server, logging the outcome
+async fn delete_job(client: reqwest::Client, url: String, api_key: String) {
+    tracing::info!("Deleting abandoned AssemblyAI job {}", url);
+    let request = client
+        .delete(&url)
+        .header("Authorization", api_key)
+        .timeout(DELETE_TIMEOUT);
+    match request.send().await {
+        Ok(response) if response.status().is_success() => {
+            tracing::debug!("AssemblyAI job deleted");
+        }
+        Ok(response) => {
+            tracing::warn!("Failed to delete AssemblyAI job: status {}", response.status());
+        }
+        Err(e) => tracing::warn!("Failed to delete AssemblyAI job: {}", e),
+    }
+}
+
+/// AssemblyAI transcription provider.
+///
+/// Uploads the raw audio, creates a job for it and polls the job until it is done.
+/// The API key is sent as is in the `Authorization` header.
+pub struct AssemblyAiProvider;
+
+#[async_trait::async_trait]
+impl Provider for AssemblyAiProvider {
+    fn id(&self) -> &str {
+        "assemblyai"
+    }
+
+    fn name(&self) -> &str {
+        "AssemblyAI"
+    }
+
+    fn models(&self) -> Vec<TranscriptionModel> {
+        vec![
+            TranscriptionModel::new(
+                "assemblyai-universal",
+                self.id(),
+                "universal",
+                "Universal (best accuracy)",
+            )
+            .with_keywords(KeywordSupport::Keywords)
+            .with_max_upload_bytes(MAX_UPLOAD_BYTES)
+            .with_timestamps()
+            .with_price_per_minute(0.15 / 60.0),
+            TranscriptionModel::new("assemblyai-nano", self.id(), "nano", "Nano (low cost)")
+                .with_keywords(KeywordSupport::Keywords)
+                .with_max_upload_bytes(MAX_UPLOAD_BYTES)
+                .with_timestamps()
+                .with_price_per_minute(0.12 / 60.0),
+        ]
+    }
+
+    /// Builds the upload request; the job is created from the returned upload URL
+    fn build_request(
+        &self,
+        client: &reqwest::Client,
+        config: &TranscriptionConfig,
+        audio: AudioUpload,
+    ) -> anyhow::Result<reqwest::RequestBuilder> {
+        let configured = config.providers.assemblyai.base_url.as_deref();
+        let base_url = base_url(configured, BASE_URL);
+        Ok(client
+            .post(format!("{base_url}/upload"))
+            .header("Authorization", &config.api_key)
+            .header("Content-Type", "application/octet-stream")
+            .header("Content-Length", audio.len())
+            .body(audio.into_body()))
+    }
+
+    /// Parses a completed job
+    fn parse_response(
+        &self,
+        config: &TranscriptionConfig,
+        body: &[u8],
+    ) -> anyhow::Result<TranscriptionResponse> {
+        let transcript: Transcript = serde_json::from_slice(body)
+            .map_err(|e| anyhow::anyhow!("Failed to parse AssemblyAI transcript: {e}"))?;
+        let text = transcript
+            .text
+            .ok_or_else(|| anyhow::anyhow!("No transcript found in AssemblyAI response"))?;
+
+        let milliseconds = |ms: u64| ms as f64 / 1000.0;
+        let response = TranscriptionResponse {
+            text,
+            language: transcript.language_code,
+            confidence: transcript.confidence,
+            duration: transcript.audio_duration,
+            segments: transcript
+                .utterances
+                .unwrap_or_default()
+                .into_iter()
+                .map(|u| Segment {
+                    start: milliseconds(u.start),
+                    end: milliseconds(u.end),
+                    text: u.text,
+                    confidence: u.confidence,
+                    speaker: speaker_number(u.speaker.as_deref()),
+                })
+                .collect(),
+            words: transcript
+                .words
+                .unwrap_or_default()
+                .into_iter()
+                .map(|w| Word {
+                    speaker: speaker_number(w.speaker.as_deref()),
+                    word: w.text,
+                    start: milliseconds(w.start),
+                    end: milliseconds(w.end),
+                    confidence: w.confidence,
+                })
+                .collect(),
+        };
+
+        let options = &config.providers.assemblyai;
+        if !options.speaker_labels {
+            return Ok(response);
+        }
+        match response.speaker_turns(&options.speaker_label) {
+            Some(text) => Ok(TranscriptionResponse { text, ..response }),
+            None => Ok(response),
+        }
+    }
+
+    async fn transcribe(
+        &self,
+        config: &TranscriptionConfig,
+        audio_path: &Path,
+    ) -> anyhow::Result<TranscriptionResponse> {
+        let configured = config.providers.assemblyai.base_url.as_deref();
+        let base_url = base_url(configured, BASE_URL);
+        let api_key = config.api_key.as_str();
+
+        let audio = read_audio(self, config, audio_path)?;
+        let upload_bytes = audio.len() as u64;
+        let body = execute(self, config, upload_bytes, |client| {
+            self.build_request(client, config, audio.clone())
+        })
+        .await?;
+        let upload: UploadResponse = serde_json::from_slice(&body)
+            .map_err(|e| anyhow::anyhow!("Failed to parse AssemblyAI upload response: {e}"))?;
+
+        let request = job_request(config, &upload.upload_url);
+        tracing::debug!(
+            "AssemblyAI API Call:\n  URL: {}/transcript\n  Method: POST\n  Model: {}\n  Language: {}\n  Word boost: {}",
+            base_url,
+            request.speech_model,
+            request.language_code.unwrap_or(language::AUTO),
+            request.word_boost.join(", ")
+        );
+        let body = execute(self, config, 0, |client| {
+            Ok(client
+                .post(format!("{base_url}/transcript"))
+                .header("Authorization", api_key)
+                .json(&request))
+        })
+        .await?;
+
+        let job = parse_job(&body)?;
+        tracing::info!("Created AssemblyAI job {}", job.id);
+        let client = http::client(&config.network)?;
+        let mut pending = PendingJob::new(config, client, &base_url, &job.id);
+        let status_url = format!("{base_url}/transcript/{}", job.id);
+
+        match self.poll(config, &status_url, body, &mut pending).await {
+            Ok(body) => {
+                pending.finish();
+                config.report(TranscriptionStatus::Parsing {
+                    provider: self.name().to_string(),
+                });
+                self.parse_response(config, &body)
+            }
+            Err(e) => {
+                pending.delete().await;
+                Err(e)
+            }
+        }
+    }
+}
+
+impl AssemblyAiProvider {
+    /// Polls a job until it completes and returns the completed job's body.
+    ///
+    /// `body` is the job as returned when it was created. Failed jobs are marked as finished,
+    /// so they can be looked up in AssemblyAI's dashboard.
+    ///
+    /// # Errors
+    /// - If the job fails or doesn't finish within `JOB_TIMEOUT`
+    /// - If a status check fails
+    async fn poll(
+        &self,
+        config: &TranscriptionConfig,
+        status_url: &str,
+        mut body: Vec<u8>,
+        pending: &mut PendingJob,
+    ) -> anyhow::Result<Vec<u8>> {
+        let api_key = config.api_key.as_str();
+        let started = Instant::now();
+        let mut delay = FIRST_POLL_DELAY;
+
+        loop {
+            let job = parse_job(&body)?;
+            match job.status {
+                JobStatus::Completed => return Ok(body),
+                JobStatus::Error => {
+                    pending.finish();
+                    return Err(anyhow::anyhow!(
+                        "AssemblyAI could not transcribe the recording: {}",
+                        job.error.as_deref().unwrap_or("unknown error")
+                    ));
+                }
+                JobStatus::Queued | JobStatus::Processing => {
+                    config.report(TranscriptionStatus::Polling {
+                        provider: self.name().to_string(),
+                        state: if job.status == JobStatus::Queued { "queued" } else { "processing" }
+                            .to_string(),
+                    });
+                }
+            }
+
+            if started.elapsed() > JOB_TIMEOUT {
+                return Err(anyhow::anyhow!(
+                    "AssemblyAI job didn't finish within {} minutes",
+                    JOB_TIMEOUT.as_secs() / 60
+                ));
+            }
+            tokio::time::sleep(delay).await;
+            delay = (delay * 2).min(MAX_POLL_DELAY);
+            body = execute(self, config, 0, |client| {
+                Ok(client.get(status_url).header("Authorization", api_key))
+            })
+            .await?;
+        }
+    }
+}
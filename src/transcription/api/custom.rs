@@ -0,0 +1,180 @@
+//! Custom OpenAI-compatible provider implementation.
+//!
+//! Handles transcription requests to self-hosted servers (faster-whisper-server, LocalAI, etc.)
+//! that expose the OpenAI `/audio/transcriptions` multipart API. The base URL, model name,
+//! authentication style and multipart field names come from `[providers.custom.<name>]`.
+
+use reqwest::StatusCode;
+
+use super::{audio_part, TranscriptionConfig, WhisperResponse};
+use crate::config::file::{CustomAuthStyle, CustomProviderConfig};
+use crate::transcription::language;
+use crate::transcription::model::{KeywordSupport, ModelCapabilities, TranscriptionModel};
+use crate::transcription::provider::{AudioUpload, Provider, ProviderCapabilities};
+use crate::transcription::response::TranscriptionResponse;
+
+/// A user-configured OpenAI-compatible transcription server.
+///
+/// Uses multipart form data with the configured field names and authentication style.
+/// Each custom provider offers exactly one model, identified by the provider's name.
+///
+/// Keywords are passed in the configured prompt field unless it is empty.
+pub struct CustomProvider {
+    name: String,
+    settings: CustomProviderConfig,
+}
+
+impl CustomProvider {
+    /// Creates a provider from a `[providers.custom.<name>]` section
+    pub fn new(name: &str, settings: CustomProviderConfig) -> Self {
+        Self {
+            name: name.to_string(),
+            settings,
+        }
+    }
+
+    /// Transcription endpoint derived from the configured base URL
+    fn endpoint(&self) -> String {
+        format!(
+            "{}/audio/transcriptions",
+            self.settings.base_url.trim_end_matches('/')
+        )
+    }
+}
+
+impl Provider for CustomProvider {
+    fn id(&self) -> &str {
+        &self.name
+    }
+
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn models(&self) -> Vec<TranscriptionModel> {
+        let settings = &self.settings;
+        let prompt = !settings.prompt_field.is_empty();
+        let capabilities = ModelCapabilities {
+            prompt,
+            keywords: if prompt { KeywordSupport::Prompt } else { KeywordSupport::None },
+            max_upload_bytes: settings.max_upload_mb.map(|mb| mb * 1024 * 1024),
+            formats: settings.formats.clone(),
+            languages: settings.languages.clone(),
+            ..ModelCapabilities::default()
+        };
+        vec![
+            TranscriptionModel::new(&self.name, &self.name, &settings.model, &settings.model)
+                .with_capabilities(capabilities),
+        ]
+    }
+
+    fn capabilities(&self) -> ProviderCapabilities {
+        ProviderCapabilities {
+            requires_api_key: self.settings.auth != CustomAuthStyle::None,
+            ..ProviderCapabilities::default()
+        }
+    }
+
+    fn build_request(
+        &self,
+        client: &reqwest::Client,
+        config: &TranscriptionConfig,
+        audio: AudioUpload,
+    ) -> anyhow::Result<reqwest::RequestBuilder> {
+        let name = &self.name;
+        let settings = &self.settings;
+
+        let mut form = reqwest::multipart::Form::new()
+            .part(settings.file_field.clone(), audio_part(audio)?)
+            .text(settings.model_field.clone(), settings.model.clone());
+
+        // Debug log: Log the API call details (without the audio data)
+        let mut debug_params = vec![
+            format!("{}={}", settings.model_field, settings.model),
+        ];
+
+        // Without a language hint the model detects the language itself
+        if let Some(language) = &config.language {
+            let language = language::base_code(language);
+            form = form.text("language", language.to_string());
+            debug_params.push(format!("language={language}"));
+        }
+
+        // Add keywords as prompt for better transcription context
+        if !config.keywords.is_empty() && config.model.capabilities().prompt {
+            let prompt = config.keywords.join(", ");
+            form = form.text(settings.prompt_field.clone(), prompt.clone());
+            debug_params.push(format!("{}={prompt}", settings.prompt_field));
+            tracing::debug!("Keywords used as prompt for custom provider '{}': {:?}", name, config.keywords);
+        }
+
+        let endpoint = self.endpoint();
+
+        let auth_header = match settings.auth {
+            CustomAuthStyle::Bearer => "Authorization: Bearer <redacted>".to_string(),
+            CustomAuthStyle::Header => format!("{}: <redacted>", settings.auth_header),
+            CustomAuthStyle::None => "none".to_string(),
+        };
+
+        tracing::debug!(
+            "Custom provider '{}' API Call:\n  URL: {}\n  Method: POST\n  Headers:\n    {}\n    Content-Type: multipart/form-data\n  Body parameters: {}",
+            name,
+            endpoint,
+            auth_header,
+            debug_params.join("\n    ")
+        );
+
+        let request = client.post(&endpoint).multipart(form);
+        Ok(match settings.auth {
+            CustomAuthStyle::Bearer => request.bearer_auth(&config.api_key),
+            CustomAuthStyle::Header => request.header(settings.auth_header.as_str(), &config.api_key),
+            CustomAuthStyle::None => request,
+        })
+    }
+
+    fn parse_response(
+        &self,
+        _config: &TranscriptionConfig,
+        body: &[u8],
+    ) -> anyhow::Result<TranscriptionResponse> {
+        let custom_response: WhisperResponse = serde_json::from_slice(body)
+            .map_err(|e| anyhow::anyhow!("Failed to parse response from '{}': {e}", self.name))?;
+
+        // Debug log: Log the full response for debugging
+        tracing::debug!(
+            "Custom provider '{}' API Response:\n  Status: Success\n  Full response: {:#?}",
+            self.name,
+            custom_response
+        );
+
+        Ok(custom_response.into())
+    }
+
+    fn map_http_error(&self, status: StatusCode, body: &str) -> anyhow::Error {
+        let name = &self.name;
+        let endpoint = self.endpoint();
+        let message = match status.as_u16() {
+            401 | 403 => format!("'{name}' rejected the API key. Please run 'ostt auth' to update it or check the auth settings in [providers.custom.{name}]."),
+            404 => format!("'{name}' has no transcription endpoint at {endpoint}. Check base_url in [providers.custom.{name}]."),
+            413 => format!("Recording is too large for '{name}'. Set max_upload_mb in [providers.custom.{name}] so long recordings are split."),
+            429 => format!("Too many requests to '{name}'. Please wait and try again."),
+            500 | 502 | 503 | 504 => format!("'{name}' is experiencing issues (status {status}): {body}"),
+            _ => format!("'{name}' API error (status {status}): {body}"),
+        };
+        anyhow::anyhow!(message)
+    }
+
+    fn map_network_error(&self, error: &reqwest::Error) -> anyhow::Error {
+        let name = &self.name;
+        let message = if error.is_connect() {
+            format!("Failed to connect to '{name}' at {}. Check that the server is running.", self.endpoint())
+        } else if error.is_timeout() {
+            format!("Request to '{name}' timed out. The server is not responding.")
+        } else if error.is_builder() {
+            format!("Failed to build request for '{name}': {error}. Check base_url in [providers.custom.{name}].")
+        } else {
+            format!("'{name}' network error: {error}")
+        };
+        anyhow::anyhow!(message)
+    }
+}
@@ -2,156 +2,344 @@
 //!
 //! Handles transcription requests to Deepgram's API using binary audio data.
 
-use std::path::Path;
 use serde::Deserialize;
 use urlencoding;
 
-use super::TranscriptionConfig;
-use super::super::model::TranscriptionModel;
+use super::{base_url, discovered_description, TranscriptionConfig};
+use crate::config::file::ProvidersConfig;
+use crate::transcription::model::{KeywordSupport, TranscriptionModel};
+use crate::transcription::provider::{AudioUpload, Provider};
+use crate::transcription::response::{Segment, TranscriptionResponse, Word};
 
-/// Deepgram response structure (kept for potential future use)
-#[allow(dead_code)]
-#[derive(Debug, Deserialize)]
-struct DeepgramResult {
-    transcript: String,
-}
+/// Public Deepgram API; the pre-recorded transcription endpoint is `/listen` below it
+const BASE_URL: &str = "https://api.deepgram.com/v1";
+
+/// Largest file accepted by the pre-recorded endpoint
+const MAX_UPLOAD_BYTES: u64 = 2 * 1024 * 1024 * 1024;
 
 #[derive(Debug, Deserialize)]
 struct DeepgramChannel {
     alternatives: Vec<DeepgramAlternative>,
+    #[serde(default)]
+    detected_language: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+pub(crate) struct DeepgramAlternative {
+    pub(crate) transcript: String,
+    #[serde(default)]
+    pub(crate) confidence: Option<f32>,
+    #[serde(default)]
+    pub(crate) words: Vec<DeepgramWord>,
+}
+
+#[derive(Debug, Deserialize)]
+pub(crate) struct DeepgramWord {
+    word: String,
+    #[serde(default)]
+    punctuated_word: Option<String>,
+    start: f64,
+    end: f64,
+    #[serde(default)]
+    confidence: Option<f32>,
+    #[serde(default)]
+    speaker: Option<u32>,
+}
+
+impl DeepgramWord {
+    /// Converts to the provider-independent word, preferring the punctuated form
+    pub(crate) fn into_word(self) -> Word {
+        Word {
+            word: self.punctuated_word.unwrap_or(self.word),
+            start: self.start,
+            end: self.end,
+            confidence: self.confidence,
+            speaker: self.speaker,
+        }
+    }
 }
 
 #[derive(Debug, Deserialize)]
-struct DeepgramAlternative {
+struct DeepgramUtterance {
+    start: f64,
+    end: f64,
     transcript: String,
+    #[serde(default)]
+    confidence: Option<f32>,
+    #[serde(default)]
+    speaker: Option<u32>,
+}
+
+#[derive(Debug, Deserialize)]
+struct DeepgramMetadata {
+    #[serde(default)]
+    duration: Option<f64>,
 }
 
 #[derive(Debug, Deserialize)]
 struct DeepgramResponse {
+    #[serde(default)]
+    metadata: Option<DeepgramMetadata>,
     results: DeepgramResults,
 }
 
+/// Response of the `/models` endpoint; only the speech-to-text models are of interest
+#[derive(Debug, Deserialize)]
+struct DeepgramModels {
+    #[serde(default)]
+    stt: Vec<DeepgramModel>,
+}
+
+#[derive(Debug, Deserialize)]
+struct DeepgramModel {
+    canonical_name: String,
+}
+
 #[derive(Debug, Deserialize)]
 struct DeepgramResults {
     channels: Vec<DeepgramChannel>,
+    #[serde(default)]
+    utterances: Vec<DeepgramUtterance>,
 }
 
-/// Transcribes an audio file using Deepgram's API.
+/// Builds the query string shared by batch and live requests.
 ///
-/// Sends raw binary audio data with Token authentication and model specified in query parameters.
-pub async fn transcribe(
-    config: &TranscriptionConfig,
-    audio_path: &Path,
-) -> anyhow::Result<String> {
-    let audio_data = std::fs::read(audio_path).map_err(|e| {
-        anyhow::anyhow!("Failed to read audio file: {e}")
-    })?;
-
-    let client = reqwest::Client::new();
-
-    // Build the API URL with query parameters
-    let mut url = format!(
-        "{}?model={}",
-        config.model.endpoint(),
-        config.model.api_model_name()
-    );
+/// Contains the model, the feature flags from `[providers.deepgram]` and the keywords, as
+/// keyterms or keywords depending on the model.
+pub(crate) fn query(config: &TranscriptionConfig) -> String {
+    let mut query = format!("model={}", config.model.api_model_name());
 
     // Add Deepgram feature flags from provider configuration
     let deepgram_config = &config.providers.deepgram;
     if deepgram_config.filler_words {
-        url.push_str("&filler_words=true");
+        query.push_str("&filler_words=true");
     }
     if deepgram_config.measurements {
-        url.push_str("&measurements=true");
+        query.push_str("&measurements=true");
     }
     if deepgram_config.numerals {
-        url.push_str("&numerals=true");
+        query.push_str("&numerals=true");
     }
     if deepgram_config.paragraphs {
-        url.push_str("&paragraphs=true");
+        query.push_str("&paragraphs=true");
     }
     if deepgram_config.profanity_filter {
-        url.push_str("&profanity_filter=true");
+        query.push_str("&profanity_filter=true");
     }
     if deepgram_config.punctuate {
-        url.push_str("&punctuate=true");
+        query.push_str("&punctuate=true");
     }
     if deepgram_config.smart_format {
-        url.push_str("&smart_format=true");
+        query.push_str("&smart_format=true");
     }
     if deepgram_config.utterances {
-        url.push_str("&utterances=true");
+        query.push_str("&utterances=true");
     }
     if deepgram_config.utt_split != 0.8 {
-        url.push_str(&format!("&utt_split={}", deepgram_config.utt_split));
+        query.push_str(&format!("&utt_split={}", deepgram_config.utt_split));
+    }
+    if deepgram_config.diarize {
+        query.push_str("&diarize=true");
     }
     if deepgram_config.mip_opt_out {
-        url.push_str("&mip_opt_out=true");
+        query.push_str("&mip_opt_out=true");
     }
 
     // Add keywords/keyterms if any (nova-3 uses keyterms, nova-2 uses keywords)
-    if !config.keywords.is_empty() {
-        let param_name = match config.model {
-            TranscriptionModel::DeepgramNova3 => "keyterm",
-            TranscriptionModel::DeepgramNova2 => "keywords",
-            _ => "keywords", // fallback
-        };
+    let param_name = match config.model.capabilities().keywords {
+        KeywordSupport::Keyterms => Some("keyterm"),
+        KeywordSupport::Keywords => Some("keywords"),
+        KeywordSupport::None | KeywordSupport::Prompt => None,
+    };
+    if let Some(param_name) = param_name {
         for keyword in &config.keywords {
-            url.push_str(&format!("&{}={}", param_name, urlencoding::encode(keyword)));
+            query.push_str(&format!("&{}={}", param_name, urlencoding::encode(keyword)));
         }
     }
 
-    let response = match client
-        .post(&url)
-        .header("Authorization", format!("Token {}", config.api_key))
-        .header("Content-Type", "audio/mpeg")
-        .body(audio_data)
-        .send()
-        .await
-    {
-        Ok(resp) => resp,
-        Err(e) => {
-            let error_msg = if e.is_connect() {
-                "Failed to connect to Deepgram API server. Check your internet connection.".to_string()
-            } else if e.is_timeout() {
-                "Request to Deepgram timed out. The API server is not responding.".to_string()
-            } else if e.to_string().contains("builder") {
-                format!("Failed to build Deepgram API request: {e}. This may be a configuration error.")
-            } else {
-                format!("Deepgram network error: {e}")
-            };
-            return Err(anyhow::anyhow!(error_msg));
+    query
+}
+
+/// Returns the language query parameter for a request, if any.
+///
+/// Pre-recorded requests without a configured language ask Deepgram to detect it. Live
+/// streams can't detect the language up front; nova-3 instead transcribes in multilingual
+/// mode, other models fall back to Deepgram's default (English).
+pub(crate) fn language_param(config: &TranscriptionConfig, streaming: bool) -> Option<String> {
+    match (&config.language, streaming) {
+        (Some(language), _) => Some(format!("language={}", urlencoding::encode(language))),
+        (None, false) => Some("detect_language=true".to_string()),
+        (None, true) if config.model.api_model_name() == "nova-3" => {
+            Some("language=multi".to_string())
         }
-    };
+        (None, true) => None,
+    }
+}
+
+/// Replaces the flattened transcript with speaker-labelled turns when diarization is enabled.
+///
+/// Responses without speaker information (e.g. from a model that doesn't diarize) are
+/// returned unchanged.
+pub(crate) fn label_speakers(
+    config: &TranscriptionConfig,
+    response: TranscriptionResponse,
+) -> TranscriptionResponse {
+    let deepgram_config = &config.providers.deepgram;
+    if !deepgram_config.diarize {
+        return response;
+    }
+    match response.speaker_turns(&deepgram_config.speaker_label) {
+        Some(text) => TranscriptionResponse { text, ..response },
+        None => response,
+    }
+}
+
+/// Deepgram transcription provider.
+///
+/// Sends raw binary audio data with Token authentication and model specified in query parameters.
+pub struct DeepgramProvider;
 
-    if !response.status().is_success() {
-        let status = response.status();
-        let error_body = response.text().await.unwrap_or_else(|_| "Unknown error".to_string());
+impl Provider for DeepgramProvider {
+    fn id(&self) -> &str {
+        "deepgram"
+    }
 
-        let human_readable = match status.as_u16() {
-            401 => "Deepgram API key is invalid or expired. Please run 'ostt auth' to update your API key.".to_string(),
-            403 => "You don't have permission to use Deepgram's API. Check your API key and account status.".to_string(),
-            429 => "Too many requests to Deepgram. You've hit the API rate limit. Please wait and try again.".to_string(),
-            500 | 502 | 503 | 504 => "Deepgram API server is experiencing issues. Please try again later.".to_string(),
-            _ => format!("Deepgram API error (status {status}): {error_body}"),
+    fn name(&self) -> &str {
+        "Deepgram"
+    }
+
+    fn models(&self) -> Vec<TranscriptionModel> {
+        vec![
+            TranscriptionModel::new("nova-3", self.id(), "nova-3", "Nova 3 (latest, fastest)")
+                .with_keywords(KeywordSupport::Keyterms)
+                .with_max_upload_bytes(MAX_UPLOAD_BYTES)
+                .with_timestamps()
+                .with_price_per_minute(0.0043),
+            TranscriptionModel::new("nova-2", self.id(), "nova-2", "Nova 2 (previous generation)")
+                .with_keywords(KeywordSupport::Keywords)
+                .with_max_upload_bytes(MAX_UPLOAD_BYTES)
+                .with_timestamps()
+                .with_price_per_minute(0.0043),
+        ]
+    }
+
+    fn models_request(
+        &self,
+        client: &reqwest::Client,
+        providers: &ProvidersConfig,
+        api_key: &str,
+    ) -> Option<reqwest::RequestBuilder> {
+        let base_url = base_url(providers.deepgram.base_url.as_deref(), BASE_URL);
+        Some(
+            client
+                .get(format!("{base_url}/models"))
+                .header("Authorization", format!("Token {api_key}")),
+        )
+    }
+
+    fn parse_models(&self, body: &[u8]) -> anyhow::Result<Vec<String>> {
+        let models: DeepgramModels = serde_json::from_slice(body)
+            .map_err(|e| anyhow::anyhow!("Failed to parse Deepgram model list: {e}"))?;
+        // Every language and version of a model is listed separately
+        let mut names: Vec<String> = Vec::new();
+        for model in models.stt {
+            if !names.contains(&model.canonical_name) {
+                names.push(model.canonical_name);
+            }
+        }
+        Ok(names)
+    }
+
+    fn discovered_model(&self, api_name: &str) -> Option<TranscriptionModel> {
+        let keywords = if api_name.starts_with("nova-3") {
+            KeywordSupport::Keyterms
+        } else if api_name.starts_with("whisper") {
+            KeywordSupport::None
+        } else {
+            KeywordSupport::Keywords
         };
+        Some(
+            TranscriptionModel::new(api_name, self.id(), api_name, discovered_description(api_name))
+                .with_keywords(keywords)
+                .with_max_upload_bytes(MAX_UPLOAD_BYTES)
+                .with_timestamps(),
+        )
+    }
+
+    fn build_request(
+        &self,
+        client: &reqwest::Client,
+        config: &TranscriptionConfig,
+        audio: AudioUpload,
+    ) -> anyhow::Result<reqwest::RequestBuilder> {
+        let base_url = base_url(config.providers.deepgram.base_url.as_deref(), BASE_URL);
+        let mut url = format!("{base_url}/listen?{}", query(config));
+        if let Some(language) = language_param(config, false) {
+            url.push_str(&format!("&{language}"));
+        }
+
+        tracing::debug!(
+            "Deepgram API Call:\n  URL: {}\n  Method: POST\n  Headers:\n    Authorization: Token <redacted>\n    Content-Type: {}",
+            url,
+            audio.mime_type
+        );
 
-        return Err(anyhow::anyhow!(human_readable));
+        Ok(client
+            .post(&url)
+            .header("Authorization", format!("Token {}", config.api_key))
+            .header("Content-Type", audio.mime_type)
+            .header("Content-Length", audio.len())
+            .body(audio.into_body()))
     }
 
-    let deepgram_response: DeepgramResponse = response
-        .json()
-        .await
-        .map_err(|e| anyhow::anyhow!("Failed to parse Deepgram response: {e}"))?;
+    fn parse_response(
+        &self,
+        config: &TranscriptionConfig,
+        body: &[u8],
+    ) -> anyhow::Result<TranscriptionResponse> {
+        let deepgram_response: DeepgramResponse = serde_json::from_slice(body)
+            .map_err(|e| anyhow::anyhow!("Failed to parse Deepgram response: {e}"))?;
 
-    // Extract transcript from the nested response structure
-    let transcript = deepgram_response
-        .results
-        .channels
-        .first()
-        .and_then(|channel| channel.alternatives.first())
-        .map(|alt| alt.transcript.clone())
-        .ok_or_else(|| anyhow::anyhow!("No transcript found in Deepgram response"))?;
+        // Extract transcript from the nested response structure
+        let channel = deepgram_response
+            .results
+            .channels
+            .into_iter()
+            .next()
+            .ok_or_else(|| anyhow::anyhow!("No transcript found in Deepgram response"))?;
+        let language = channel.detected_language;
+        let alternative = channel
+            .alternatives
+            .into_iter()
+            .next()
+            .ok_or_else(|| anyhow::anyhow!("No transcript found in Deepgram response"))?;
 
-    Ok(transcript)
+        // Utterances are only returned when enabled in [providers.deepgram]
+        let segments = deepgram_response
+            .results
+            .utterances
+            .into_iter()
+            .map(|u| Segment {
+                start: u.start,
+                end: u.end,
+                text: u.transcript,
+                confidence: u.confidence,
+                speaker: u.speaker,
+            })
+            .collect();
+
+        let response = TranscriptionResponse {
+            text: alternative.transcript,
+            language,
+            confidence: alternative.confidence,
+            duration: deepgram_response.metadata.and_then(|m| m.duration),
+            segments,
+            words: alternative
+                .words
+                .into_iter()
+                .map(DeepgramWord::into_word)
+                .collect(),
+        };
+        Ok(label_speakers(config, response))
+    }
 }
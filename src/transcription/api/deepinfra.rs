@@ -2,120 +2,166 @@
 //!
 //! Handles transcription requests to DeepInfra's inference API using multipart form data.
 
-use std::path::Path;
-use serde::Deserialize;
-
-use super::TranscriptionConfig;
-
-/// DeepInfra API response structure
-#[derive(Debug, Deserialize)]
-struct DeepInfraResponse {
-    text: String,
-}
-
-/// Transcribes an audio file using DeepInfra's Whisper API.
+use super::{
+    audio_part, base_url, model_prompt, whisper_variant, TranscriptionConfig, WhisperResponse,
+};
+use crate::config::file::{ProvidersConfig, ResponseFormat, TimestampGranularity};
+use crate::transcription::language;
+use crate::transcription::model::{KeywordSupport, TranscriptionModel};
+use crate::transcription::provider::{AudioUpload, Provider};
+use crate::transcription::response::TranscriptionResponse;
+
+/// Public DeepInfra API; the inference endpoint is below it, followed by the model name
+const BASE_URL: &str = "https://api.deepinfra.com/v1";
+
+/// DeepInfra transcription provider.
 ///
 /// Uses multipart form data with bearer token authentication.
 /// DeepInfra hosts OpenAI's Whisper model and compatible models.
-pub async fn transcribe(
-    config: &TranscriptionConfig,
-    audio_path: &Path,
-) -> anyhow::Result<String> {
-    let audio_data = std::fs::read(audio_path).map_err(|e| {
-        anyhow::anyhow!("Failed to read audio file: {e}")
-    })?;
-
-    let client = reqwest::Client::new();
-
-    let file_name = audio_path
-        .file_name()
-        .unwrap_or_default()
-        .to_string_lossy()
-        .to_string();
-
-    let file_part = reqwest::multipart::Part::bytes(audio_data)
-        .file_name(file_name.clone())
-        .mime_str("audio/mpeg")
-        .map_err(|e| anyhow::anyhow!("Failed to create file part for upload: {e}"))?;
-
-    let mut form = reqwest::multipart::Form::new().part("audio", file_part);
-
-    // Debug log: Log the API call details (without the audio data)
-    let mut debug_params = vec![];
-
-    // Build the URL with model name in the path
-    let endpoint = format!(
-        "{}/{}",
-        config.model.endpoint(),
-        config.model.api_model_name()
-    );
-
-    // Add keywords as prompt for better transcription context (similar to OpenAI)
-    if !config.keywords.is_empty() {
-        let prompt = config.keywords.join(", ");
-        form = form.text("prompt", prompt.clone());
-        debug_params.push(format!("prompt={prompt}"));
-        tracing::debug!("Keywords used as prompt for DeepInfra model: {:?}", config.keywords);
+///
+/// Keywords are passed as the `prompt` parameter, after the prompt text from
+/// `[providers.deepinfra]`.
+pub struct DeepInfraProvider;
+
+impl Provider for DeepInfraProvider {
+    fn id(&self) -> &str {
+        "deepinfra"
+    }
+
+    fn name(&self) -> &str {
+        "DeepInfra"
+    }
+
+    fn models(&self) -> Vec<TranscriptionModel> {
+        vec![
+            TranscriptionModel::new(
+                "deepinfra-whisper-large-v3",
+                self.id(),
+                "openai/whisper-large-v3",
+                "Whisper Large V3 (best accuracy)",
+            )
+            .with_prompt()
+            .with_keywords(KeywordSupport::Prompt)
+            .with_translation()
+            .with_timestamps()
+            .with_price_per_minute(0.00045),
+            TranscriptionModel::new(
+                "deepinfra-whisper-base",
+                self.id(),
+                "openai/whisper-base",
+                "Whisper Base (fast, lightweight)",
+            )
+            .with_prompt()
+            .with_keywords(KeywordSupport::Prompt)
+            .with_translation()
+            .with_timestamps()
+            .with_price_per_minute(0.0002),
+        ]
+    }
+
+    fn models_request(
+        &self,
+        client: &reqwest::Client,
+        providers: &ProvidersConfig,
+        api_key: &str,
+    ) -> Option<reqwest::RequestBuilder> {
+        // The OpenAI-compatible part of the API lists the hosted models
+        let base_url = base_url(providers.deepinfra.base_url.as_deref(), BASE_URL);
+        Some(client.get(format!("{base_url}/openai/models")).bearer_auth(api_key))
+    }
+
+    fn discovered_model(&self, api_name: &str) -> Option<TranscriptionModel> {
+        // Names are "<organisation>/<model>", e.g. "openai/whisper-large-v3-turbo"
+        let name = api_name.rsplit('/').next().unwrap_or(api_name).to_lowercase();
+        name.contains("whisper")
+            .then(|| whisper_variant(&format!("deepinfra-{name}"), self.id(), api_name))
     }
 
-    tracing::debug!(
-        "DeepInfra API Call:\n  URL: {}\n  Method: POST\n  Headers:\n    Authorization: Bearer <redacted>\n    Content-Type: multipart/form-data\n  Body parameters: {}",
-        endpoint,
-        if debug_params.is_empty() {
-            "none".to_string()
-        } else {
-            debug_params.join("\n    ")
+    fn build_request(
+        &self,
+        client: &reqwest::Client,
+        config: &TranscriptionConfig,
+        audio: AudioUpload,
+    ) -> anyhow::Result<reqwest::RequestBuilder> {
+        let mut form = reqwest::multipart::Form::new().part("audio", audio_part(audio)?);
+
+        // Debug log: Log the API call details (without the audio data)
+        let mut debug_params = vec![];
+
+        // Build the URL with model name in the path
+        let base_url = base_url(config.providers.deepinfra.base_url.as_deref(), BASE_URL);
+        let endpoint = format!("{base_url}/inference/{}", config.model.api_model_name());
+
+        // Without a language hint the model detects the language itself
+        if let Some(language) = &config.language {
+            let language = language::base_code(language);
+            form = form.text("language", language.to_string());
+            debug_params.push(format!("language={language}"));
         }
-    );
-
-    let response = match client
-        .post(&endpoint)
-        .bearer_auth(&config.api_key)
-        .multipart(form)
-        .send()
-        .await
-    {
-        Ok(resp) => resp,
-        Err(e) => {
-            let error_msg = if e.is_connect() {
-                "Failed to connect to DeepInfra API server. Check your internet connection.".to_string()
-            } else if e.is_timeout() {
-                "Request to DeepInfra timed out. The API server is not responding.".to_string()
-            } else if e.to_string().contains("builder") {
-                format!("Failed to build DeepInfra API request: {e}. This may be a configuration error.")
-            } else {
-                format!("DeepInfra network error: {e}")
-            };
-            return Err(anyhow::anyhow!(error_msg));
+
+        // Whisper translates to English instead of transcribing when asked to
+        if config.translate {
+            form = form.text("task", "translate");
+            debug_params.push("task=translate".to_string());
         }
-    };
 
-    if !response.status().is_success() {
-        let status = response.status();
-        let error_body = response.text().await.unwrap_or_else(|_| "Unknown error".to_string());
+        let options = &config.providers.deepinfra;
 
-        let human_readable = match status.as_u16() {
-            401 => "DeepInfra API key is invalid or expired. Please run 'ostt auth' to update your API key.".to_string(),
-            403 => "You don't have permission to use DeepInfra's API. Check your API key and account status.".to_string(),
-            429 => "Too many requests to DeepInfra. You've hit the API rate limit. Please wait and try again.".to_string(),
-            500 | 502 | 503 | 504 => "DeepInfra API server is experiencing issues. Please try again later.".to_string(),
-            _ => format!("DeepInfra API error (status {status}): {error_body}"),
-        };
+        // Add keywords as prompt for better transcription context (similar to OpenAI)
+        if let Some(prompt) = model_prompt(options, config) {
+            form = form.text("prompt", prompt.clone());
+            debug_params.push(format!("prompt={prompt}"));
+            tracing::debug!("Keywords used as prompt for DeepInfra model: {:?}", config.keywords);
+        }
 
-        return Err(anyhow::anyhow!(human_readable));
-    }
+        if let Some(temperature) = options.temperature {
+            form = form.text("temperature", temperature.to_string());
+            debug_params.push(format!("temperature={temperature}"));
+        }
+
+        // The inference API answers with JSON including segments unless told otherwise
+        if let Some(response_format) = options.response_format {
+            form = form.text("response_format", response_format.as_str());
+            debug_params.push(format!("response_format={}", response_format.as_str()));
+        }
 
-    let deepinfra_response: DeepInfraResponse = response
-        .json()
-        .await
-        .map_err(|e| anyhow::anyhow!("Failed to parse DeepInfra response: {e}"))?;
+        // DeepInfra takes a single timing level; word timings include the segments
+        if let Some(granularities) = &options.timestamp_granularities {
+            let level = if granularities.contains(&TimestampGranularity::Word) {
+                TimestampGranularity::Word
+            } else {
+                TimestampGranularity::Segment
+            };
+            form = form.text("chunk_level", level.as_str());
+            debug_params.push(format!("chunk_level={}", level.as_str()));
+        }
 
-    // Debug log: Log the full response for debugging
-    tracing::debug!(
-        "DeepInfra API Response:\n  Status: Success\n  Transcription length: {} characters\n  Full response: {:#?}",
-        deepinfra_response.text.len(),
-        deepinfra_response
-    );
+        tracing::debug!(
+            "DeepInfra API Call:\n  URL: {}\n  Method: POST\n  Headers:\n    Authorization: Bearer <redacted>\n    Content-Type: multipart/form-data\n  Body parameters: {}",
+            endpoint,
+            if debug_params.is_empty() {
+                "none".to_string()
+            } else {
+                debug_params.join("\n    ")
+            }
+        );
+
+        Ok(client
+            .post(&endpoint)
+            .bearer_auth(&config.api_key)
+            .multipart(form))
+    }
 
-    Ok(deepinfra_response.text)
+    fn parse_response(
+        &self,
+        config: &TranscriptionConfig,
+        body: &[u8],
+    ) -> anyhow::Result<TranscriptionResponse> {
+        let response_format = config
+            .providers
+            .deepinfra
+            .response_format
+            .unwrap_or(ResponseFormat::Json);
+        WhisperResponse::parse(self.name(), response_format, body)
+    }
 }
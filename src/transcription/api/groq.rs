@@ -2,117 +2,167 @@
 //!
 //! Handles transcription requests to Groq's OpenAI-compatible Whisper API using multipart form data.
 
-use std::path::Path;
-use serde::Deserialize;
-
-use super::TranscriptionConfig;
-
-/// Groq API response wrapper
-#[derive(Debug, Deserialize)]
-struct GroqResponse {
-    text: String,
-}
-
-/// Transcribes an audio file using Groq's Whisper API.
+use super::{
+    audio_part, base_url, model_prompt, timestamp_granularities, whisper_variant,
+    TranscriptionConfig, WhisperResponse,
+};
+use crate::config::file::{ProvidersConfig, ResponseFormat};
+use crate::transcription::language;
+use crate::transcription::model::{KeywordSupport, TranscriptionModel};
+use crate::transcription::provider::{AudioUpload, Provider};
+use crate::transcription::response::TranscriptionResponse;
+
+/// Public Groq API; transcriptions and translations are below it
+const BASE_URL: &str = "https://api.groq.com/openai/v1";
+
+/// Largest file accepted by the transcription endpoint on the free tier
+const MAX_UPLOAD_BYTES: u64 = 25 * 1024 * 1024;
+
+/// Groq transcription provider.
 ///
 /// Uses multipart form data with bearer token authentication.
 /// Groq provides an OpenAI-compatible API endpoint.
-/// 
-/// Keywords are passed as the `prompt` parameter to guide transcription context.
-pub async fn transcribe(
-    config: &TranscriptionConfig,
-    audio_path: &Path,
-) -> anyhow::Result<String> {
-    let audio_data = std::fs::read(audio_path).map_err(|e| {
-        anyhow::anyhow!("Failed to read audio file: {e}")
-    })?;
-
-    let client = reqwest::Client::new();
-
-    let file_name = audio_path
-        .file_name()
-        .unwrap_or_default()
-        .to_string_lossy()
-        .to_string();
-
-    let file_part = reqwest::multipart::Part::bytes(audio_data)
-        .file_name(file_name.clone())
-        .mime_str("audio/mpeg")
-        .map_err(|e| anyhow::anyhow!("Failed to create file part for upload: {e}"))?;
-
-    let mut form = reqwest::multipart::Form::new()
-        .part("file", file_part)
-        .text("model", config.model.api_model_name().to_string());
-
-    // Debug log: Log the API call details (without the audio data)
-    let mut debug_params = vec![
-        format!("model={}", config.model.api_model_name()),
-    ];
-
-    // Add keywords as prompt for better transcription context
-    if !config.keywords.is_empty() {
-        let prompt = config.keywords.join(", ");
-        form = form.text("prompt", prompt.clone());
-        debug_params.push(format!("prompt={prompt}"));
-        tracing::debug!("Keywords used as prompt for Groq model: {:?}", config.keywords);
+///
+/// Keywords are passed as the `prompt` parameter to guide transcription context, after the
+/// prompt text from `[providers.groq]`.
+pub struct GroqProvider;
+
+/// Returns the configured response format; verbose_json adds the detected language,
+/// segments and word timestamps
+fn response_format(config: &TranscriptionConfig) -> ResponseFormat {
+    config
+        .providers
+        .groq
+        .response_format
+        .unwrap_or(ResponseFormat::VerboseJson)
+}
+
+impl Provider for GroqProvider {
+    fn id(&self) -> &str {
+        "groq"
     }
 
-    let endpoint = config.model.endpoint();
-
-    tracing::debug!(
-        "Groq API Call:\n  URL: {}\n  Method: POST\n  Headers:\n    Authorization: Bearer <redacted>\n    Content-Type: multipart/form-data\n  Body parameters: {}",
-        endpoint,
-        debug_params.join("\n    ")
-    );
-
-    let response = match client
-        .post(endpoint)
-        .bearer_auth(&config.api_key)
-        .multipart(form)
-        .send()
-        .await
-    {
-        Ok(resp) => resp,
-        Err(e) => {
-            let error_msg = if e.is_connect() {
-                "Failed to connect to Groq API server. Check your internet connection.".to_string()
-            } else if e.is_timeout() {
-                "Request to Groq timed out. The API server is not responding.".to_string()
-            } else if e.to_string().contains("builder") {
-                format!("Failed to build Groq API request: {e}. This may be a configuration error.")
-            } else {
-                format!("Groq network error: {e}")
-            };
-            return Err(anyhow::anyhow!(error_msg));
-        }
-    };
-
-    if !response.status().is_success() {
-        let status = response.status();
-        let error_body = response.text().await.unwrap_or_else(|_| "Unknown error".to_string());
-
-        let human_readable = match status.as_u16() {
-            401 => "Groq API key is invalid or expired. Please run 'ostt auth' to update your API key.".to_string(),
-            403 => "You don't have permission to use Groq's API. Check your API key and account status.".to_string(),
-            429 => "Too many requests to Groq. You've hit the API rate limit. Please wait and try again.".to_string(),
-            500 | 502 | 503 | 504 => "Groq API server is experiencing issues. Please try again later.".to_string(),
-            _ => format!("Groq API error (status {status}): {error_body}"),
-        };
+    fn name(&self) -> &str {
+        "Groq"
+    }
+
+    fn models(&self) -> Vec<TranscriptionModel> {
+        vec![
+            TranscriptionModel::new(
+                "groq-whisper-large-v3",
+                self.id(),
+                "whisper-large-v3",
+                "Whisper Large V3 (high accuracy)",
+            )
+            .with_prompt()
+            .with_keywords(KeywordSupport::Prompt)
+            .with_max_upload_bytes(MAX_UPLOAD_BYTES)
+            .with_translation()
+            .with_timestamps()
+            // $0.111 per hour
+            .with_price_per_minute(0.111 / 60.0),
+            TranscriptionModel::new(
+                "groq-whisper-large-v3-turbo",
+                self.id(),
+                "whisper-large-v3-turbo",
+                "Whisper Large V3 Turbo (fastest)",
+            )
+            .with_prompt()
+            .with_keywords(KeywordSupport::Prompt)
+            .with_max_upload_bytes(MAX_UPLOAD_BYTES)
+            .with_timestamps()
+            // $0.04 per hour
+            .with_price_per_minute(0.04 / 60.0),
+        ]
+    }
+
+    fn models_request(
+        &self,
+        client: &reqwest::Client,
+        providers: &ProvidersConfig,
+        api_key: &str,
+    ) -> Option<reqwest::RequestBuilder> {
+        let base_url = base_url(providers.groq.base_url.as_deref(), BASE_URL);
+        Some(client.get(format!("{base_url}/models")).bearer_auth(api_key))
+    }
 
-        return Err(anyhow::anyhow!(human_readable));
+    fn discovered_model(&self, api_name: &str) -> Option<TranscriptionModel> {
+        // Groq mostly lists language models; its speech models are Whisper variants
+        api_name.contains("whisper").then(|| {
+            whisper_variant(&format!("groq-{api_name}"), self.id(), api_name)
+                .with_max_upload_bytes(MAX_UPLOAD_BYTES)
+        })
     }
 
-    let groq_response: GroqResponse = response
-        .json()
-        .await
-        .map_err(|e| anyhow::anyhow!("Failed to parse Groq response: {e}"))?;
+    fn build_request(
+        &self,
+        client: &reqwest::Client,
+        config: &TranscriptionConfig,
+        audio: AudioUpload,
+    ) -> anyhow::Result<reqwest::RequestBuilder> {
+        let mut form = reqwest::multipart::Form::new()
+            .part("file", audio_part(audio)?)
+            .text("model", config.model.api_model_name().to_string());
+
+        // Debug log: Log the API call details (without the audio data)
+        let mut debug_params = vec![
+            format!("model={}", config.model.api_model_name()),
+        ];
+
+        // Without a language hint the model detects the language itself; the translation
+        // endpoint always detects it
+        if let (Some(language), false) = (&config.language, config.translate) {
+            let language = language::base_code(language);
+            form = form.text("language", language.to_string());
+            debug_params.push(format!("language={language}"));
+        }
+
+        let options = &config.providers.groq;
 
-    // Debug log: Log the full response for debugging
-    tracing::debug!(
-        "Groq API Response:\n  Status: Success\n  Transcription length: {} characters\n  Full response: {:#?}",
-        groq_response.text.len(),
-        groq_response
-    );
+        // Add keywords as prompt for better transcription context
+        if let Some(prompt) = model_prompt(options, config) {
+            form = form.text("prompt", prompt.clone());
+            debug_params.push(format!("prompt={prompt}"));
+            tracing::debug!("Keywords used as prompt for Groq model: {:?}", config.keywords);
+        }
 
-    Ok(groq_response.text)
+        if let Some(temperature) = options.temperature {
+            form = form.text("temperature", temperature.to_string());
+            debug_params.push(format!("temperature={temperature}"));
+        }
+
+        // Translations have segments only, without requesting timestamp granularities
+        let response_format = response_format(config);
+        form = form.text("response_format", response_format.as_str());
+        debug_params.push(format!("response_format={}", response_format.as_str()));
+        for granularity in timestamp_granularities(options, response_format, config.translate) {
+            form = form.text("timestamp_granularities[]", granularity.as_str());
+            debug_params.push(format!("timestamp_granularities[]={}", granularity.as_str()));
+        }
+        let base_url = base_url(config.providers.groq.base_url.as_deref(), BASE_URL);
+        let url = if config.translate {
+            format!("{base_url}/audio/translations")
+        } else {
+            format!("{base_url}/audio/transcriptions")
+        };
+
+        tracing::debug!(
+            "Groq API Call:\n  URL: {}\n  Method: POST\n  Headers:\n    Authorization: Bearer <redacted>\n    Content-Type: multipart/form-data\n  Body parameters: {}",
+            url,
+            debug_params.join("\n    ")
+        );
+
+        Ok(client
+            .post(&url)
+            .bearer_auth(&config.api_key)
+            .multipart(form))
+    }
+
+    fn parse_response(
+        &self,
+        config: &TranscriptionConfig,
+        body: &[u8],
+    ) -> anyhow::Result<TranscriptionResponse> {
+        WhisperResponse::parse(self.name(), response_format(config), body)
+    }
 }
@@ -0,0 +1,342 @@
+//! Offline transcription using whisper.cpp.
+//!
+//! Loads a GGML/GGUF Whisper model from disk and runs inference on the CPU, so no API key
+//! or network connection is needed. The recording is read straight from the uncompressed
+//! WAV written by `AudioRecorder`, which means ffmpeg is not involved on this path.
+//!
+//! Loaded models are kept in memory for the rest of the process, so only the first
+//! transcription with a model pays for reading it from disk.
+//!
+//! Only available when ostt is built with the `local-whisper` cargo feature.
+
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex, OnceLock};
+
+use whisper_rs::WhisperContext;
+
+use super::TranscriptionConfig;
+use crate::recording::encode::{read_wav, resample};
+use crate::recording::AudioFormat;
+use crate::transcription::language;
+use crate::transcription::model::{KeywordSupport, TranscriptionModel};
+use crate::transcription::provider::{AudioUpload, Provider, ProviderCapabilities};
+use crate::transcription::response::{Segment, TranscriptionResponse};
+
+/// Sample rate expected by Whisper models
+const WHISPER_SAMPLE_RATE: u32 = 16000;
+
+/// Models loaded so far, by path
+static CONTEXTS: OnceLock<Mutex<HashMap<PathBuf, Arc<WhisperContext>>>> = OnceLock::new();
+
+/// Offline whisper.cpp transcription provider.
+///
+/// Inference is CPU-bound, so it runs on a blocking thread to keep the UI responsive.
+/// Dropping the transcription future (a cancelled transcription) aborts the inference.
+///
+/// Keywords are passed as the initial prompt to bias the decoder towards them.
+pub struct LocalProvider;
+
+#[async_trait::async_trait]
+impl Provider for LocalProvider {
+    fn id(&self) -> &str {
+        "local"
+    }
+
+    fn name(&self) -> &str {
+        "Local"
+    }
+
+    fn models(&self) -> Vec<TranscriptionModel> {
+        vec![TranscriptionModel::new(
+            "local-whisper",
+            self.id(),
+            "whisper.cpp",
+            "Whisper (offline, model file from disk)",
+        )
+        .with_prompt()
+        .with_keywords(KeywordSupport::Prompt)
+        .with_translation()
+        .with_timestamps()
+        .with_formats(&[AudioFormat::Wav])
+        .with_price_per_minute(0.0)]
+    }
+
+    fn capabilities(&self) -> ProviderCapabilities {
+        ProviderCapabilities {
+            requires_api_key: false,
+            local: true,
+        }
+    }
+
+    fn build_request(
+        &self,
+        _client: &reqwest::Client,
+        _config: &TranscriptionConfig,
+        _audio: AudioUpload,
+    ) -> anyhow::Result<reqwest::RequestBuilder> {
+        Err(anyhow::anyhow!("Local provider does not use HTTP"))
+    }
+
+    fn parse_response(
+        &self,
+        _config: &TranscriptionConfig,
+        _body: &[u8],
+    ) -> anyhow::Result<TranscriptionResponse> {
+        Err(anyhow::anyhow!("Local provider does not use HTTP"))
+    }
+
+    async fn transcribe(
+        &self,
+        config: &TranscriptionConfig,
+        audio_path: &Path,
+    ) -> anyhow::Result<TranscriptionResponse> {
+        let model_path = expand_home(&config.providers.local.model_path)?;
+        if !model_path.exists() {
+            return Err(anyhow::anyhow!(
+                "Local Whisper model not found at {}. Download a GGML model (e.g. ggml-base.bin) and set model_path in [providers.local].",
+                model_path.display()
+            ));
+        }
+
+        let samples = read_wav_mono_16k(audio_path)?;
+        let threads = match config.providers.local.threads {
+            0 => std::thread::available_parallelism()
+                .map(|n| n.get())
+                .unwrap_or(4),
+            n => n as usize,
+        };
+        let prompt = config.keywords.join(", ");
+        let translate = config.translate;
+        let language = config
+            .language
+            .as_deref()
+            .map(|language| language::base_code(language).to_string());
+
+        tracing::debug!(
+            "Local Whisper Call:\n  Model: {}\n  Threads: {}\n  Samples: {} at {}Hz\n  Language: {}\n  Translate: {}\n  Prompt: {}",
+            model_path.display(),
+            threads,
+            samples.len(),
+            WHISPER_SAMPLE_RATE,
+            language.as_deref().unwrap_or(language::AUTO),
+            translate,
+            if prompt.is_empty() { "none" } else { &prompt }
+        );
+
+        // Dropping the future doesn't stop the blocking thread, so the guard tells
+        // whisper.cpp to abort instead
+        let cancelled = Arc::new(AtomicBool::new(false));
+        let _cancel_on_drop = CancelOnDrop(Arc::clone(&cancelled));
+        tokio::task::spawn_blocking(move || {
+            let language = language.as_deref();
+            run_whisper(&model_path, &samples, threads, language, translate, &prompt, cancelled)
+        })
+            .await
+            .map_err(|e| anyhow::anyhow!("Local transcription task failed: {e}"))?
+    }
+}
+
+/// Sets its flag when dropped, aborting the inference that checks the flag.
+struct CancelOnDrop(Arc<AtomicBool>);
+
+impl Drop for CancelOnDrop {
+    fn drop(&mut self) {
+        self.0.store(true, Ordering::Relaxed);
+    }
+}
+
+/// Runs whisper.cpp over 16 kHz mono samples and returns the joined segment text with
+/// segment timings and the detected language. `language` is `None` for auto-detection;
+/// with `translate`, the text is translated to English. whisper.cpp stops early once
+/// `cancelled` is set.
+fn run_whisper(
+    model_path: &Path,
+    samples: &[f32],
+    threads: usize,
+    language: Option<&str>,
+    translate: bool,
+    prompt: &str,
+    cancelled: Arc<AtomicBool>,
+) -> anyhow::Result<TranscriptionResponse> {
+    use whisper_rs::{FullParams, SamplingStrategy};
+
+    let context = load_context(model_path)?;
+    let mut state = context
+        .create_state()
+        .map_err(|e| anyhow::anyhow!("Failed to initialize Whisper state: {e}"))?;
+
+    let mut params = FullParams::new(SamplingStrategy::Greedy { best_of: 1 });
+    params.set_n_threads(threads as i32);
+    params.set_print_special(false);
+    params.set_print_progress(false);
+    params.set_print_realtime(false);
+    params.set_print_timestamps(false);
+    // English-only models ignore the language; multilingual ones detect it unless it is set
+    params.set_language(Some(match (context.is_multilingual(), language) {
+        (false, _) => "en",
+        (true, Some(language)) => language,
+        (true, None) => language::AUTO,
+    }));
+    params.set_translate(translate);
+    if !prompt.is_empty() {
+        params.set_initial_prompt(prompt);
+    }
+    let abort = Arc::clone(&cancelled);
+    params.set_abort_callback_safe(move || abort.load(Ordering::Relaxed));
+
+    state.full(params, samples).map_err(|e| {
+        if cancelled.load(Ordering::Relaxed) {
+            anyhow::anyhow!("Local transcription cancelled")
+        } else {
+            anyhow::anyhow!("Local transcription failed: {e}")
+        }
+    })?;
+
+    let segment_count = state
+        .full_n_segments()
+        .map_err(|e| anyhow::anyhow!("Failed to read transcription segments: {e}"))?;
+
+    let mut text = String::new();
+    let mut segments = Vec::new();
+    for segment in 0..segment_count {
+        let segment_text = state
+            .full_get_segment_text(segment)
+            .map_err(|e| anyhow::anyhow!("Failed to read transcription segment: {e}"))?;
+        text.push_str(&segment_text);
+
+        // Segment timestamps are in units of 10 ms
+        let start = state.full_get_segment_t0(segment).unwrap_or(0) as f64 / 100.0;
+        let end = state.full_get_segment_t1(segment).unwrap_or(0) as f64 / 100.0;
+        segments.push(Segment {
+            start,
+            end,
+            text: segment_text.trim().to_string(),
+            confidence: None,
+            speaker: None,
+        });
+    }
+
+    let language = state
+        .full_lang_id_from_state()
+        .ok()
+        .and_then(whisper_rs::get_lang_str)
+        .map(str::to_string);
+
+    let text = text.trim().to_string();
+    tracing::debug!(
+        "Local Whisper Response:\n  Segments: {}\n  Transcription length: {} characters",
+        segment_count,
+        text.len()
+    );
+
+    Ok(TranscriptionResponse {
+        text,
+        language,
+        duration: Some(samples.len() as f64 / WHISPER_SAMPLE_RATE as f64),
+        segments,
+        ..TranscriptionResponse::default()
+    })
+}
+
+/// Returns the whisper.cpp context of a model, loading it on first use.
+///
+/// The lock is held while loading, so concurrent transcriptions with the same model load it
+/// only once.
+fn load_context(model_path: &Path) -> anyhow::Result<Arc<WhisperContext>> {
+    use whisper_rs::WhisperContextParameters;
+
+    let mut contexts = CONTEXTS
+        .get_or_init(Default::default)
+        .lock()
+        .unwrap_or_else(|e| e.into_inner());
+    if let Some(context) = contexts.get(model_path) {
+        return Ok(Arc::clone(context));
+    }
+
+    // Route whisper.cpp's own logging into tracing instead of stderr (which would corrupt the TUI)
+    whisper_rs::install_logging_hooks();
+
+    let model_path_str = model_path
+        .to_str()
+        .ok_or_else(|| anyhow::anyhow!("Model path is not valid UTF-8: {}", model_path.display()))?;
+    tracing::info!("Loading Whisper model {}", model_path.display());
+    let context = WhisperContext::new_with_params(model_path_str, WhisperContextParameters::default())
+        .map_err(|e| anyhow::anyhow!("Failed to load Whisper model {}: {e}", model_path.display()))?;
+    let context = Arc::new(context);
+    contexts.insert(model_path.to_path_buf(), Arc::clone(&context));
+    Ok(context)
+}
+
+/// Reads a WAV file as the 16 kHz mono f32 samples Whisper expects.
+///
+/// Accepts any WAV the recorder or ffmpeg writes; channels are averaged and the signal is
+/// resampled with the encoder's resampler.
+fn read_wav_mono_16k(path: &Path) -> anyhow::Result<Vec<f32>> {
+    let (samples, sample_rate) = read_wav(path)
+        .ok_or_else(|| anyhow::anyhow!("Failed to read WAV file {}", path.display()))?;
+    Ok(resample(&samples, sample_rate, WHISPER_SAMPLE_RATE)
+        .into_iter()
+        .map(|sample| sample as f32 / 32768.0)
+        .collect())
+}
+
+/// Expands a leading "~" to the user's home directory.
+fn expand_home(path: &str) -> anyhow::Result<PathBuf> {
+    match path.strip_prefix("~/") {
+        Some(rest) => Ok(dirs::home_dir()
+            .ok_or_else(|| anyhow::anyhow!("Could not determine home directory"))?
+            .join(rest)),
+        None => Ok(PathBuf::from(path)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::file::{NetworkConfig, ProvidersConfig};
+
+    #[test]
+    fn converts_wav_to_16k_mono() {
+        let path = std::env::temp_dir().join(format!("ostt-local-{}.wav", std::process::id()));
+        let spec = hound::WavSpec {
+            channels: 2,
+            sample_rate: 48000,
+            bits_per_sample: 16,
+            sample_format: hound::SampleFormat::Int,
+        };
+        let mut writer = hound::WavWriter::create(&path, spec).unwrap();
+        // One second of a constant signal, louder on the left channel
+        for _ in 0..48000 {
+            writer.write_sample(16384i16).unwrap();
+            writer.write_sample(0i16).unwrap();
+        }
+        writer.finalize().unwrap();
+
+        let samples = read_wav_mono_16k(&path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+        assert_eq!(samples.len(), 16000);
+        assert!(samples.iter().all(|&s| (s - 0.25).abs() < 1e-3));
+    }
+
+    #[tokio::test]
+    async fn reports_missing_model() {
+        let mut providers = ProvidersConfig::default();
+        providers.local.model_path = "/nonexistent/ggml-base.bin".to_string();
+        let model = LocalProvider.models().remove(0);
+        let config = TranscriptionConfig::new(
+            model,
+            String::new(),
+            Vec::new(),
+            providers,
+            NetworkConfig::default(),
+        );
+
+        let error = LocalProvider
+            .transcribe(&config, Path::new("/nonexistent/recording.wav"))
+            .await
+            .unwrap_err();
+        assert!(error.to_string().contains("model not found"), "{error}");
+    }
+}
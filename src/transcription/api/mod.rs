@@ -1,20 +1,35 @@
 //! Transcription API client with provider-specific implementations.
 //!
-//! This module provides a trait-based system for handling multiple transcription providers
-//! (OpenAI, Deepgram, etc.) with their respective APIs. Each provider implements the
-//! `TranscriptionProvider` trait to handle authentication and API communication.
+//! Each provider module implements the `Provider` trait for its API (OpenAI, Deepgram, etc.).
+//! This module holds the shared request flow that drives those implementations and the
+//! entry point that dispatches a transcription to the provider serving the selected model.
+//! Providers with a multi-step workflow (e.g. AssemblyAI's upload, job and polling) reuse
+//! the retrying request step, `execute`, for each of their requests.
 
-mod openai;
-mod deepgram;
-mod deepinfra;
-mod groq;
+pub(crate) mod openai;
+pub(crate) mod assemblyai;
+pub(crate) mod deepgram;
+pub(crate) mod deepinfra;
+pub(crate) mod groq;
+pub(crate) mod custom;
+#[cfg(feature = "local-whisper")]
+pub(crate) mod local;
 
 use serde::Deserialize;
 use std::path::Path;
 
-use super::model::TranscriptionModel;
-use super::provider::TranscriptionProvider;
-use crate::config::file::ProvidersConfig;
+use super::cleanup::Cleanup;
+use super::http::{self, RetryPolicy};
+use super::language;
+use super::model::{KeywordSupport, TranscriptionModel};
+use super::progress::{ProgressReporter, TranscriptionStatus, UploadProgress};
+use super::provider::{AudioUpload, Provider};
+use super::registry::ProviderRegistry;
+use super::response::{confidence_from_logprob, Segment, TranscriptionResponse, Word};
+use crate::config::file::{
+    NetworkConfig, ProvidersConfig, ResponseFormat, TimestampGranularity, WhisperApiConfig,
+};
+use crate::recording::{encode, AudioFormat};
 
 /// Configuration for transcription requests
 #[derive(Debug, Clone)]
@@ -27,6 +42,16 @@ pub struct TranscriptionConfig {
     pub keywords: Vec<String>,
     /// Provider-specific configurations
     pub providers: ProvidersConfig,
+    /// Timeouts and retry policy
+    pub network: NetworkConfig,
+    /// Spoken language as an ISO 639-1 code, `None` to let the provider detect it
+    pub language: Option<String>,
+    /// Translate the speech to English instead of transcribing it
+    pub translate: bool,
+    /// Where retry and progress updates are reported, if anyone is listening
+    pub progress: Option<ProgressReporter>,
+    /// Where providers register the deletion of server-side jobs, if the caller runs them
+    pub cleanup: Option<Cleanup>,
 }
 
 impl TranscriptionConfig {
@@ -36,29 +61,258 @@ impl TranscriptionConfig {
         api_key: String,
         keywords: Vec<String>,
         providers: ProvidersConfig,
+        network: NetworkConfig,
     ) -> Self {
         Self {
             model,
             api_key,
             keywords,
             providers,
+            network,
+            language: None,
+            translate: false,
+            progress: None,
+            cleanup: None,
         }
     }
+
+    /// Sets the spoken language (`None` for automatic detection)
+    pub fn with_language(mut self, language: Option<String>) -> Self {
+        self.language = language;
+        self
+    }
+
+    /// Translates the speech to English instead of transcribing it
+    pub fn with_translate(mut self, translate: bool) -> Self {
+        self.translate = translate;
+        self
+    }
+
+    /// Reports progress updates (stages, upload progress, retries) to the given reporter
+    pub fn with_progress(mut self, progress: ProgressReporter) -> Self {
+        self.progress = Some(progress);
+        self
+    }
+
+    /// Registers the deletion of jobs left on the server with the given cleanup, which the
+    /// caller runs when the transcription is cancelled
+    pub fn with_cleanup(mut self, cleanup: Cleanup) -> Self {
+        self.cleanup = Some(cleanup);
+        self
+    }
+
+    /// Publishes a status update if a progress reporter is attached
+    pub fn report(&self, status: TranscriptionStatus) {
+        if let Some(progress) = &self.progress {
+            progress.report(status);
+        }
+    }
+}
+
+/// Response body of OpenAI-compatible transcription APIs.
+///
+/// Covers both the plain `json` format (text only) and `verbose_json`, which adds the
+/// language, duration, segments and (when requested) word timestamps. Shared by the OpenAI,
+/// Groq, DeepInfra and custom providers.
+#[derive(Debug, Deserialize)]
+pub(crate) struct WhisperResponse {
+    text: String,
+    #[serde(default)]
+    language: Option<String>,
+    #[serde(default)]
+    duration: Option<f64>,
+    #[serde(default)]
+    segments: Option<Vec<WhisperSegment>>,
+    #[serde(default)]
+    words: Option<Vec<WhisperWord>>,
+}
+
+#[derive(Debug, Deserialize)]
+struct WhisperSegment {
+    start: f64,
+    end: f64,
+    text: String,
+    #[serde(default)]
+    avg_logprob: Option<f64>,
+}
+
+#[derive(Debug, Deserialize)]
+struct WhisperWord {
+    word: String,
+    start: f64,
+    end: f64,
+}
+
+impl From<WhisperResponse> for TranscriptionResponse {
+    fn from(response: WhisperResponse) -> Self {
+        TranscriptionResponse {
+            text: response.text,
+            language: response.language,
+            confidence: None,
+            duration: response.duration,
+            segments: response
+                .segments
+                .unwrap_or_default()
+                .into_iter()
+                .map(|s| Segment {
+                    start: s.start,
+                    end: s.end,
+                    text: s.text.trim().to_string(),
+                    confidence: s.avg_logprob.map(confidence_from_logprob),
+                    speaker: None,
+                })
+                .collect(),
+            words: response
+                .words
+                .unwrap_or_default()
+                .into_iter()
+                .map(|w| Word {
+                    word: w.word,
+                    start: w.start,
+                    end: w.end,
+                    confidence: None,
+                    speaker: None,
+                })
+                .collect(),
+        }
+        .with_derived_confidence()
+    }
+}
+
+impl WhisperResponse {
+    /// Parses a response body in the requested format.
+    ///
+    /// JSON formats are decoded; plain text and subtitle formats are the transcript itself.
+    ///
+    /// # Errors
+    /// - If a JSON body cannot be parsed
+    pub(crate) fn parse(
+        provider_name: &str,
+        format: ResponseFormat,
+        body: &[u8],
+    ) -> anyhow::Result<TranscriptionResponse> {
+        if !format.is_json() {
+            let text = String::from_utf8_lossy(body).trim().to_string();
+            return Ok(TranscriptionResponse::from_text(text));
+        }
+
+        let response: WhisperResponse = serde_json::from_slice(body)
+            .map_err(|e| anyhow::anyhow!("Failed to parse {provider_name} response: {e}"))?;
+
+        // Debug log: Log the full response for debugging
+        tracing::debug!(
+            "{} API Response:\n  Status: Success\n  Full response: {:#?}",
+            provider_name,
+            response
+        );
+
+        Ok(response.into())
+    }
+}
+
+/// Returns the API base URL of a provider, without a trailing slash.
+///
+/// The `base_url` from the provider's config section is used if set, otherwise the
+/// provider's public API. `OSTT_<PROVIDER>_BASE_URL` environment variables are applied to the
+/// config when it is loaded, so requests only depend on the config they are given.
+pub(crate) fn base_url(configured: Option<&str>, default: &str) -> String {
+    configured
+        .unwrap_or(default)
+        .trim()
+        .trim_end_matches('/')
+        .to_string()
+}
+
+/// Builds the prompt for the Whisper-style APIs: the configured prompt text followed by the
+/// keywords. Returns `None` if both are empty.
+pub(crate) fn whisper_prompt(options: &WhisperApiConfig, keywords: &[String]) -> Option<String> {
+    let keywords = keywords.join(", ");
+    match (options.prompt.trim(), keywords.as_str()) {
+        ("", "") => None,
+        (prompt, "") => Some(prompt.to_string()),
+        ("", keywords) => Some(keywords.to_string()),
+        (prompt, keywords) => Some(format!("{prompt} {keywords}")),
+    }
 }
 
-/// Response from transcription APIs (unified across providers).
-#[derive(Debug, Clone, Deserialize)]
-pub struct TranscriptionResponse {
-    /// The transcribed text from the audio file
-    pub text: String,
+/// Builds the Whisper-style prompt for a request from what the model takes: the configured
+/// prompt text if it takes a prompt, and the keywords if it takes them in the prompt.
+pub(crate) fn model_prompt(
+    options: &WhisperApiConfig,
+    config: &TranscriptionConfig,
+) -> Option<String> {
+    let capabilities = config.model.capabilities();
+    if !capabilities.prompt {
+        if let Some(prompt) = whisper_prompt(options, &config.keywords) {
+            tracing::debug!(
+                "{} does not support a prompt, not sending: {}",
+                config.model.id(),
+                prompt
+            );
+        }
+        return None;
+    }
+    let keywords: &[String] = match capabilities.keywords {
+        KeywordSupport::Prompt => &config.keywords,
+        _ => &[],
+    };
+    whisper_prompt(options, keywords)
+}
+
+/// Describes a Whisper variant found in a provider's model list.
+///
+/// Whisper takes a prompt and returns timestamps. English-only variants (`-en`) transcribe
+/// English only, and neither they nor the turbo variants can translate.
+pub(crate) fn whisper_variant(
+    id: &str,
+    provider_id: &str,
+    api_name: &str,
+) -> TranscriptionModel {
+    let name = api_name.to_lowercase();
+    let english_only = name.ends_with("-en") || name.ends_with(".en");
+    let model = TranscriptionModel::new(id, provider_id, api_name, discovered_description(api_name))
+        .with_prompt()
+        .with_keywords(KeywordSupport::Prompt)
+        .with_timestamps();
+    match (english_only, name.contains("turbo")) {
+        (true, _) => model.with_languages(&["en"]),
+        (false, true) => model,
+        (false, false) => model.with_translation(),
+    }
+}
+
+/// Description of a model found in a provider's model list
+pub(crate) fn discovered_description(api_name: &str) -> String {
+    format!("{api_name} (discovered)")
+}
+
+/// Returns the timestamp granularities to request.
+///
+/// Timestamps are only available with `verbose_json` and not from translation endpoints;
+/// unless configured otherwise, both segment and word timestamps are requested.
+pub(crate) fn timestamp_granularities(
+    options: &WhisperApiConfig,
+    format: ResponseFormat,
+    translate: bool,
+) -> Vec<TimestampGranularity> {
+    if format != ResponseFormat::VerboseJson || translate {
+        return Vec::new();
+    }
+    options
+        .timestamp_granularities
+        .clone()
+        .unwrap_or_else(|| vec![TimestampGranularity::Segment, TimestampGranularity::Word])
 }
 
 /// Transcribes an audio file using the configured transcription model.
 ///
-/// This function routes the request to the appropriate provider-specific implementation
-/// based on the configured model. The caller doesn't need to know which provider is being used.
+/// Looks up the provider serving the configured model in the provider registry and hands
+/// the request to it. The caller doesn't need to know which provider is being used.
 ///
 /// # Errors
+/// - If no registered provider serves the configured model
+/// - If translation is requested but the model cannot translate
+/// - If the model doesn't accept the audio format and transcoding it fails
 /// - If the audio file cannot be read from disk
 /// - If the API request fails due to network issues (connection, timeout)
 /// - If the API returns an HTTP error (401 for invalid key, 429 for rate limit, etc.)
@@ -66,27 +320,388 @@ pub struct TranscriptionResponse {
 pub async fn transcribe(
     config: &TranscriptionConfig,
     audio_path: &Path,
-) -> anyhow::Result<String> {
+) -> anyhow::Result<TranscriptionResponse> {
+    let registry = ProviderRegistry::from_config(&config.providers);
+    let provider = registry.provider_for(&config.model).ok_or_else(|| {
+        anyhow::anyhow!(
+            "No provider '{}' is available for model '{}'",
+            config.model.provider_id(),
+            config.model.id()
+        )
+    })?;
+    check_translation(config, &registry)?;
+    check_language(config)?;
+
     tracing::info!(
-        "Transcribing with {} ({})",
-        config.model.provider().name(),
+        "{} with {} ({})",
+        if config.translate { "Translating" } else { "Transcribing" },
+        provider.name(),
         config.model.id()
     );
 
-    let result = match config.model.provider() {
-        TranscriptionProvider::OpenAI => {
-            openai::transcribe(config, audio_path).await
-        }
-        TranscriptionProvider::Deepgram => {
-            deepgram::transcribe(config, audio_path).await
+    let transcoded = transcode_if_needed(config, audio_path)?;
+    let upload_path = transcoded.as_deref().unwrap_or(audio_path);
+    let result = match check_upload(config, upload_path) {
+        Ok(()) => provider.transcribe(config, upload_path).await,
+        Err(e) => Err(e),
+    };
+    if let Some(path) = transcoded {
+        if let Err(e) = std::fs::remove_file(&path) {
+            tracing::debug!("Failed to remove transcoded file: {}", e);
         }
-        TranscriptionProvider::DeepInfra => {
-            deepinfra::transcribe(config, audio_path).await
+    }
+    let mut response = result?;
+
+    // Store languages as codes; providers that don't report one used the requested language
+    response.language = response
+        .language
+        .as_deref()
+        .map(language::normalize)
+        .or_else(|| config.language.clone());
+    Ok(response)
+}
+
+/// Transcodes the audio to the model's preferred format if the model doesn't accept its
+/// format, returning the path of the transcoded file.
+///
+/// # Errors
+/// - If the transcoding fails (e.g. ffmpeg is not installed)
+fn transcode_if_needed(
+    config: &TranscriptionConfig,
+    audio_path: &Path,
+) -> anyhow::Result<Option<std::path::PathBuf>> {
+    let model = &config.model;
+    let format = AudioFormat::from_path(audio_path);
+    if format.is_some_and(|format| model.accepts_format(format)) {
+        return Ok(None);
+    }
+
+    let target = model.preferred_format();
+    let stem = audio_path
+        .file_stem()
+        .map(|stem| stem.to_string_lossy().to_string())
+        .unwrap_or_default();
+    let output = std::env::temp_dir().join(format!(
+        "ostt_{}_{stem}_{}.{}",
+        std::process::id(),
+        model.id(),
+        target.extension()
+    ));
+    tracing::info!(
+        "{} doesn't accept {}, transcoding to {}",
+        model.id(),
+        format.map_or_else(|| "this audio format".to_string(), |f| f.to_string()),
+        target
+    );
+    config.report(TranscriptionStatus::Encoding);
+    encode::transcode(audio_path, &output, target.default_encoding())
+        .map_err(|e| anyhow::anyhow!("Failed to transcode audio to {target} for {}: {e}", model.id()))?;
+    Ok(Some(output))
+}
+
+/// Checks that the configured model can translate, if translation is requested.
+///
+/// # Errors
+/// - If translation is requested and the model doesn't support it; the error names the
+///   models that do
+pub fn check_translation(
+    config: &TranscriptionConfig,
+    registry: &ProviderRegistry,
+) -> anyhow::Result<()> {
+    if !config.translate || config.model.supports_translation() {
+        return Ok(());
+    }
+
+    let supported: Vec<String> = registry
+        .models()
+        .into_iter()
+        .filter(|model| model.supports_translation())
+        .map(|model| model.id().to_string())
+        .collect();
+    Err(anyhow::anyhow!(
+        "'{}' cannot translate to English. Models with translation support: {}",
+        config.model.id(),
+        supported.join(", ")
+    ))
+}
+
+/// Checks that the configured model transcribes the requested language.
+///
+/// # Errors
+/// - If a language is set and the model's capabilities don't include it
+pub fn check_language(config: &TranscriptionConfig) -> anyhow::Result<()> {
+    let capabilities = config.model.capabilities();
+    match &config.language {
+        Some(code) if !capabilities.supports_language(code) => Err(anyhow::anyhow!(
+            "'{}' doesn't transcribe '{}'. Supported languages: {}",
+            config.model.id(),
+            code,
+            capabilities.languages.join(", ")
+        )),
+        _ => Ok(()),
+    }
+}
+
+/// Checks an audio file against the model's upload limits before it is sent.
+///
+/// The duration is only checked for WAV files, the only ones whose length can be read
+/// without decoding them. Keywords the model can't take are logged and left out.
+///
+/// # Errors
+/// - If the file is larger than the model accepts
+/// - If the audio is longer than the model accepts
+pub fn check_upload(config: &TranscriptionConfig, audio_path: &Path) -> anyhow::Result<()> {
+    let model = &config.model;
+    let capabilities = model.capabilities();
+
+    if let Some(limit) = capabilities.max_upload_bytes {
+        let size = std::fs::metadata(audio_path).map_err(audio_read_error)?.len();
+        if size > limit {
+            return Err(anyhow::anyhow!(
+                "Recording is {:.1} MB, above the {} MB upload limit of '{}'",
+                size as f64 / (1024.0 * 1024.0),
+                limit / (1024 * 1024),
+                model.id()
+            ));
         }
-        TranscriptionProvider::Groq => {
-            groq::transcribe(config, audio_path).await
+    }
+
+    if let Some(limit) = capabilities.max_duration_secs {
+        let duration = hound::WavReader::open(audio_path)
+            .ok()
+            .map(|reader| reader.duration() as f64 / reader.spec().sample_rate as f64);
+        if let Some(duration) = duration.filter(|duration| *duration > limit as f64) {
+            return Err(anyhow::anyhow!(
+                "Recording is {:.0} seconds long, above the {} second limit of '{}'",
+                duration,
+                limit,
+                model.id()
+            ));
         }
-    }?;
+    }
 
-    Ok(result)
+    if !config.keywords.is_empty() && capabilities.keywords == KeywordSupport::None {
+        tracing::warn!("{} doesn't take keywords, sending none", model.id());
+    }
+    Ok(())
+}
+
+/// Runs the shared HTTP request flow for a provider.
+///
+/// Reads the audio file, lets the provider build the request, sends it through `execute`
+/// and parses the response body with the provider's hook.
+///
+/// # Errors
+/// - If the audio file cannot be read from disk
+/// - If the request cannot be built or sent
+/// - If the API returns an HTTP error (after exhausting retries for transient ones)
+/// - If the API response cannot be parsed
+pub async fn send<P: Provider + ?Sized>(
+    provider: &P,
+    config: &TranscriptionConfig,
+    audio_path: &Path,
+) -> anyhow::Result<TranscriptionResponse> {
+    let audio = read_audio(provider, config, audio_path)?;
+    // Multipart bodies can't be cloned, so the request is rebuilt for every attempt
+    let upload_bytes = audio.len() as u64;
+    let body = execute(provider, config, upload_bytes, |client| {
+        provider.build_request(client, config, audio.clone())
+    })
+    .await?;
+    config.report(TranscriptionStatus::Parsing {
+        provider: provider.name().to_string(),
+    });
+    provider.parse_response(config, &body)
+}
+
+/// Reads an audio file for upload, with upload progress reported if anyone is listening.
+///
+/// # Errors
+/// - If the audio file cannot be read from disk
+pub(crate) fn read_audio<P: Provider + ?Sized>(
+    provider: &P,
+    config: &TranscriptionConfig,
+    audio_path: &Path,
+) -> anyhow::Result<AudioUpload> {
+    let data = std::fs::read(audio_path).map_err(audio_read_error)?;
+
+    let file_name = audio_path
+        .file_name()
+        .unwrap_or_default()
+        .to_string_lossy()
+        .to_string();
+
+    let mime_type = AudioFormat::from_path(audio_path)
+        .map_or("application/octet-stream", AudioFormat::mime_type);
+
+    Ok(AudioUpload {
+        data,
+        file_name,
+        mime_type,
+        progress: config
+            .progress
+            .clone()
+            .map(|reporter| UploadProgress::new(reporter, provider.name())),
+    })
+}
+
+/// Wraps an error reading the recording.
+///
+/// The I/O error stays the source, so that a fallback chain can tell it from API errors.
+fn audio_read_error(error: std::io::Error) -> anyhow::Error {
+    let message = format!("Failed to read audio file: {error}");
+    anyhow::Error::new(error).context(message)
+}
+
+/// Sends a request through the shared client and returns the body of the successful
+/// response.
+///
+/// `build` is called for every attempt, since request bodies can't always be cloned. The
+/// deadline of each attempt allows for uploading `upload_bytes`. Rate limits, server errors
+/// and dropped connections are retried according to the `[network]` retry policy, with each
+/// retry reported to the progress listener. Failures are mapped through the provider's
+/// error hooks.
+///
+/// # Errors
+/// - If the request cannot be built or sent
+/// - If the API returns an HTTP error (after exhausting retries for transient ones)
+/// - If the response body cannot be read
+pub(crate) async fn execute<P, F>(
+    provider: &P,
+    config: &TranscriptionConfig,
+    upload_bytes: u64,
+    build: F,
+) -> anyhow::Result<Vec<u8>>
+where
+    P: Provider + ?Sized,
+    F: Fn(&reqwest::Client) -> anyhow::Result<reqwest::RequestBuilder>,
+{
+    let client = http::client(&config.network)?;
+    let policy = RetryPolicy::from_config(&config.network);
+    let mut attempt = 0;
+
+    loop {
+        attempt += 1;
+        let timeout = http::request_timeout(&config.network, upload_bytes);
+        let request = build(&client)?.timeout(timeout);
+
+        let (error, retry_after, reason) = match request.send().await {
+            Ok(response) if response.status().is_success() => {
+                let body = response
+                    .bytes()
+                    .await
+                    .map_err(|e| anyhow::anyhow!("Failed to read {} response: {e}", provider.name()))?;
+                return Ok(body.to_vec());
+            }
+            Ok(response) => {
+                let status = response.status();
+                let retry_after = http::retry_after(response.headers());
+                let error_body = response.text().await.unwrap_or_else(|_| "Unknown error".to_string());
+                let error = provider.map_http_error(status, &error_body);
+                if !http::is_retryable_status(status) {
+                    return Err(error);
+                }
+                let reason = match status.canonical_reason() {
+                    Some(text) => format!("{} returned {} {text}", provider.name(), status.as_u16()),
+                    None => format!("{} returned {}", provider.name(), status.as_u16()),
+                };
+                (error, retry_after, reason)
+            }
+            Err(e) => {
+                let error = provider.map_network_error(&e);
+                if !http::is_retryable_error(&e) {
+                    return Err(error);
+                }
+                let reason = if e.is_timeout() {
+                    format!("{} timed out", provider.name())
+                } else {
+                    format!("Could not reach {}", provider.name())
+                };
+                (error, None, reason)
+            }
+        };
+
+        let Some(delay) = policy.delay(attempt, retry_after) else {
+            return Err(error);
+        };
+
+        tracing::warn!(
+            "{} request failed ({}), retrying in {:.1}s (attempt {}/{})",
+            provider.name(),
+            error,
+            delay.as_secs_f32(),
+            attempt,
+            policy.max_retries
+        );
+        config.report(TranscriptionStatus::Retrying {
+            attempt,
+            max_retries: policy.max_retries,
+            delay,
+            reason,
+        });
+        tokio::time::sleep(delay).await;
+        config.report(TranscriptionStatus::Transcribing);
+    }
+}
+
+/// Creates the multipart file part shared by the multipart-based providers.
+///
+/// # Errors
+/// - If the MIME type is rejected
+fn audio_part(audio: AudioUpload) -> anyhow::Result<reqwest::multipart::Part> {
+    let (length, file_name, mime_type) = (audio.len(), audio.file_name.clone(), audio.mime_type);
+    // With a length, the multipart body keeps its Content-Length despite the streamed part
+    reqwest::multipart::Part::stream_with_length(audio.into_body(), length)
+        .file_name(file_name)
+        .mime_str(mime_type)
+        .map_err(|e| anyhow::anyhow!("Failed to create file part for upload: {e}"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn converts_verbose_json_with_segments_and_words() {
+        let body = r#"{
+            "text": " Hello world.",
+            "language": "english",
+            "duration": 1.5,
+            "segments": [{"start": 0.0, "end": 1.5, "text": " Hello world.", "avg_logprob": -0.1}],
+            "words": [{"word": "Hello", "start": 0.0, "end": 0.6}, {"word": "world", "start": 0.7, "end": 1.4}]
+        }"#;
+        let response: TranscriptionResponse =
+            serde_json::from_str::<WhisperResponse>(body).unwrap().into();
+
+        assert_eq!(response.language.as_deref(), Some("english"));
+        assert_eq!(response.segments[0].text, "Hello world.");
+        assert_eq!(response.words.len(), 2);
+        let confidence = response.confidence.unwrap();
+        assert!((confidence - (-0.1f64).exp() as f32).abs() < 1e-6);
+    }
+
+    #[test]
+    fn plain_json_has_no_details() {
+        let response: TranscriptionResponse =
+            serde_json::from_str::<WhisperResponse>(r#"{"text": "Hi"}"#).unwrap().into();
+        assert_eq!(response, TranscriptionResponse::from_text("Hi"));
+        assert!(!response.has_details());
+    }
+
+    #[test]
+    fn prompt_text_comes_before_keywords() {
+        let options = WhisperApiConfig {
+            prompt: "Meeting notes.".to_string(),
+            ..WhisperApiConfig::default()
+        };
+        let keywords = vec!["ostt".to_string(), "Deepgram".to_string()];
+        assert_eq!(
+            whisper_prompt(&options, &keywords).as_deref(),
+            Some("Meeting notes. ostt, Deepgram")
+        );
+        assert_eq!(whisper_prompt(&WhisperApiConfig::default(), &[]), None);
+
+        let response = WhisperResponse::parse("Groq", ResponseFormat::Text, b" Hello.\n").unwrap();
+        assert_eq!(response, TranscriptionResponse::from_text("Hello."));
+    }
 }
@@ -2,130 +2,190 @@
 //!
 //! Handles transcription requests to OpenAI's Whisper API using multipart form data.
 
-use std::path::Path;
-use serde::Deserialize;
-
-use super::TranscriptionConfig;
-
-/// OpenAI API response wrapper
-#[derive(Debug, Deserialize)]
-struct OpenAiResponse {
-    text: String,
+use super::{
+    audio_part, base_url, discovered_description, model_prompt, timestamp_granularities,
+    whisper_variant, TranscriptionConfig, WhisperResponse,
+};
+use crate::config::file::{ProvidersConfig, ResponseFormat};
+use crate::transcription::language;
+use crate::transcription::model::{KeywordSupport, TranscriptionModel};
+use crate::transcription::provider::{AudioUpload, Provider};
+use crate::transcription::response::TranscriptionResponse;
+
+/// Public OpenAI API; transcriptions and translations are below it
+const BASE_URL: &str = "https://api.openai.com/v1";
+
+/// Largest file accepted by the transcription endpoint
+const MAX_UPLOAD_BYTES: u64 = 25 * 1024 * 1024;
+
+/// Longest audio the gpt-4o models accept
+const GPT_4O_MAX_DURATION_SECS: u64 = 1500;
+
+/// Returns the response format for a request.
+///
+/// Only models with timestamps (whisper-1) return segments and word timestamps
+/// (verbose_json) and subtitles; the gpt-4o models support json and text only, so other
+/// configured formats fall back to json.
+fn response_format(config: &TranscriptionConfig) -> ResponseFormat {
+    let timestamps = config.model.capabilities().timestamps;
+    match config.providers.openai.response_format {
+        Some(format @ (ResponseFormat::Json | ResponseFormat::Text)) => format,
+        Some(format) if timestamps => format,
+        Some(_) => ResponseFormat::Json,
+        None if timestamps => ResponseFormat::VerboseJson,
+        None => ResponseFormat::Json,
+    }
 }
 
-/// Transcribes an audio file using OpenAI's Whisper API.
+/// OpenAI transcription provider.
 ///
 /// Uses multipart form data with bearer token authentication.
-/// 
-/// Keywords are passed as the `prompt` parameter to guide transcription context.
-/// OpenAI's Whisper API uses the prompt to improve accuracy for domain-specific terms.
-pub async fn transcribe(
-    config: &TranscriptionConfig,
-    audio_path: &Path,
-) -> anyhow::Result<String> {
-    let audio_data = std::fs::read(audio_path).map_err(|e| {
-        anyhow::anyhow!("Failed to read audio file: {e}")
-    })?;
-
-    let client = reqwest::Client::new();
-
-    let file_name = audio_path
-        .file_name()
-        .unwrap_or_default()
-        .to_string_lossy()
-        .to_string();
-
-    let file_part = reqwest::multipart::Part::bytes(audio_data)
-        .file_name(file_name.clone())
-        .mime_str("audio/mpeg")
-        .map_err(|e| anyhow::anyhow!("Failed to create file part for upload: {e}"))?;
-
-    let mut form = reqwest::multipart::Form::new()
-        .part("file", file_part)
-        .text("model", config.model.api_model_name().to_string());
-
-    // Debug log: Log the API call details (without the audio data)
-    let mut debug_params = vec![
-        format!("model={}", config.model.api_model_name()),
-    ];
-
-    // Add keywords as prompt for better transcription context
-    // Note: gpt-4o-transcribe doesn't support prompt parameter, only whisper-1 and gpt-4o-mini-transcribe do
-    if !config.keywords.is_empty() {
-        let should_use_prompt = match config.model.api_model_name() {
-            "gpt-4o-transcribe" => false, // gpt-4o-transcribe doesn't support prompt
-            _ => true, // whisper-1 and gpt-4o-mini-transcribe support it
+///
+/// Keywords are passed as the `prompt` parameter to guide transcription context, after the
+/// prompt text from `[providers.openai]`, for the models that take a prompt. OpenAI's
+/// Whisper API uses the prompt to improve accuracy for domain-specific terms.
+pub struct OpenAiProvider;
+
+impl Provider for OpenAiProvider {
+    fn id(&self) -> &str {
+        "openai"
+    }
+
+    fn name(&self) -> &str {
+        "OpenAI"
+    }
+
+    fn models(&self) -> Vec<TranscriptionModel> {
+        vec![
+            TranscriptionModel::new(
+                "gpt-4o-transcribe",
+                self.id(),
+                "gpt-4o-transcribe",
+                "GPT-4o Transcribe (latest, best accuracy)",
+            )
+            .with_max_upload_bytes(MAX_UPLOAD_BYTES)
+            .with_max_duration_secs(GPT_4O_MAX_DURATION_SECS)
+            .with_price_per_minute(0.006),
+            TranscriptionModel::new(
+                "gpt-4o-mini-transcribe",
+                self.id(),
+                "gpt-4o-mini-transcribe",
+                "GPT-4o Mini Transcribe (faster, lighter)",
+            )
+            .with_prompt()
+            .with_keywords(KeywordSupport::Prompt)
+            .with_max_upload_bytes(MAX_UPLOAD_BYTES)
+            .with_max_duration_secs(GPT_4O_MAX_DURATION_SECS)
+            .with_price_per_minute(0.003),
+            TranscriptionModel::new("whisper", self.id(), "whisper-1", "Whisper (legacy)")
+                .with_prompt()
+                .with_keywords(KeywordSupport::Prompt)
+                .with_max_upload_bytes(MAX_UPLOAD_BYTES)
+                .with_translation()
+                .with_timestamps()
+                .with_price_per_minute(0.006),
+        ]
+    }
+
+    fn models_request(
+        &self,
+        client: &reqwest::Client,
+        providers: &ProvidersConfig,
+        api_key: &str,
+    ) -> Option<reqwest::RequestBuilder> {
+        let base_url = base_url(providers.openai.base_url.as_deref(), BASE_URL);
+        Some(client.get(format!("{base_url}/models")).bearer_auth(api_key))
+    }
+
+    fn discovered_model(&self, api_name: &str) -> Option<TranscriptionModel> {
+        // The model list also holds chat, embedding and speech synthesis models
+        let model = if api_name.starts_with("whisper") {
+            whisper_variant(api_name, self.id(), api_name)
+        } else if api_name.contains("transcribe") {
+            TranscriptionModel::new(api_name, self.id(), api_name, discovered_description(api_name))
+                .with_max_duration_secs(GPT_4O_MAX_DURATION_SECS)
+        } else {
+            return None;
         };
-        
-        if should_use_prompt {
-            let prompt = config.keywords.join(", ");
+        Some(model.with_max_upload_bytes(MAX_UPLOAD_BYTES))
+    }
+
+    fn build_request(
+        &self,
+        client: &reqwest::Client,
+        config: &TranscriptionConfig,
+        audio: AudioUpload,
+    ) -> anyhow::Result<reqwest::RequestBuilder> {
+        let mut form = reqwest::multipart::Form::new()
+            .part("file", audio_part(audio)?)
+            .text("model", config.model.api_model_name().to_string());
+
+        // Debug log: Log the API call details (without the audio data)
+        let mut debug_params = vec![
+            format!("model={}", config.model.api_model_name()),
+        ];
+
+        // Without a language hint the model detects the language itself; the translation
+        // endpoint always detects it
+        if let (Some(language), false) = (&config.language, config.translate) {
+            let language = language::base_code(language);
+            form = form.text("language", language.to_string());
+            debug_params.push(format!("language={language}"));
+        }
+
+        let options = &config.providers.openai;
+
+        // Add keywords as prompt for better transcription context
+        if let Some(prompt) = model_prompt(options, config) {
             form = form.text("prompt", prompt.clone());
             debug_params.push(format!("prompt={prompt}"));
             tracing::debug!("Keywords used as prompt for OpenAI model: {:?}", config.keywords);
-        } else {
-            tracing::debug!("Keywords defined but {} does not support prompt parameter. Keywords: {:?}", 
-                config.model.api_model_name(), config.keywords);
         }
-    }
 
-    let endpoint = config.model.endpoint();
-    let url = format!("{endpoint}?response_format=json");
-    debug_params.push("response_format=json".to_string());
-
-    tracing::debug!(
-        "OpenAI API Call:\n  URL: {}\n  Method: POST\n  Headers:\n    Authorization: Bearer <redacted>\n    Content-Type: multipart/form-data\n  Body parameters: {}",
-        url,
-        debug_params.join("\n    ")
-    );
-
-    let response = match client
-        .post(&url)
-        .bearer_auth(&config.api_key)
-        .multipart(form)
-        .send()
-        .await
-    {
-        Ok(resp) => resp,
-        Err(e) => {
-            let error_msg = if e.is_connect() {
-                "Failed to connect to OpenAI API server. Check your internet connection.".to_string()
-            } else if e.is_timeout() {
-                "Request to OpenAI timed out. The API server is not responding.".to_string()
-            } else if e.to_string().contains("builder") {
-                format!("Failed to build OpenAI API request: {e}. This may be a configuration error.")
-            } else {
-                format!("OpenAI network error: {e}")
-            };
-            return Err(anyhow::anyhow!(error_msg));
+        if let Some(temperature) = options.temperature {
+            form = form.text("temperature", temperature.to_string());
+            debug_params.push(format!("temperature={temperature}"));
         }
-    };
-
-    if !response.status().is_success() {
-        let status = response.status();
-        let error_body = response.text().await.unwrap_or_else(|_| "Unknown error".to_string());
-
-        let human_readable = match status.as_u16() {
-            401 => "OpenAI API key is invalid or expired. Please run 'ostt auth' to update your API key.".to_string(),
-            403 => "You don't have permission to use OpenAI's API. Check your API key and account status.".to_string(),
-            429 => "Too many requests to OpenAI. You've hit the API rate limit. Please wait and try again.".to_string(),
-            500 | 502 | 503 | 504 => "OpenAI API server is experiencing issues. Please try again later.".to_string(),
-            _ => format!("OpenAI API error (status {status}): {error_body}"),
-        };
 
-        return Err(anyhow::anyhow!(human_readable));
-    }
+        let response_format = response_format(config);
+        if options.response_format.is_some_and(|format| format != response_format) {
+            tracing::warn!(
+                "{} doesn't support response_format {}, using {}",
+                config.model.api_model_name(),
+                options.response_format.map_or("", |format| format.as_str()),
+                response_format.as_str()
+            );
+        }
+        form = form.text("response_format", response_format.as_str());
+        debug_params.push(format!("response_format={}", response_format.as_str()));
+        for granularity in timestamp_granularities(options, response_format, config.translate) {
+            form = form.text("timestamp_granularities[]", granularity.as_str());
+            debug_params.push(format!("timestamp_granularities[]={}", granularity.as_str()));
+        }
+        let base_url = base_url(config.providers.openai.base_url.as_deref(), BASE_URL);
+        let url = if config.translate {
+            format!("{base_url}/audio/translations")
+        } else {
+            format!("{base_url}/audio/transcriptions")
+        };
 
-    let transcription: OpenAiResponse = response
-        .json()
-        .await
-        .map_err(|e| anyhow::anyhow!("Failed to parse OpenAI response: {e}"))?;
+        tracing::debug!(
+            "OpenAI API Call:\n  URL: {}\n  Method: POST\n  Headers:\n    Authorization: Bearer <redacted>\n    Content-Type: multipart/form-data\n  Body parameters: {}",
+            url,
+            debug_params.join("\n    ")
+        );
 
-    // Debug log: Log the full response for debugging
-    tracing::debug!(
-        "OpenAI API Response:\n  Status: Success\n  Transcription length: {} characters\n  Full response: {:#?}",
-        transcription.text.len(),
-        transcription
-    );
+        Ok(client
+            .post(&url)
+            .bearer_auth(&config.api_key)
+            .multipart(form))
+    }
 
-    Ok(transcription.text)
+    fn parse_response(
+        &self,
+        config: &TranscriptionConfig,
+        body: &[u8],
+    ) -> anyhow::Result<TranscriptionResponse> {
+        WhisperResponse::parse(self.name(), response_format(config), body)
+    }
 }
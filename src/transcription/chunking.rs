@@ -0,0 +1,317 @@
+//! Chunked transcription of long recordings.
+//!
+//! Hosted APIs cap the size of an upload (25 MB for OpenAI and Groq), which long recordings
+//! such as meetings or lectures exceed. Such recordings are split into chunks below the
+//! limit, preferably at a pause in speech, and each chunk is transcribed on its own. When
+//! no pause is found near the cut, consecutive chunks overlap by a second so no word is lost,
+//! and the words the overlap produces twice are removed when the texts are stitched back
+//! together.
+
+use anyhow::Context;
+use futures_util::{stream, StreamExt, TryStreamExt};
+use std::ops::Range;
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+use super::api::{self, TranscriptionConfig};
+use super::progress::TranscriptionStatus;
+use super::response::TranscriptionResponse;
+
+/// Length of the frames the loudness is measured over when looking for a pause
+const FRAME_SECS: f64 = 0.02;
+/// How far before the maximum chunk length a pause is searched for
+const SILENCE_SEARCH_SECS: f64 = 30.0;
+/// RMS level (of 16-bit samples) below which a frame counts as silence
+const SILENCE_RMS: f64 = 500.0;
+/// Overlap between consecutive chunks when they have to be cut mid-speech
+const OVERLAP_SECS: f64 = 1.0;
+/// Longest run of repeated words removed when stitching overlapping chunks
+const MAX_OVERLAP_WORDS: usize = 12;
+
+/// A part of a recording, encoded to its own file.
+#[derive(Debug, Clone, PartialEq)]
+pub struct AudioChunk {
+    /// Encoded audio of the chunk
+    pub path: PathBuf,
+    /// Position of the chunk in the recording, in seconds
+    pub start: f64,
+    /// Seconds at the start of the chunk that are also at the end of the previous one
+    pub overlap: f64,
+}
+
+impl AudioChunk {
+    /// A chunk covering a whole recording
+    pub fn whole(path: impl Into<PathBuf>) -> Self {
+        Self {
+            path: path.into(),
+            start: 0.0,
+            overlap: 0.0,
+        }
+    }
+
+    /// Describes the chunk holding `range` of the recording's samples.
+    ///
+    /// # Arguments
+    /// * `path` - Encoded audio of the chunk
+    /// * `range` - Sample range of the chunk, as returned by `plan_chunks`
+    /// * `previous_end` - End of the previous chunk's range (0 for the first chunk)
+    /// * `sample_rate` - Sample rate of the recording
+    pub fn new(path: PathBuf, range: &Range<usize>, previous_end: usize, sample_rate: u32) -> Self {
+        let sample_rate = sample_rate as f64;
+        Self {
+            path,
+            start: range.start as f64 / sample_rate,
+            overlap: previous_end.saturating_sub(range.start) as f64 / sample_rate,
+        }
+    }
+}
+
+/// Splits a recording into sample ranges of at most `max_chunk_secs` seconds.
+///
+/// Each cut is placed at the quietest point of the last 30 seconds (at most a quarter of
+/// the chunk) before the maximum length. If that point isn't silent, the cut is made at
+/// the maximum length and the next chunk starts a second earlier.
+pub fn plan_chunks(samples: &[i16], sample_rate: u32, max_chunk_secs: f64) -> Vec<Range<usize>> {
+    let rate = sample_rate as f64;
+    let frame = ((FRAME_SECS * rate) as usize).max(1);
+    let max_len = ((max_chunk_secs * rate) as usize).max(frame * 4);
+    let search = ((SILENCE_SEARCH_SECS * rate) as usize).min(max_len / 4);
+    let overlap = ((OVERLAP_SECS * rate) as usize).min(max_len / 4);
+
+    let mut ranges = Vec::new();
+    let mut start = 0;
+    while samples.len() - start > max_len {
+        let limit = start + max_len;
+        let (cut, level) = quietest_frame(&samples[limit - search..limit], frame);
+        if level <= SILENCE_RMS {
+            let cut = limit - search + cut;
+            ranges.push(start..cut);
+            start = cut;
+        } else {
+            ranges.push(start..limit);
+            start = limit - overlap;
+        }
+    }
+    ranges.push(start..samples.len());
+    ranges
+}
+
+/// Finds the quietest frame, returning the offset of its center and its RMS level
+fn quietest_frame(samples: &[i16], frame: usize) -> (usize, f64) {
+    samples
+        .chunks_exact(frame)
+        .enumerate()
+        .map(|(index, chunk)| {
+            let energy: f64 = chunk.iter().map(|&s| (s as f64) * (s as f64)).sum();
+            (index * frame + frame / 2, (energy / frame as f64).sqrt())
+        })
+        .min_by(|a, b| a.1.total_cmp(&b.1))
+        .unwrap_or((samples.len() / 2, f64::MAX))
+}
+
+/// Transcribes the chunks of a recording with one model and stitches the results together.
+///
+/// Up to `concurrency` chunks are uploaded at the same time. A single chunk is simply
+/// transcribed as is.
+///
+/// # Errors
+/// - If any chunk fails to transcribe; the remaining uploads are cancelled
+pub async fn transcribe_chunks(
+    config: &TranscriptionConfig,
+    chunks: &[AudioChunk],
+    concurrency: usize,
+) -> anyhow::Result<TranscriptionResponse> {
+    if let [chunk] = chunks {
+        return api::transcribe(config, &chunk.path).await;
+    }
+
+    let total = chunks.len();
+    let completed = AtomicUsize::new(0);
+    config.report(TranscriptionStatus::TranscribingChunks {
+        completed: 0,
+        total,
+    });
+
+    // The futures are created up front: a stream mapping over borrowed chunks isn't `Send`
+    let uploads: Vec<_> = chunks
+        .iter()
+        .enumerate()
+        .map(|(index, chunk)| transcribe_chunk(config, chunk, index, total, &completed))
+        .collect();
+    let responses: Vec<TranscriptionResponse> = stream::iter(uploads)
+        .buffered(concurrency.max(1))
+        .try_collect()
+        .await?;
+
+    Ok(merge(chunks, responses))
+}
+
+/// Transcribes one chunk of `total` and reports the progress when it is done
+async fn transcribe_chunk(
+    config: &TranscriptionConfig,
+    chunk: &AudioChunk,
+    index: usize,
+    total: usize,
+    completed: &AtomicUsize,
+) -> anyhow::Result<TranscriptionResponse> {
+    if let Ok(metadata) = std::fs::metadata(&chunk.path) {
+        tracing::debug!(
+            "Transcribing part {}/{} ({} bytes)",
+            index + 1,
+            total,
+            metadata.len()
+        );
+    }
+    let response = api::transcribe(config, &chunk.path)
+        .await
+        .with_context(|| format!("Part {}/{total} failed", index + 1))?;
+
+    let completed = completed.fetch_add(1, Ordering::Relaxed) + 1;
+    config.report(TranscriptionStatus::TranscribingChunks { completed, total });
+    Ok(response)
+}
+
+/// Combines the per-chunk results into one, shifting timings to the whole recording.
+///
+/// Words and segments that lie entirely within an overlap were already produced by the
+/// previous chunk and are dropped.
+fn merge(chunks: &[AudioChunk], responses: Vec<TranscriptionResponse>) -> TranscriptionResponse {
+    let mut merged = TranscriptionResponse::default();
+    let mut confidences = Vec::new();
+
+    for (chunk, response) in chunks.iter().zip(responses) {
+        let covered = chunk.start + chunk.overlap;
+
+        merged.text = if chunk.overlap > 0.0 {
+            stitch(&merged.text, &response.text)
+        } else {
+            join(&merged.text, &response.text)
+        };
+        merged.language = merged.language.or(response.language);
+        if let Some(duration) = response.duration {
+            merged.duration = Some(chunk.start + duration);
+        }
+        confidences.extend(response.confidence);
+
+        merged.segments.extend(
+            response
+                .segments
+                .into_iter()
+                .map(|mut segment| {
+                    segment.start += chunk.start;
+                    segment.end += chunk.start;
+                    segment
+                })
+                .filter(|segment| segment.end > covered),
+        );
+        merged.words.extend(
+            response
+                .words
+                .into_iter()
+                .map(|mut word| {
+                    word.start += chunk.start;
+                    word.end += chunk.start;
+                    word
+                })
+                .filter(|word| word.start >= covered),
+        );
+    }
+
+    // Prefer a confidence derived from all segments or words; otherwise average the chunks'
+    let merged = merged.with_derived_confidence();
+    if merged.confidence.is_some() || confidences.is_empty() {
+        return merged;
+    }
+    let confidence = confidences.iter().sum::<f32>() / confidences.len() as f32;
+    TranscriptionResponse {
+        confidence: Some(confidence),
+        ..merged
+    }
+}
+
+/// Joins the texts of two chunks that don't overlap
+fn join(previous: &str, next: &str) -> String {
+    match (previous.trim(), next.trim()) {
+        ("", next) => next.to_string(),
+        (previous, "") => previous.to_string(),
+        (previous, next) => format!("{previous} {next}"),
+    }
+}
+
+/// Joins the texts of two overlapping chunks, dropping the words both transcribed.
+///
+/// The longest run of words ending `previous` that also starts `next` is removed from
+/// `next`, ignoring case and punctuation. Up to two leading words of `next` may be skipped
+/// first, since the word cut at the start of a chunk is often transcribed badly; such a
+/// match must span at least two words.
+pub fn stitch(previous: &str, next: &str) -> String {
+    let previous_words: Vec<String> = previous.split_whitespace().map(normalize).collect();
+    let next_tokens: Vec<&str> = next.split_whitespace().collect();
+    let next_words: Vec<String> = next_tokens.iter().map(|w| normalize(w)).collect();
+
+    let longest = MAX_OVERLAP_WORDS
+        .min(previous_words.len())
+        .min(next_words.len());
+    let duplicate = (1..=longest).rev().find_map(|length| {
+        let tail = &previous_words[previous_words.len() - length..];
+        (0..=2)
+            .filter(|&skip| skip == 0 || length >= 2)
+            .find(|&skip| {
+                next_words
+                    .get(skip..skip + length)
+                    .is_some_and(|head| head == tail && tail.iter().all(|w| !w.is_empty()))
+            })
+            .map(|skip| skip + length)
+    });
+
+    match duplicate {
+        Some(count) => join(previous, &next_tokens[count..].join(" ")),
+        None => join(previous, next),
+    }
+}
+
+/// Lowercases a word and strips punctuation for overlap comparison
+fn normalize(word: &str) -> String {
+    word.chars()
+        .filter(|c| c.is_alphanumeric())
+        .flat_map(char::to_lowercase)
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn stitch_removes_repeated_words() {
+        assert_eq!(
+            stitch("we will meet on Tuesday at", "Tuesday, at noon in the office."),
+            "we will meet on Tuesday at noon in the office."
+        );
+        assert_eq!(
+            stitch("the quarterly numbers look good", "ers look good this year"),
+            "the quarterly numbers look good this year"
+        );
+        assert_eq!(stitch("first part.", "Second part."), "first part. Second part.");
+        assert_eq!(stitch("", "only text"), "only text");
+    }
+
+    #[test]
+    fn plan_cuts_at_silence_and_overlaps_otherwise() {
+        let rate = 1000;
+        // 100s of "speech" with a pause from 50s to 51s
+        let mut samples = vec![5000i16; 100 * rate];
+        samples[50 * rate..51 * rate].fill(0);
+
+        let ranges = plan_chunks(&samples, rate as u32, 60.0);
+        assert_eq!(ranges.len(), 2);
+        assert!((50 * rate..51 * rate).contains(&ranges[0].end));
+        assert_eq!(ranges[1].start, ranges[0].end);
+
+        // No pause at all: hard cut with a second of overlap
+        let samples = vec![5000i16; 100 * rate];
+        let ranges = plan_chunks(&samples, rate as u32, 60.0);
+        assert_eq!(ranges, vec![0..60 * rate, 59 * rate..100 * rate]);
+        assert!(ranges.iter().all(|r| r.len() <= 60 * rate));
+    }
+}
@@ -0,0 +1,59 @@
+//! Cleanup of server-side work a transcription leaves behind.
+//!
+//! AssemblyAI transcribes asynchronously: the recording is turned into a job on the server
+//! that outlives the requests. A cancelled transcription is aborted by dropping its task,
+//! which can't run async code, so the provider registers the job's deletion here instead
+//! and the caller runs it once the task has ended.
+
+use futures_util::future::{join_all, BoxFuture};
+use std::collections::HashMap;
+use std::future::Future;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+
+/// Cleanups registered by the providers of a transcription, cheap to clone into its tasks.
+#[derive(Clone, Default)]
+pub struct Cleanup {
+    tasks: Arc<Mutex<HashMap<u64, BoxFuture<'static, ()>>>>,
+    next_id: Arc<AtomicU64>,
+}
+
+impl std::fmt::Debug for Cleanup {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Cleanup")
+            .field("pending", &self.tasks.lock().map(|tasks| tasks.len()).ok())
+            .finish()
+    }
+}
+
+impl Cleanup {
+    /// Creates an empty set of cleanups
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Registers a cleanup and returns its ID, for `remove` once it is no longer needed
+    pub fn register(&self, task: impl Future<Output = ()> + Send + 'static) -> u64 {
+        let id = self.next_id.fetch_add(1, Ordering::Relaxed);
+        if let Ok(mut tasks) = self.tasks.lock() {
+            tasks.insert(id, Box::pin(task));
+        }
+        id
+    }
+
+    /// Removes a cleanup without running it
+    pub fn remove(&self, id: u64) {
+        if let Ok(mut tasks) = self.tasks.lock() {
+            tasks.remove(&id);
+        }
+    }
+
+    /// Runs every registered cleanup, concurrently, and waits for them to finish
+    pub async fn run(&self) {
+        let tasks: Vec<_> = match self.tasks.lock() {
+            Ok(mut tasks) => tasks.drain().map(|(_, task)| task).collect(),
+            Err(_) => return,
+        };
+        join_all(tasks).await;
+    }
+}
@@ -0,0 +1,313 @@
+//! Discovery of models from the providers' model lists.
+//!
+//! Providers release models more often than ostt is released. Providers with a model list
+//! endpoint are asked for their models once an API key is saved for them; speech models
+//! ostt doesn't know yet are described with the provider's metadata for that model family
+//! and offered next to the built-in models. The lists are cached in the data directory
+//! and fetched again once they are older than a day; a list that came back empty or
+//! couldn't be fetched is tried again after an hour.
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::path::Path;
+
+use super::http;
+use super::model::TranscriptionModel;
+use super::provider::Provider;
+use super::registry::ProviderRegistry;
+use crate::config::file::{NetworkConfig, ProvidersConfig};
+use crate::config;
+
+/// File in the data directory holding the model lists
+pub const CACHE_FILE: &str = "models.json";
+
+/// How long a fetched model list is used before it is fetched again
+pub const CACHE_TTL_HOURS: i64 = 24;
+
+/// How long an empty or failed fetch is remembered before the list is fetched again
+pub const RETRY_TTL_HOURS: i64 = 1;
+
+/// Model lists fetched from the providers, by provider ID.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct ModelCache {
+    #[serde(default)]
+    providers: BTreeMap<String, ModelList>,
+}
+
+/// A provider's model list as it was fetched
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+struct ModelList {
+    fetched_at: DateTime<Utc>,
+    /// API model names
+    models: Vec<String>,
+    /// Whether the last fetch came back empty or failed; the models are from an earlier one
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    retry: bool,
+}
+
+impl ModelCache {
+    /// Loads the cache from the data directory.
+    ///
+    /// A missing or unreadable cache is treated as empty, so discovery never stands in the
+    /// way of transcribing with the built-in models.
+    pub fn load(data_dir: &Path) -> Self {
+        let path = data_dir.join(CACHE_FILE);
+        let Ok(content) = std::fs::read_to_string(&path) else {
+            return Self::default();
+        };
+        serde_json::from_str(&content).unwrap_or_else(|e| {
+            tracing::warn!("Ignoring unreadable model cache {}: {}", path.display(), e);
+            Self::default()
+        })
+    }
+
+    /// Writes the cache to the data directory.
+    ///
+    /// # Errors
+    /// - If the cache file cannot be written
+    pub fn save(&self, data_dir: &Path) -> anyhow::Result<()> {
+        std::fs::create_dir_all(data_dir)?;
+        std::fs::write(data_dir.join(CACHE_FILE), serde_json::to_string_pretty(self)?)?;
+        Ok(())
+    }
+
+    /// Returns whether the provider's list was fetched less than a TTL before `now`.
+    ///
+    /// Empty or failed fetches use the shorter retry TTL.
+    pub fn is_fresh(&self, provider_id: &str, now: DateTime<Utc>) -> bool {
+        self.providers.get(provider_id).is_some_and(|list| {
+            let ttl = if list.retry || list.models.is_empty() {
+                RETRY_TTL_HOURS
+            } else {
+                CACHE_TTL_HOURS
+            };
+            now.signed_duration_since(list.fetched_at) < chrono::Duration::hours(ttl)
+        })
+    }
+
+    /// Stores a provider's model list as fetched at `now`
+    pub fn set(&mut self, provider_id: &str, models: Vec<String>, now: DateTime<Utc>) {
+        self.providers.insert(
+            provider_id.to_string(),
+            ModelList {
+                fetched_at: now,
+                models,
+                retry: false,
+            },
+        );
+    }
+
+    /// Records an empty or failed fetch at `now`, keeping the models fetched before
+    pub fn set_retry(&mut self, provider_id: &str, now: DateTime<Utc>) {
+        let list = self
+            .providers
+            .entry(provider_id.to_string())
+            .or_insert_with(|| ModelList {
+                fetched_at: now,
+                models: Vec::new(),
+                retry: true,
+            });
+        list.fetched_at = now;
+        list.retry = true;
+    }
+
+    /// Describes the listed speech models that aren't built in.
+    ///
+    /// Lists of providers that are no longer registered are skipped. Lists are used even
+    /// when they are stale, so a selected discovered model keeps working offline.
+    pub fn models(&self, registry: &ProviderRegistry) -> Vec<TranscriptionModel> {
+        let mut models = Vec::new();
+        for (provider_id, list) in &self.providers {
+            let Some(provider) = registry.provider(provider_id) else {
+                continue;
+            };
+            let builtin = provider.models();
+            models.extend(
+                list.models
+                    .iter()
+                    .filter(|name| !builtin.iter().any(|m| m.api_model_name() == name.as_str()))
+                    .filter_map(|name| provider.discovered_model(name)),
+            );
+        }
+        models
+    }
+}
+
+/// Creates a registry with the built-in, custom and cached discovered models.
+pub fn registry(providers: &ProvidersConfig, data_dir: &Path) -> ProviderRegistry {
+    let mut registry = ProviderRegistry::from_config(providers);
+    let discovered = ModelCache::load(data_dir).models(&registry);
+    registry.add_discovered(discovered);
+    registry
+}
+
+/// Fetches the API model names from a provider's model list.
+///
+/// Returns an empty list for providers without a model list endpoint.
+///
+/// # Errors
+/// - If the request fails or the API returns an error
+/// - If the response cannot be parsed
+pub async fn fetch_models(
+    provider: &dyn Provider,
+    providers: &ProvidersConfig,
+    network: &NetworkConfig,
+    api_key: &str,
+) -> anyhow::Result<Vec<String>> {
+    let client = http::client(network)?;
+    let Some(request) = provider.models_request(&client, providers, api_key) else {
+        return Ok(Vec::new());
+    };
+    let response = request
+        .timeout(http::request_timeout(network, 0))
+        .send()
+        .await
+        .map_err(|e| provider.map_network_error(&e))?;
+    let status = response.status();
+    if !status.is_success() {
+        let body = response.text().await.unwrap_or_default();
+        return Err(provider.map_http_error(status, &body));
+    }
+    let body = response
+        .bytes()
+        .await
+        .map_err(|e| anyhow::anyhow!("Failed to read {} model list: {e}", provider.name()))?;
+    provider.parse_models(&body)
+}
+
+/// Fetches the model lists that are missing or stale and updates the cache.
+///
+/// Only providers with a model list endpoint and a saved API key are asked. A list that
+/// cannot be fetched is logged and the cached one, if any, is kept until the retry TTL
+/// has passed.
+///
+/// # Returns
+/// The updated cache
+pub async fn refresh(
+    registry: &ProviderRegistry,
+    providers: &ProvidersConfig,
+    network: &NetworkConfig,
+    data_dir: &Path,
+) -> ModelCache {
+    let mut cache = ModelCache::load(data_dir);
+    let now = Utc::now();
+    let mut changed = false;
+
+    for provider in registry.providers() {
+        if !cache.is_fresh(provider.id(), now) {
+            changed |= update(&mut cache, provider.as_ref(), providers, network, now).await;
+        }
+    }
+
+    if changed {
+        if let Err(e) = cache.save(data_dir) {
+            tracing::warn!("Failed to save model cache: {}", e);
+        }
+    }
+    cache
+}
+
+/// Fetches one provider's model list, even if the cached one is fresh, and updates the cache.
+///
+/// Used after the provider's API key changed, since the new key may list other models.
+///
+/// # Returns
+/// The updated cache
+pub async fn refresh_provider(
+    provider: &dyn Provider,
+    providers: &ProvidersConfig,
+    network: &NetworkConfig,
+    data_dir: &Path,
+) -> ModelCache {
+    let mut cache = ModelCache::load(data_dir);
+    if update(&mut cache, provider, providers, network, Utc::now()).await {
+        if let Err(e) = cache.save(data_dir) {
+            tracing::warn!("Failed to save model cache: {}", e);
+        }
+    }
+    cache
+}
+
+/// Fetches a provider's model list into the cache, returning whether the cache changed.
+///
+/// Providers without a saved API key are skipped.
+async fn update(
+    cache: &mut ModelCache,
+    provider: &dyn Provider,
+    providers: &ProvidersConfig,
+    network: &NetworkConfig,
+    now: DateTime<Utc>,
+) -> bool {
+    let Some(api_key) = config::get_api_key(provider.id()).ok().flatten() else {
+        return false;
+    };
+    match fetch_models(provider, providers, network, &api_key).await {
+        Ok(models) if models.is_empty() => cache.set_retry(provider.id(), now),
+        Ok(models) => {
+            tracing::info!("Fetched {} models from {}", models.len(), provider.name());
+            cache.set(provider.id(), models, now);
+        }
+        Err(e) => {
+            tracing::warn!("Failed to fetch {} models: {}", provider.name(), e);
+            cache.set_retry(provider.id(), now);
+        }
+    }
+    true
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn describes_listed_models_that_are_not_built_in() {
+        let registry = ProviderRegistry::builtin();
+        let now = Utc::now();
+        let mut cache = ModelCache::default();
+        cache.set(
+            "groq",
+            vec![
+                "whisper-large-v3".to_string(),
+                "distil-whisper-large-v3-en".to_string(),
+                "llama-3.3-70b-versatile".to_string(),
+            ],
+            now,
+        );
+        cache.set("unknown", vec!["whisper-2".to_string()], now);
+
+        let models = cache.models(&registry);
+        assert_eq!(models.len(), 1);
+        assert_eq!(models[0].id(), "groq-distil-whisper-large-v3-en");
+        assert_eq!(models[0].capabilities().languages, vec!["en".to_string()]);
+        assert!(!models[0].supports_translation());
+
+        assert!(cache.is_fresh("groq", now));
+        let tomorrow = now + chrono::Duration::hours(CACHE_TTL_HOURS);
+        assert!(!cache.is_fresh("groq", tomorrow));
+        assert!(!cache.is_fresh("openai", now));
+    }
+
+    #[test]
+    fn retries_empty_or_failed_fetches_sooner() {
+        let registry = ProviderRegistry::builtin();
+        let now = Utc::now();
+        let later = now + chrono::Duration::hours(RETRY_TTL_HOURS);
+        let mut cache = ModelCache::default();
+
+        cache.set_retry("openai", now);
+        assert!(cache.is_fresh("openai", now));
+        assert!(!cache.is_fresh("openai", later));
+
+        // A failed fetch keeps the models fetched before
+        cache.set("groq", vec!["distil-whisper-large-v3-en".to_string()], now);
+        cache.set_retry("groq", now);
+        assert!(cache.is_fresh("groq", now));
+        assert!(!cache.is_fresh("groq", later));
+        assert_eq!(cache.models(&registry).len(), 1);
+
+        let saved: ModelCache =
+            serde_json::from_str(&serde_json::to_string(&cache).unwrap()).unwrap();
+        assert_eq!(saved, cache);
+    }
+}
@@ -0,0 +1,260 @@
+//! Fallback chain across transcription models.
+//!
+//! A recording is transcribed with the first model of the chain; when that fails (after the
+//! provider's own retries), the same audio is sent to the next model, and so on, unless the
+//! recording itself can't be read. Each attempt is recorded so callers can persist latency
+//! and success statistics, which in turn can be used to put the historically fastest healthy
+//! model first.
+
+use std::collections::HashMap;
+use std::time::{Duration, Instant};
+
+use super::api::TranscriptionConfig;
+use super::chunking::{self, AudioChunk};
+use super::model::TranscriptionModel;
+use super::progress::TranscriptionStatus;
+use super::response::TranscriptionResponse;
+use crate::history::ModelStats;
+
+/// Outcome of a single model attempt within a fallback chain.
+#[derive(Debug, Clone)]
+pub struct Attempt {
+    /// ID of the model that was tried
+    pub model_id: String,
+    /// How long the attempt took
+    pub latency: Duration,
+    /// Error message if the attempt failed
+    pub error: Option<String>,
+}
+
+/// A successful transcription together with the model that produced it.
+#[derive(Debug, Clone)]
+pub struct FallbackTranscript {
+    /// The transcription result
+    pub response: TranscriptionResponse,
+    /// The model that produced the text
+    pub model: TranscriptionModel,
+}
+
+/// Transcribes a recording with each configuration in turn until one succeeds.
+///
+/// `audio` holds the whole recording as one chunk, or the chunks of a long recording, which
+/// each model transcribes with up to `concurrency` uploads at a time. Every attempt, failed
+/// or successful, is appended to `attempts`. A recording that can't be read fails the chain
+/// right away, since no other model could transcribe it either.
+///
+/// # Errors
+/// - If the chain is empty
+/// - If the recording can't be read
+/// - If every model in the chain fails; the error lists each model's failure
+pub async fn transcribe_with_fallback(
+    chain: &[TranscriptionConfig],
+    audio: &[AudioChunk],
+    concurrency: usize,
+    attempts: &mut Vec<Attempt>,
+) -> anyhow::Result<FallbackTranscript> {
+    let mut failures = Vec::new();
+    let mut last_error = None;
+
+    for (index, config) in chain.iter().enumerate() {
+        if index > 0 {
+            let previous = chain[index - 1].model.id();
+            tracing::warn!("Falling back from '{}' to '{}'", previous, config.model.id());
+            config.report(TranscriptionStatus::FallingBack {
+                from: previous.to_string(),
+                to: config.model.id().to_string(),
+            });
+        }
+
+        let started = Instant::now();
+        let result = chunking::transcribe_chunks(config, audio, concurrency).await;
+        let latency = started.elapsed();
+
+        match result {
+            Ok(response) => {
+                attempts.push(Attempt {
+                    model_id: config.model.id().to_string(),
+                    latency,
+                    error: None,
+                });
+                return Ok(FallbackTranscript {
+                    response,
+                    model: config.model.clone(),
+                });
+            }
+            Err(e) => {
+                tracing::warn!("Transcription with '{}' failed: {}", config.model.id(), e);
+                attempts.push(Attempt {
+                    model_id: config.model.id().to_string(),
+                    latency,
+                    error: Some(e.to_string()),
+                });
+                if !is_retryable(&e) {
+                    return Err(e);
+                }
+                failures.push(format!("{}: {e}", config.model.id()));
+                last_error = Some(e);
+            }
+        }
+    }
+
+    match (failures.len(), last_error) {
+        (1, Some(error)) => Err(error),
+        (_, None) => Err(anyhow::anyhow!("No transcription model configured")),
+        (n, Some(_)) => Err(anyhow::anyhow!(
+            "All {n} models failed:\n{}",
+            failures.join("\n")
+        )),
+    }
+}
+
+/// Returns whether another model might succeed after this error.
+///
+/// Only errors reading the audio are final: they would happen with every model.
+fn is_retryable(error: &anyhow::Error) -> bool {
+    error.downcast_ref::<std::io::Error>().is_none()
+}
+
+/// Reorders a fallback chain so the historically fastest healthy model comes first.
+///
+/// Models whose most recent attempt succeeded are ordered by average latency; models with
+/// no history keep their configured position after them, and models whose last attempt
+/// failed go last. The sort is stable, so ties keep the configured order.
+pub fn order_by_performance(
+    chain: &mut [TranscriptionConfig],
+    stats: &HashMap<String, ModelStats>,
+) {
+    chain.sort_by_key(|config| match stats.get(config.model.id()) {
+        Some(stats) if stats.last_succeeded => {
+            (0, stats.average_latency.unwrap_or(Duration::MAX))
+        }
+        None => (1, Duration::ZERO),
+        Some(_) => (2, Duration::ZERO),
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::file::{NetworkConfig, ProvidersConfig};
+    use crate::recording::encode::write_wav;
+    use crate::transcription::mock::MockServer;
+    use crate::transcription::ProviderRegistry;
+
+    /// A chain of OpenAI's whisper after Groq, which can't be reached
+    fn chain(server: &MockServer) -> Vec<TranscriptionConfig> {
+        let mut providers = ProvidersConfig::default();
+        providers.openai.base_url = server.base_url("openai");
+        // Nothing listens on port 1
+        providers.groq.base_url = Some("http://127.0.0.1:1/openai/v1".to_string());
+        let network = NetworkConfig {
+            max_retries: 0,
+            ..NetworkConfig::default()
+        };
+        let registry = ProviderRegistry::from_config(&providers);
+        ["groq-whisper-large-v3", "whisper"]
+            .iter()
+            .map(|id| {
+                let model = registry.find_model(id).unwrap();
+                let (providers, network) = (providers.clone(), network.clone());
+                TranscriptionConfig::new(model, "key".to_string(), Vec::new(), providers, network)
+            })
+            .collect()
+    }
+
+    #[tokio::test]
+    async fn falls_back_unless_the_recording_is_unreadable() {
+        let server = MockServer::start("127.0.0.1:0").await.unwrap();
+        let chain = chain(&server);
+        let path = std::env::temp_dir().join(format!("ostt_fallback_{}.wav", std::process::id()));
+        write_wav(&[0i16; 1600], 16000, &path).unwrap();
+
+        let mut attempts = Vec::new();
+        let audio = [AudioChunk::whole(&path)];
+        let transcript = transcribe_with_fallback(&chain, &audio, 1, &mut attempts)
+            .await
+            .unwrap();
+        std::fs::remove_file(&path).unwrap();
+        assert_eq!(transcript.model.id(), "whisper");
+        assert_eq!(attempts.len(), 2);
+        assert!(attempts[0].error.is_some());
+        assert!(attempts[1].error.is_none());
+
+        // The file is gone now, which no model in the chain can do anything about
+        let mut attempts = Vec::new();
+        let error = transcribe_with_fallback(&chain, &audio, 1, &mut attempts)
+            .await
+            .unwrap_err();
+        assert_eq!(attempts.len(), 1);
+        assert!(error.to_string().starts_with("Failed to read audio file"), "{error}");
+    }
+
+    #[tokio::test]
+    async fn stops_when_a_chunk_is_unreadable() {
+        let server = MockServer::start("127.0.0.1:0").await.unwrap();
+        let chain = chain(&server);
+        let dir = std::env::temp_dir();
+        let missing = dir.join(format!("ostt_fallback_missing_{}.wav", std::process::id()));
+        let present = dir.join(format!("ostt_fallback_present_{}.wav", std::process::id()));
+        write_wav(&[0i16; 1600], 16000, &present).unwrap();
+
+        let mut attempts = Vec::new();
+        let audio = [
+            AudioChunk::new(missing, &(0..1600), 0, 16000),
+            AudioChunk::new(present.clone(), &(1600..3200), 1600, 16000),
+        ];
+        let error = transcribe_with_fallback(&chain, &audio, 1, &mut attempts)
+            .await
+            .unwrap_err();
+        std::fs::remove_file(&present).unwrap();
+        assert_eq!(attempts.len(), 1);
+        assert!(format!("{error:#}").contains("Failed to read audio file"), "{error:#}");
+    }
+
+    #[test]
+    fn ranks_healthy_models_by_latency() {
+        let registry = ProviderRegistry::from_config(&ProvidersConfig::default());
+        let ids = [
+            "whisper",
+            "nova-3",
+            "gpt-4o-transcribe",
+            "groq-whisper-large-v3",
+            "deepinfra-whisper-base",
+        ];
+        let mut chain: Vec<TranscriptionConfig> = ids
+            .iter()
+            .map(|id| {
+                let model = registry.find_model(id).unwrap();
+                let providers = ProvidersConfig::default();
+                let network = NetworkConfig::default();
+                TranscriptionConfig::new(model, String::new(), Vec::new(), providers, network)
+            })
+            .collect();
+        let stats = |latency_ms: Option<u64>, last_succeeded| ModelStats {
+            attempts: 1,
+            successes: last_succeeded as u32,
+            average_latency: latency_ms.map(Duration::from_millis),
+            last_succeeded,
+        };
+        // whisper has no history
+        let stats = HashMap::from([
+            ("nova-3".to_string(), stats(Some(100), false)),
+            ("gpt-4o-transcribe".to_string(), stats(Some(2000), true)),
+            ("groq-whisper-large-v3".to_string(), stats(Some(300), true)),
+            ("deepinfra-whisper-base".to_string(), stats(None, true)),
+        ]);
+
+        order_by_performance(&mut chain, &stats);
+        let order: Vec<&str> = chain.iter().map(|config| config.model.id()).collect();
+        assert_eq!(
+            order,
+            [
+                "groq-whisper-large-v3",
+                "gpt-4o-transcribe",
+                "deepinfra-whisper-base",
+                "whisper",
+                "nova-3"
+            ]
+        );
+    }
+}
@@ -0,0 +1,212 @@
+//! Shared HTTP client and retry policy for transcription requests.
+//!
+//! All providers send their requests through a shared `reqwest::Client` configured from the
+//! `[network]` section of the config file, so connections are pooled. Every request has a
+//! connect timeout and a deadline that grows with the size of its upload. Transient
+//! failures (rate limits, server errors, dropped connections) are retried with exponential
+//! backoff and jitter, honouring `Retry-After`.
+
+use reqwest::header::{HeaderMap, RETRY_AFTER};
+use reqwest::StatusCode;
+use std::collections::HashMap;
+use std::sync::{Mutex, OnceLock};
+use std::time::Duration;
+
+use crate::config::NetworkConfig;
+
+/// Longest `Retry-After` the client is willing to wait; longer waits fail immediately
+const MAX_RETRY_AFTER: Duration = Duration::from_secs(60);
+
+/// Slowest upload rate requests are given time for, in bytes per second (128 kbit/s)
+const MIN_UPLOAD_BYTES_PER_SEC: u64 = 16 * 1024;
+
+/// Clients built so far, by connect timeout in seconds
+static CLIENTS: OnceLock<Mutex<HashMap<u64, reqwest::Client>>> = OnceLock::new();
+
+/// Returns the shared HTTP client for a network configuration.
+///
+/// Clients only differ in their connect timeout, so there is one per connect timeout and
+/// requests with the same configuration share a connection pool. The deadline of each
+/// request is set with [`request_timeout`].
+///
+/// # Errors
+/// - If the TLS backend cannot be initialized
+pub fn client(network: &NetworkConfig) -> anyhow::Result<reqwest::Client> {
+    let mut clients = CLIENTS
+        .get_or_init(Default::default)
+        .lock()
+        .unwrap_or_else(|e| e.into_inner());
+    if let Some(client) = clients.get(&network.connect_timeout_secs) {
+        return Ok(client.clone());
+    }
+
+    let client = reqwest::Client::builder()
+        .connect_timeout(Duration::from_secs(network.connect_timeout_secs))
+        .pool_idle_timeout(Duration::from_secs(90))
+        .user_agent(concat!("ostt/", env!("CARGO_PKG_VERSION")))
+        .build()
+        .map_err(|e| anyhow::anyhow!("Failed to create HTTP client: {e}"))?;
+    clients.insert(network.connect_timeout_secs, client.clone());
+    Ok(client)
+}
+
+/// Returns how long a request that uploads `upload_bytes` may take.
+///
+/// The API gets `request_timeout_secs` to answer, and the upload gets time at a slow
+/// uplink rate on top, so that long recordings aren't cut off while they are being sent.
+/// (reqwest's read timeout doesn't help against stalls here: until the response arrives,
+/// it is a single timer that includes the upload.)
+pub fn request_timeout(network: &NetworkConfig, upload_bytes: u64) -> Duration {
+    Duration::from_secs(network.request_timeout_secs)
+        + Duration::from_secs(upload_bytes.div_ceil(MIN_UPLOAD_BYTES_PER_SEC))
+}
+
+/// Exponential backoff retry policy.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RetryPolicy {
+    /// Maximum number of retries after the first attempt
+    pub max_retries: u32,
+    /// Delay before the first retry
+    pub initial_backoff: Duration,
+    /// Upper bound for the computed delay
+    pub max_backoff: Duration,
+}
+
+impl RetryPolicy {
+    /// Creates the retry policy configured in `[network]`
+    pub fn from_config(network: &NetworkConfig) -> Self {
+        Self {
+            max_retries: network.max_retries,
+            initial_backoff: Duration::from_millis(network.initial_backoff_ms),
+            max_backoff: Duration::from_millis(network.max_backoff_ms.max(network.initial_backoff_ms)),
+        }
+    }
+
+    /// Returns the delay before the given retry (1-based), or `None` to give up.
+    ///
+    /// Uses the server's `Retry-After` when given, otherwise doubles the initial backoff per
+    /// retry up to `max_backoff` and picks a random delay in the upper half of that window so
+    /// that clients hitting the same rate limit don't retry in lockstep. Gives up once the
+    /// retries are exhausted or the server asks for a wait longer than a minute.
+    pub fn delay(&self, attempt: u32, retry_after: Option<Duration>) -> Option<Duration> {
+        if attempt > self.max_retries {
+            return None;
+        }
+
+        if let Some(retry_after) = retry_after {
+            return (retry_after <= MAX_RETRY_AFTER).then_some(retry_after);
+        }
+
+        let exponent = attempt.saturating_sub(1).min(16);
+        let backoff = self
+            .initial_backoff
+            .saturating_mul(1 << exponent)
+            .min(self.max_backoff);
+        let half = backoff / 2;
+        Some(half + half.mul_f64(fastrand::f64()))
+    }
+}
+
+/// Returns whether a request that failed with this status is worth retrying
+pub fn is_retryable_status(status: StatusCode) -> bool {
+    matches!(status.as_u16(), 408 | 429 | 500 | 502 | 503 | 504)
+}
+
+/// Returns whether a request that failed with this network error is worth retrying.
+///
+/// Connection failures, timeouts and I/O errors while sending or receiving (such as a
+/// dropped connection) are transient. Other errors, like an invalid URL or header, fail the
+/// same way on every attempt.
+pub fn is_retryable_error(error: &reqwest::Error) -> bool {
+    error.is_connect() || error.is_timeout() || error.is_body() || has_io_source(error)
+}
+
+/// Returns whether an I/O error is among the causes of an error
+fn has_io_source(error: &(dyn std::error::Error + 'static)) -> bool {
+    std::iter::successors(error.source(), |e| e.source()).any(|e| e.is::<std::io::Error>())
+}
+
+/// Parses a `Retry-After` header given either in seconds or as an HTTP date.
+///
+/// Returns `None` when the header is missing or malformed. Dates in the past yield zero.
+pub fn retry_after(headers: &HeaderMap) -> Option<Duration> {
+    let value = headers.get(RETRY_AFTER)?.to_str().ok()?.trim();
+
+    if let Ok(seconds) = value.parse::<u64>() {
+        return Some(Duration::from_secs(seconds));
+    }
+
+    let date = chrono::DateTime::parse_from_rfc2822(value).ok()?;
+    Some(
+        (date.with_timezone(&chrono::Utc) - chrono::Utc::now())
+            .to_std()
+            .unwrap_or(Duration::ZERO),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use reqwest::header::HeaderValue;
+
+    fn policy() -> RetryPolicy {
+        RetryPolicy {
+            max_retries: 3,
+            initial_backoff: Duration::from_millis(500),
+            max_backoff: Duration::from_secs(2),
+        }
+    }
+
+    #[test]
+    fn delay_backs_off_exponentially_within_bounds() {
+        let policy = policy();
+        for (attempt, window) in [(1, 500), (2, 1000), (3, 2000)] {
+            let delay = policy.delay(attempt, None).unwrap();
+            assert!(delay >= Duration::from_millis(window / 2));
+            assert!(delay <= Duration::from_millis(window));
+        }
+        assert_eq!(policy.delay(4, None), None);
+    }
+
+    #[test]
+    fn delay_honours_retry_after() {
+        let policy = policy();
+        assert_eq!(
+            policy.delay(1, Some(Duration::from_secs(7))),
+            Some(Duration::from_secs(7))
+        );
+        assert_eq!(policy.delay(1, Some(Duration::from_secs(600))), None);
+    }
+
+    #[tokio::test]
+    async fn retries_only_transient_errors() {
+        let network = NetworkConfig {
+            connect_timeout_secs: 2,
+            ..NetworkConfig::default()
+        };
+        assert_eq!(request_timeout(&network, 0), Duration::from_secs(120));
+        assert_eq!(request_timeout(&network, 1_000_000), Duration::from_secs(182));
+
+        let client = client(&network).unwrap();
+        // Nothing listens on port 1
+        let refused = client.get("http://127.0.0.1:1/").send().await.unwrap_err();
+        assert!(is_retryable_error(&refused));
+        let invalid = client.get("http://exa mple.com/").send().await.unwrap_err();
+        assert!(!is_retryable_error(&invalid));
+    }
+
+    #[test]
+    fn parses_retry_after_header() {
+        let mut headers = HeaderMap::new();
+        assert_eq!(retry_after(&headers), None);
+
+        headers.insert(RETRY_AFTER, HeaderValue::from_static("12"));
+        assert_eq!(retry_after(&headers), Some(Duration::from_secs(12)));
+
+        headers.insert(
+            RETRY_AFTER,
+            HeaderValue::from_static("Wed, 21 Oct 2015 07:28:00 GMT"),
+        );
+        assert_eq!(retry_after(&headers), Some(Duration::ZERO));
+    }
+}
@@ -0,0 +1,109 @@
+//! Transcription language selection.
+//!
+//! The language is configured as an ISO 639-1 code (optionally with a region, e.g. "en-US"
+//! for Deepgram) or "auto" to let the provider detect it. Whisper-based APIs report the
+//! detected language by name ("german"), so reported languages are normalized to codes
+//! before they are stored.
+
+/// Configuration value that leaves language detection to the provider
+pub const AUTO: &str = "auto";
+
+/// Languages supported by Whisper, as (code, name) pairs
+#[rustfmt::skip]
+const WHISPER_LANGUAGES: &[(&str, &str)] = &[
+    ("en", "english"), ("zh", "chinese"), ("de", "german"), ("es", "spanish"),
+    ("ru", "russian"), ("ko", "korean"), ("fr", "french"), ("ja", "japanese"),
+    ("pt", "portuguese"), ("tr", "turkish"), ("pl", "polish"), ("ca", "catalan"),
+    ("nl", "dutch"), ("ar", "arabic"), ("sv", "swedish"), ("it", "italian"),
+    ("id", "indonesian"), ("hi", "hindi"), ("fi", "finnish"), ("vi", "vietnamese"),
+    ("he", "hebrew"), ("uk", "ukrainian"), ("el", "greek"), ("ms", "malay"),
+    ("cs", "czech"), ("ro", "romanian"), ("da", "danish"), ("hu", "hungarian"),
+    ("ta", "tamil"), ("no", "norwegian"), ("th", "thai"), ("ur", "urdu"),
+    ("hr", "croatian"), ("bg", "bulgarian"), ("lt", "lithuanian"), ("la", "latin"),
+    ("mi", "maori"), ("ml", "malayalam"), ("cy", "welsh"), ("sk", "slovak"),
+    ("te", "telugu"), ("fa", "persian"), ("lv", "latvian"), ("bn", "bengali"),
+    ("sr", "serbian"), ("az", "azerbaijani"), ("sl", "slovenian"), ("kn", "kannada"),
+    ("et", "estonian"), ("mk", "macedonian"), ("br", "breton"), ("eu", "basque"),
+    ("is", "icelandic"), ("hy", "armenian"), ("ne", "nepali"), ("mn", "mongolian"),
+    ("bs", "bosnian"), ("kk", "kazakh"), ("sq", "albanian"), ("sw", "swahili"),
+    ("gl", "galician"), ("mr", "marathi"), ("pa", "punjabi"), ("si", "sinhala"),
+    ("km", "khmer"), ("sn", "shona"), ("yo", "yoruba"), ("so", "somali"),
+    ("af", "afrikaans"), ("oc", "occitan"), ("ka", "georgian"), ("be", "belarusian"),
+    ("tg", "tajik"), ("sd", "sindhi"), ("gu", "gujarati"), ("am", "amharic"),
+    ("yi", "yiddish"), ("lo", "lao"), ("uz", "uzbek"), ("fo", "faroese"),
+    ("ht", "haitian creole"), ("ps", "pashto"), ("tk", "turkmen"), ("nn", "nynorsk"),
+    ("mt", "maltese"), ("sa", "sanskrit"), ("lb", "luxembourgish"), ("my", "myanmar"),
+    ("bo", "tibetan"), ("tl", "tagalog"), ("mg", "malagasy"), ("as", "assamese"),
+    ("tt", "tatar"), ("haw", "hawaiian"), ("ln", "lingala"), ("ha", "hausa"),
+    ("ba", "bashkir"), ("jw", "javanese"), ("su", "sundanese"), ("yue", "cantonese"),
+];
+
+/// Parses a configured or command-line language.
+///
+/// Returns `None` for "auto" (or an empty value) and the lowercased code otherwise. Region
+/// subtags keep their case, e.g. "en-US".
+///
+/// # Errors
+/// - If the value doesn't look like an ISO 639 code such as "de", "yue" or "pt-BR"
+pub fn parse(value: &str) -> anyhow::Result<Option<String>> {
+    let value = value.trim();
+    if value.is_empty() || value.eq_ignore_ascii_case(AUTO) {
+        return Ok(None);
+    }
+
+    let (code, region) = match value.split_once('-') {
+        Some((code, region)) => (code, Some(region)),
+        None => (value, None),
+    };
+    let valid_code = (2..=3).contains(&code.len()) && code.chars().all(|c| c.is_ascii_alphabetic());
+    let valid_region = region.is_none_or(|r| !r.is_empty() && r.chars().all(|c| c.is_ascii_alphanumeric()));
+    if !valid_code || !valid_region {
+        return Err(anyhow::anyhow!(
+            "Invalid language '{value}'. Use an ISO 639-1 code such as \"en\" or \"de\", or \"auto\"."
+        ));
+    }
+
+    Ok(Some(match region {
+        Some(region) => format!("{}-{region}", code.to_ascii_lowercase()),
+        None => code.to_ascii_lowercase(),
+    }))
+}
+
+/// Returns the language without its region ("pt-BR" becomes "pt"), as Whisper expects it
+pub fn base_code(language: &str) -> &str {
+    language.split('-').next().unwrap_or(language)
+}
+
+/// Converts a language reported by a provider to its code ("German" becomes "de").
+///
+/// Codes and unknown names are returned lowercased as they are.
+pub fn normalize(language: &str) -> String {
+    let language = language.trim().to_lowercase();
+    WHISPER_LANGUAGES
+        .iter()
+        .find(|(_, name)| *name == language)
+        .map(|(code, _)| code.to_string())
+        .unwrap_or(language)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_codes_and_auto() {
+        assert_eq!(parse("auto").unwrap(), None);
+        assert_eq!(parse("").unwrap(), None);
+        assert_eq!(parse("DE").unwrap().as_deref(), Some("de"));
+        assert_eq!(parse("en-US").unwrap().as_deref(), Some("en-US"));
+        assert!(parse("german").is_err());
+        assert!(parse("e1").is_err());
+    }
+
+    #[test]
+    fn normalizes_whisper_names() {
+        assert_eq!(normalize("German"), "de");
+        assert_eq!(normalize("de"), "de");
+        assert_eq!(normalize("klingon"), "klingon");
+    }
+}
@@ -0,0 +1,384 @@
+//! Live streaming transcription over Deepgram's WebSocket API.
+//!
+//! While recording, raw PCM chunks from the `AudioRecorder` sample sink are streamed to
+//! Deepgram's live `/v1/listen` endpoint. Interim and final results are published through a
+//! watch channel so the recording UI can show them under the sparkline, and the final
+//! transcript is ready as soon as the recording stops. If the connection cannot be
+//! established or drops, the session reports an error and the caller falls back to the
+//! regular batch upload of the recorded file.
+
+use futures_util::{SinkExt, StreamExt};
+use serde::Deserialize;
+use std::time::Duration;
+use tokio::sync::{mpsc, watch};
+use tokio::task::JoinHandle;
+use tokio_tungstenite::tungstenite::client::IntoClientRequest;
+use tokio_tungstenite::tungstenite::http::HeaderValue;
+use tokio_tungstenite::tungstenite::Message;
+
+use super::api::deepgram::{self, DeepgramAlternative, DeepgramWord};
+use super::api::TranscriptionConfig;
+use super::response::{Segment, TranscriptionResponse, Word};
+
+/// How often a keep-alive message is sent so Deepgram doesn't close an idle (paused) stream
+const KEEP_ALIVE_INTERVAL: Duration = Duration::from_secs(5);
+
+/// Transcript received so far during a live session.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct LiveTranscript {
+    /// Finalized text; will not change any more
+    pub final_text: String,
+    /// Latest interim hypothesis for the audio after the finalized text
+    pub interim: String,
+}
+
+/// A live transcription result message from Deepgram
+#[derive(Debug, Deserialize)]
+struct LiveResults {
+    #[serde(rename = "type")]
+    kind: String,
+    #[serde(default)]
+    is_final: bool,
+    #[serde(default)]
+    start: f64,
+    #[serde(default)]
+    duration: f64,
+    #[serde(default)]
+    channel: Option<LiveChannel>,
+}
+
+#[derive(Debug, Deserialize)]
+struct LiveChannel {
+    alternatives: Vec<DeepgramAlternative>,
+}
+
+/// Everything collected from the finalized results of a session
+#[derive(Debug, Default)]
+struct Collected {
+    transcript: LiveTranscript,
+    segments: Vec<Segment>,
+    words: Vec<Word>,
+}
+
+/// A running live transcription session.
+///
+/// Dropping the session without calling `finish` aborts the stream.
+pub struct LiveSession {
+    updates: watch::Receiver<LiveTranscript>,
+    task: Option<JoinHandle<anyhow::Result<TranscriptionResponse>>>,
+}
+
+impl LiveSession {
+    /// Starts streaming samples to the live endpoint in the background.
+    ///
+    /// Samples received before the connection is established are buffered by the channel,
+    /// so nothing is lost while connecting. The stream ends when the sample channel closes.
+    ///
+    /// # Arguments
+    /// * `config` - Model, API key, keywords and Deepgram settings for the session
+    /// * `url` - WebSocket endpoint, e.g. "wss://api.deepgram.com/v1/listen"
+    /// * `sample_rate` - Sample rate of the 16-bit mono PCM in `samples`
+    /// * `samples` - Receiver of captured sample chunks
+    pub fn start(
+        config: TranscriptionConfig,
+        url: &str,
+        sample_rate: u32,
+        samples: mpsc::UnboundedReceiver<Vec<i16>>,
+    ) -> Self {
+        let mut url = format!(
+            "{}?{}&encoding=linear16&sample_rate={}&channels=1&interim_results=true",
+            url,
+            deepgram::query(&config),
+            sample_rate
+        );
+        if let Some(language) = deepgram::language_param(&config, true) {
+            url.push_str(&format!("&{language}"));
+        }
+        let (updates_tx, updates) = watch::channel(LiveTranscript::default());
+
+        let task = tokio::spawn(async move {
+            let result = stream(&config, &url, samples, &updates_tx).await;
+            if let Err(e) = &result {
+                tracing::warn!("Live transcription failed: {}", e);
+            }
+            result
+        });
+
+        Self {
+            updates,
+            task: Some(task),
+        }
+    }
+
+    /// Returns the transcript received so far
+    pub fn transcript(&self) -> LiveTranscript {
+        self.updates.borrow().clone()
+    }
+
+    /// Returns whether the stream is still running (false once it ended or failed)
+    pub fn is_active(&self) -> bool {
+        self.task.as_ref().is_some_and(|task| !task.is_finished())
+    }
+
+    /// Waits for the final transcript after the sample channel has been closed.
+    ///
+    /// # Errors
+    /// - If the connection failed or dropped during the session
+    /// - If Deepgram does not deliver the final results within `timeout`
+    pub async fn finish(mut self, timeout: Duration) -> anyhow::Result<TranscriptionResponse> {
+        let Some(mut task) = self.task.take() else {
+            return Err(anyhow::anyhow!("Live transcription session already finished"));
+        };
+
+        match tokio::time::timeout(timeout, &mut task).await {
+            Ok(Ok(result)) => result,
+            Ok(Err(e)) => Err(anyhow::anyhow!("Live transcription task failed: {e}")),
+            Err(_) => {
+                task.abort();
+                Err(anyhow::anyhow!(
+                    "Live transcription did not finish within {}s",
+                    timeout.as_secs()
+                ))
+            }
+        }
+    }
+}
+
+impl Drop for LiveSession {
+    fn drop(&mut self) {
+        if let Some(task) = self.task.take() {
+            task.abort();
+        }
+    }
+}
+
+/// Connects to the live endpoint, streams the samples and collects the results.
+///
+/// # Errors
+/// - If the WebSocket handshake fails (network error, invalid key, bad URL)
+/// - If the connection drops before Deepgram confirms the end of the stream
+async fn stream(
+    config: &TranscriptionConfig,
+    url: &str,
+    mut samples: mpsc::UnboundedReceiver<Vec<i16>>,
+    updates: &watch::Sender<LiveTranscript>,
+) -> anyhow::Result<TranscriptionResponse> {
+    let mut request = url
+        .into_client_request()
+        .map_err(|e| anyhow::anyhow!("Invalid Deepgram streaming URL '{url}': {e}"))?;
+    let auth = HeaderValue::from_str(&format!("Token {}", config.api_key))
+        .map_err(|e| anyhow::anyhow!("Invalid Deepgram API key: {e}"))?;
+    request.headers_mut().insert("Authorization", auth);
+
+    tracing::debug!(
+        "Deepgram Live Call:\n  URL: {}\n  Headers:\n    Authorization: Token <redacted>",
+        url
+    );
+
+    let connect_timeout = Duration::from_secs(config.network.connect_timeout_secs);
+    let (socket, _) = tokio::time::timeout(connect_timeout, tokio_tungstenite::connect_async(request))
+        .await
+        .map_err(|_| anyhow::anyhow!("Timed out connecting to Deepgram live streaming"))?
+        .map_err(|e| anyhow::anyhow!("Failed to connect to Deepgram live streaming: {e}"))?;
+    let (mut write, mut read) = socket.split();
+
+    tracing::info!("Live transcription connected");
+
+    let mut collected = Collected::default();
+    let mut keep_alive = tokio::time::interval(KEEP_ALIVE_INTERVAL);
+    let mut audio_done = false;
+
+    loop {
+        tokio::select! {
+            chunk = samples.recv(), if !audio_done => match chunk {
+                Some(chunk) => {
+                    let bytes: Vec<u8> = chunk.iter().flat_map(|s| s.to_le_bytes()).collect();
+                    write.send(Message::binary(bytes)).await.map_err(|e| {
+                        anyhow::anyhow!("Failed to send audio to Deepgram: {e}")
+                    })?;
+                }
+                None => {
+                    // Recording stopped: ask Deepgram to flush the remaining results and close
+                    audio_done = true;
+                    write
+                        .send(Message::text(r#"{"type":"CloseStream"}"#))
+                        .await
+                        .map_err(|e| anyhow::anyhow!("Failed to close Deepgram stream: {e}"))?;
+                }
+            },
+            _ = keep_alive.tick(), if !audio_done => {
+                write
+                    .send(Message::text(r#"{"type":"KeepAlive"}"#))
+                    .await
+                    .map_err(|e| anyhow::anyhow!("Failed to send keep-alive to Deepgram: {e}"))?;
+            }
+            message = read.next() => match message {
+                Some(Ok(Message::Text(text))) => {
+                    if apply_message(&mut collected, &text) {
+                        let _ = updates.send(collected.transcript.clone());
+                    }
+                }
+                Some(Ok(Message::Close(frame))) => {
+                    if audio_done {
+                        break;
+                    }
+                    let reason = frame.map(|f| f.reason.to_string()).unwrap_or_default();
+                    return Err(anyhow::anyhow!("Deepgram closed the live stream: {reason}"));
+                }
+                Some(Ok(_)) => {}
+                Some(Err(e)) => {
+                    return Err(anyhow::anyhow!("Deepgram live stream error: {e}"));
+                }
+                None if audio_done => break,
+                None => return Err(anyhow::anyhow!("Deepgram live stream ended unexpectedly")),
+            },
+        }
+    }
+
+    // Anything still interim when the stream closed is the best hypothesis there is
+    let transcript = &collected.transcript;
+    let text = join_text(&transcript.final_text, &transcript.interim);
+    tracing::debug!(
+        "Deepgram Live Response:\n  Transcription length: {} characters\n  Segments: {}",
+        text.len(),
+        collected.segments.len()
+    );
+    let response = TranscriptionResponse {
+        text,
+        segments: collected.segments,
+        words: collected.words,
+        ..TranscriptionResponse::default()
+    }
+    .with_derived_confidence();
+    Ok(deepgram::label_speakers(config, response))
+}
+
+/// Applies a Deepgram message to the collected results, returning whether the transcript
+/// changed.
+fn apply_message(collected: &mut Collected, message: &str) -> bool {
+    let Ok(results) = serde_json::from_str::<LiveResults>(message) else {
+        tracing::debug!("Ignoring unparseable Deepgram live message: {}", message);
+        return false;
+    };
+    if results.kind != "Results" {
+        return false;
+    }
+
+    let Some(alternative) = results
+        .channel
+        .and_then(|channel| channel.alternatives.into_iter().next())
+    else {
+        return false;
+    };
+
+    let transcript = &mut collected.transcript;
+    if results.is_final {
+        transcript.final_text = join_text(&transcript.final_text, &alternative.transcript);
+        transcript.interim.clear();
+        if !alternative.transcript.trim().is_empty() {
+            collected.segments.push(Segment {
+                start: results.start,
+                end: results.start + results.duration,
+                text: alternative.transcript.trim().to_string(),
+                confidence: alternative.confidence,
+                speaker: None,
+            });
+        }
+        collected
+            .words
+            .extend(alternative.words.into_iter().map(DeepgramWord::into_word));
+    } else {
+        transcript.interim = alternative.transcript;
+    }
+    true
+}
+
+/// Joins two transcript fragments with a single space, skipping empty ones.
+fn join_text(first: &str, second: &str) -> String {
+    match (first.trim(), second.trim()) {
+        ("", second) => second.to_string(),
+        (first, "") => first.to_string(),
+        (first, second) => format!("{first} {second}"),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::file::{NetworkConfig, ProvidersConfig};
+    use crate::transcription::model::TranscriptionModel;
+    use tokio::net::TcpListener;
+
+    /// Minimal stand-in for Deepgram's live endpoint: answers every audio chunk with an
+    /// interim result and finalizes everything when the client sends CloseStream.
+    async fn stand_in_server() -> String {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let address = listener.local_addr().unwrap();
+
+        tokio::spawn(async move {
+            let (stream, _) = listener.accept().await.unwrap();
+            let mut socket = tokio_tungstenite::accept_async(stream).await.unwrap();
+            let mut chunks = 0;
+            while let Some(Ok(message)) = socket.next().await {
+                let reply = match message {
+                    Message::Binary(_) => {
+                        chunks += 1;
+                        format!(
+                            r#"{{"type":"Results","is_final":false,"channel":{{"alternatives":[{{"transcript":"chunk {chunks}"}}]}}}}"#
+                        )
+                    }
+                    Message::Text(text) if text.contains("CloseStream") => {
+                        let reply = format!(
+                            r#"{{"type":"Results","is_final":true,"channel":{{"alternatives":[{{"transcript":"{chunks} chunks received"}}]}}}}"#
+                        );
+                        socket.send(Message::text(reply)).await.unwrap();
+                        socket.close(None).await.unwrap();
+                        break;
+                    }
+                    _ => continue,
+                };
+                socket.send(Message::text(reply)).await.unwrap();
+            }
+        });
+
+        format!("ws://{address}/v1/listen")
+    }
+
+    fn config() -> TranscriptionConfig {
+        TranscriptionConfig::new(
+            TranscriptionModel::new("nova-3", "deepgram", "nova-3", "Nova 3"),
+            "test-key".to_string(),
+            Vec::new(),
+            ProvidersConfig::default(),
+            NetworkConfig::default(),
+        )
+    }
+
+    #[tokio::test]
+    async fn streams_samples_and_returns_final_transcript() {
+        let url = stand_in_server().await;
+        let (sender, receiver) = mpsc::unbounded_channel();
+        let session = LiveSession::start(config(), &url, 16000, receiver);
+
+        sender.send(vec![0i16; 1600]).unwrap();
+        sender.send(vec![0i16; 1600]).unwrap();
+        drop(sender);
+
+        let response = session.finish(Duration::from_secs(5)).await.unwrap();
+        assert_eq!(response.text, "2 chunks received");
+        assert_eq!(response.segments.len(), 1);
+    }
+
+    #[tokio::test]
+    async fn fails_when_server_is_unreachable() {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("ws://{}/v1/listen", listener.local_addr().unwrap());
+        drop(listener);
+
+        let (sender, receiver) = mpsc::unbounded_channel();
+        let session = LiveSession::start(config(), &url, 16000, receiver);
+        drop(sender);
+
+        assert!(session.finish(Duration::from_secs(5)).await.is_err());
+    }
+}
@@ -0,0 +1,1100 @@
+//! Offline stand-in for the hosted transcription APIs.
+//!
+//! `ostt mock-server` serves imitations of the OpenAI, Groq, DeepInfra and Deepgram upload
+//! and model list endpoints on localhost. Each one validates requests the way the real API does
+//! (authentication, required fields, known models, response formats, upload limits) and
+//! answers in that provider's response format, so the whole record → transcribe → history
+//! path can be exercised without network access by pointing the providers' base URLs at it.
+//! AssemblyAI's upload, job and polling endpoints are imitated too; its jobs are queued,
+//! then processing, and complete on the second status check.
+//!
+//! Any API key is accepted, except for a few that simulate failures: "invalid" (401),
+//! "rate-limited" (429 with `Retry-After`) and "server-error" (500). AssemblyAI jobs
+//! created with the key "slow" never complete.
+
+use serde_json::{json, Value};
+use std::net::SocketAddr;
+use std::sync::{Arc, Mutex};
+use tokio::io::{AsyncBufReadExt, AsyncReadExt, AsyncWriteExt, BufReader};
+use tokio::net::{TcpListener, TcpStream};
+use tokio::task::JoinHandle;
+
+/// Providers served by the mock, with the base URL path of each
+pub const PROVIDERS: [(&str, &str); 5] = [
+    ("openai", "/openai/v1"),
+    ("groq", "/groq/openai/v1"),
+    ("deepinfra", "/deepinfra/v1"),
+    ("deepgram", "/deepgram/v1"),
+    ("assemblyai", "/assemblyai/v2"),
+];
+
+/// Upload limit of the OpenAI and Groq endpoints
+const MAX_UPLOAD_BYTES: usize = 25 * 1024 * 1024;
+
+/// Largest request body the server reads; larger requests are rejected with 413 unread
+const MAX_BODY_BYTES: usize = 64 * 1024 * 1024;
+
+/// Seconds each word of a mock transcript lasts
+const WORD_SECS: f64 = 0.3;
+
+/// Speech models the OpenAI endpoints accept
+const OPENAI_MODELS: [&str; 3] = ["whisper-1", "gpt-4o-transcribe", "gpt-4o-mini-transcribe"];
+
+/// Speech models the Groq endpoints accept, including one ostt doesn't know
+const GROQ_MODELS: [&str; 3] = [
+    "whisper-large-v3",
+    "whisper-large-v3-turbo",
+    "distil-whisper-large-v3-en",
+];
+
+/// Models the DeepInfra inference endpoint accepts, including one ostt doesn't know
+const DEEPINFRA_MODELS: [&str; 3] = [
+    "openai/whisper-large-v3",
+    "openai/whisper-base",
+    "openai/whisper-large-v3-turbo",
+];
+
+/// Models Deepgram's `/listen` endpoint accepts, including one ostt doesn't know
+const DEEPGRAM_MODELS: [&str; 3] = ["nova-3", "nova-2", "nova-3-medical"];
+
+/// Speech models AssemblyAI's job endpoint accepts
+const ASSEMBLYAI_MODELS: [&str; 4] = ["universal", "best", "nano", "slam-1"];
+
+/// Uploads and jobs of the AssemblyAI imitation, shared by all connections
+#[derive(Debug, Default)]
+struct AssemblyAiState {
+    /// URLs of the uploaded files
+    uploads: Vec<String>,
+    jobs: Vec<MockJob>,
+}
+
+/// An AssemblyAI transcription job
+#[derive(Debug, Clone)]
+struct MockJob {
+    id: String,
+    model: String,
+    speaker_labels: bool,
+    /// Number of status checks so far
+    polls: u32,
+    /// Whether the job never completes
+    slow: bool,
+}
+
+/// A running mock server. The server stops when this is dropped.
+pub struct MockServer {
+    address: SocketAddr,
+    task: JoinHandle<()>,
+}
+
+/// State shared by the connections of a server
+type SharedState = Arc<Mutex<AssemblyAiState>>;
+
+impl MockServer {
+    /// Starts serving on the given address, e.g. "127.0.0.1:8787" (port 0 picks a free one).
+    ///
+    /// # Errors
+    /// - If the address cannot be bound
+    pub async fn start(address: &str) -> anyhow::Result<Self> {
+        let listener = TcpListener::bind(address)
+            .await
+            .map_err(|e| anyhow::anyhow!("Failed to listen on {address}: {e}"))?;
+        let address = listener.local_addr()?;
+
+        let state = SharedState::default();
+        let task = tokio::spawn(async move {
+            while let Ok((stream, _)) = listener.accept().await {
+                let state = state.clone();
+                tokio::spawn(async move {
+                    if let Err(e) = serve(stream, &state).await {
+                        tracing::debug!("Mock server connection failed: {}", e);
+                    }
+                });
+            }
+        });
+
+        tracing::info!("Mock transcription server listening on {}", address);
+        Ok(Self { address, task })
+    }
+
+    /// Root URL of the server
+    pub fn url(&self) -> String {
+        format!("http://{}", self.address)
+    }
+
+    /// Base URL to configure for a provider, or `None` if the provider isn't mocked
+    pub fn base_url(&self, provider_id: &str) -> Option<String> {
+        PROVIDERS
+            .iter()
+            .find(|(id, _)| *id == provider_id)
+            .map(|(_, path)| format!("{}{path}", self.url()))
+    }
+}
+
+impl Drop for MockServer {
+    fn drop(&mut self) {
+        self.task.abort();
+    }
+}
+
+/// The API a request is addressed to, which determines its error format
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Api {
+    OpenAi,
+    Groq,
+    DeepInfra,
+    Deepgram,
+    AssemblyAi,
+}
+
+/// A parsed HTTP request
+struct Request {
+    method: String,
+    path: String,
+    query: String,
+    headers: Vec<(String, String)>,
+    body: Vec<u8>,
+}
+
+impl Request {
+    /// Returns the value of a header (names are compared case-insensitively)
+    fn header(&self, name: &str) -> Option<&str> {
+        self.headers
+            .iter()
+            .find(|(key, _)| key.eq_ignore_ascii_case(name))
+            .map(|(_, value)| value.as_str())
+    }
+
+    /// Returns all values of a query parameter
+    fn query_values(&self, name: &str) -> Vec<&str> {
+        self.query
+            .split('&')
+            .filter_map(|pair| pair.split_once('='))
+            .filter(|(key, _)| *key == name)
+            .map(|(_, value)| value)
+            .collect()
+    }
+}
+
+/// An HTTP response
+struct Reply {
+    status: u16,
+    content_type: &'static str,
+    body: String,
+    retry_after: Option<u64>,
+}
+
+impl Reply {
+    fn json(value: Value) -> Self {
+        Self {
+            status: 200,
+            content_type: "application/json",
+            body: value.to_string(),
+            retry_after: None,
+        }
+    }
+
+    fn text(body: String) -> Self {
+        Self {
+            status: 200,
+            content_type: "text/plain",
+            body,
+            retry_after: None,
+        }
+    }
+
+    /// An error in the body format of the given API
+    fn error(api: Api, status: u16, message: &str) -> Self {
+        let body = match api {
+            Api::OpenAi | Api::Groq => json!({
+                "error": {"message": message, "type": "invalid_request_error"}
+            }),
+            Api::DeepInfra => json!({"detail": {"error": message}}),
+            Api::AssemblyAi => json!({"error": message}),
+            Api::Deepgram => json!({
+                "err_code": "Bad Request",
+                "err_msg": message,
+                "request_id": "mock"
+            }),
+        };
+        Self {
+            status,
+            content_type: "application/json",
+            body: body.to_string(),
+            retry_after: None,
+        }
+    }
+}
+
+/// A field of a multipart form
+struct Part {
+    name: String,
+    file_name: Option<String>,
+    data: Vec<u8>,
+}
+
+impl Part {
+    fn text(&self) -> String {
+        String::from_utf8_lossy(&self.data).to_string()
+    }
+}
+
+/// Reads one request from the connection and answers it
+async fn serve(stream: TcpStream, state: &SharedState) -> anyhow::Result<()> {
+    let mut reader = BufReader::new(stream);
+    let reply = match read_request(&mut reader).await? {
+        Ok(request) => {
+            let reply = route(&request, state);
+            tracing::debug!(
+                "Mock server: {} {} -> {}",
+                request.method,
+                request.path,
+                reply.status
+            );
+            reply
+        }
+        Err(reply) => reply,
+    };
+
+    let mut head = format!(
+        "HTTP/1.1 {} {}\r\nContent-Type: {}\r\nContent-Length: {}\r\nConnection: close\r\n",
+        reply.status,
+        reason_phrase(reply.status),
+        reply.content_type,
+        reply.body.len()
+    );
+    if let Some(seconds) = reply.retry_after {
+        head.push_str(&format!("Retry-After: {seconds}\r\n"));
+    }
+    head.push_str("\r\n");
+
+    let stream = reader.get_mut();
+    stream.write_all(head.as_bytes()).await?;
+    stream.write_all(reply.body.as_bytes()).await?;
+    stream.shutdown().await?;
+    Ok(())
+}
+
+/// Parses the request line, headers and body (plain or chunked).
+///
+/// Returns the error reply for a request whose body is larger than [`MAX_BODY_BYTES`],
+/// without reading the body.
+async fn read_request(
+    reader: &mut BufReader<TcpStream>,
+) -> anyhow::Result<Result<Request, Reply>> {
+    let mut line = String::new();
+    reader.read_line(&mut line).await?;
+    let mut request_line = line.split_whitespace();
+    let method = request_line.next().unwrap_or_default().to_string();
+    let target = request_line.next().unwrap_or_default();
+    let (path, query) = target.split_once('?').unwrap_or((target, ""));
+    let (path, query) = (path.to_string(), query.to_string());
+
+    let mut headers = Vec::new();
+    loop {
+        line.clear();
+        if reader.read_line(&mut line).await? == 0 || line.trim_end().is_empty() {
+            break;
+        }
+        if let Some((name, value)) = line.split_once(':') {
+            headers.push((name.trim().to_string(), value.trim().to_string()));
+        }
+    }
+
+    let mut request = Request {
+        method,
+        path,
+        query,
+        headers,
+        body: Vec::new(),
+    };
+
+    let too_large = || Reply::error(Api::OpenAi, 413, "Request body too large");
+    if let Some(length) = request.header("content-length").and_then(|v| v.parse().ok()) {
+        if length > MAX_BODY_BYTES {
+            return Ok(Err(too_large()));
+        }
+        request.body = vec![0; length];
+        reader.read_exact(&mut request.body).await?;
+    } else if request
+        .header("transfer-encoding")
+        .is_some_and(|v| v.eq_ignore_ascii_case("chunked"))
+    {
+        loop {
+            line.clear();
+            reader.read_line(&mut line).await?;
+            let size = usize::from_str_radix(line.trim(), 16)?;
+            if size > MAX_BODY_BYTES - request.body.len() {
+                return Ok(Err(too_large()));
+            }
+            let mut chunk = vec![0; size + 2];
+            reader.read_exact(&mut chunk).await?;
+            if size == 0 {
+                break;
+            }
+            request.body.extend_from_slice(&chunk[..size]);
+        }
+    }
+
+    Ok(Ok(request))
+}
+
+/// Dispatches a request to the imitated endpoint
+fn route(request: &Request, state: &SharedState) -> Reply {
+    let path = request.path.as_str();
+    if let Some(endpoint) = path.strip_prefix("/assemblyai/v2/") {
+        let mut state = state.lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+        return assemblyai(endpoint, request, &mut state);
+    }
+    match path {
+        "/openai/v1/models" => return model_list(Api::OpenAi, &OPENAI_MODELS, "gpt-4o", request),
+        "/groq/openai/v1/models" => {
+            return model_list(Api::Groq, &GROQ_MODELS, "llama-3.3-70b-versatile", request)
+        }
+        "/deepinfra/v1/openai/models" => {
+            let other = "meta-llama/Llama-3.3-70B-Instruct";
+            return model_list(Api::DeepInfra, &DEEPINFRA_MODELS, other, request);
+        }
+        "/deepgram/v1/models" => return deepgram_models(request),
+        _ => {}
+    }
+    if let Some(endpoint) = path.strip_prefix("/openai/v1/audio/") {
+        return whisper_api(Api::OpenAi, endpoint, request);
+    }
+    if let Some(endpoint) = path.strip_prefix("/groq/openai/v1/audio/") {
+        return whisper_api(Api::Groq, endpoint, request);
+    }
+    if let Some(model) = path.strip_prefix("/deepinfra/v1/inference/") {
+        return deepinfra(model, request);
+    }
+    if path == "/deepgram/v1/listen" {
+        return deepgram(request);
+    }
+    Reply::error(Api::OpenAi, 404, &format!("No mock endpoint at {path}"))
+}
+
+/// Checks the method and API key, returning the error reply if the request is rejected
+fn authenticate(api: Api, method: &str, request: &Request) -> Result<(), Reply> {
+    if request.method != method {
+        return Err(Reply::error(api, 405, "Method not allowed"));
+    }
+
+    let scheme = match api {
+        Api::Deepgram => "Token ",
+        Api::AssemblyAi => "",
+        _ => "Bearer ",
+    };
+    let key = request
+        .header("authorization")
+        .and_then(|value| value.strip_prefix(scheme))
+        .map(str::trim);
+    match key {
+        None | Some("") => Err(Reply::error(api, 401, "Missing API key")),
+        Some("invalid") => Err(Reply::error(api, 401, "Invalid API key")),
+        Some("rate-limited") => Err(Reply {
+            retry_after: Some(1),
+            ..Reply::error(api, 429, "Rate limit reached")
+        }),
+        Some("server-error") => Err(Reply::error(api, 500, "Internal server error")),
+        Some(_) => Ok(()),
+    }
+}
+
+/// Imitates the OpenAI-style `/audio/transcriptions` and `/audio/translations` endpoints
+fn whisper_api(api: Api, endpoint: &str, request: &Request) -> Reply {
+    if let Err(reply) = authenticate(api, "POST", request) {
+        return reply;
+    }
+    let translate = match endpoint {
+        "transcriptions" => false,
+        "translations" => true,
+        _ => return Reply::error(api, 404, &format!("Unknown endpoint '{endpoint}'")),
+    };
+    let parts = match multipart(request) {
+        Ok(parts) => parts,
+        Err(message) => return Reply::error(api, 400, &message),
+    };
+    let field = |name: &str| parts.iter().find(|part| part.name == name);
+
+    match field("file") {
+        Some(file) if file.file_name.is_some() && !file.data.is_empty() => {
+            if file.data.len() > MAX_UPLOAD_BYTES {
+                return Reply::error(api, 413, "Maximum content size limit exceeded");
+            }
+        }
+        _ => return Reply::error(api, 400, "'file' is a required property"),
+    }
+
+    let model = field("model").map(Part::text).unwrap_or_default();
+    let (models, translation_models): (&[&str], &[&str]) = match api {
+        Api::Groq => (&GROQ_MODELS, &["whisper-large-v3"]),
+        _ => (&OPENAI_MODELS, &["whisper-1"]),
+    };
+    if !models.contains(&model.as_str()) {
+        return Reply::error(api, 400, &format!("The model '{model}' does not exist"));
+    }
+    if translate && !translation_models.contains(&model.as_str()) {
+        return Reply::error(api, 400, &format!("The model '{model}' does not support translation"));
+    }
+
+    let format = field("response_format")
+        .map(Part::text)
+        .unwrap_or_else(|| "json".to_string());
+    let formats: &[&str] = match (api, model.starts_with("gpt-4o")) {
+        (_, true) => &["json", "text"],
+        (Api::Groq, _) => &["json", "verbose_json", "text"],
+        _ => &["json", "verbose_json", "text", "srt", "vtt"],
+    };
+    if !formats.contains(&format.as_str()) {
+        return Reply::error(
+            api,
+            400,
+            &format!("response_format '{format}' is not compatible with model '{model}'"),
+        );
+    }
+
+    let granularities: Vec<String> = parts
+        .iter()
+        .filter(|part| part.name == "timestamp_granularities[]")
+        .map(Part::text)
+        .collect();
+    if !granularities.is_empty() && format != "verbose_json" {
+        return Reply::error(api, 400, "timestamp_granularities requires response_format verbose_json");
+    }
+    if let Some(temperature) = field("temperature").map(Part::text) {
+        if !temperature.parse::<f32>().is_ok_and(|t| (0.0..=1.0).contains(&t)) {
+            return Reply::error(api, 400, "temperature must be between 0 and 1");
+        }
+    }
+
+    let text = mock_text(&model, translate);
+    let duration = duration_of(&text);
+    match format.as_str() {
+        "text" => Reply::text(text),
+        "srt" => Reply::text(format!("1\n00:00:00,000 --> {}\n{text}\n", timestamp(duration, ','))),
+        "vtt" => Reply::text(format!(
+            "WEBVTT\n\n00:00:00.000 --> {}\n{text}\n",
+            timestamp(duration, '.')
+        )),
+        "verbose_json" => {
+            let mut body = json!({
+                "task": if translate { "translate" } else { "transcribe" },
+                "language": "english",
+                "duration": duration,
+                "text": text,
+            });
+            // Without granularities the API returns segments only
+            if granularities.is_empty() || granularities.iter().any(|g| g == "segment") {
+                body["segments"] = json!([{
+                    "id": 0, "start": 0.0, "end": duration, "text": text, "avg_logprob": -0.1
+                }]);
+            }
+            if granularities.iter().any(|g| g == "word") {
+                body["words"] = Value::Array(
+                    words_of(&text)
+                        .map(|(word, start, end)| json!({"word": word, "start": start, "end": end}))
+                        .collect(),
+                );
+            }
+            Reply::json(body)
+        }
+        _ => Reply::json(json!({"text": text})),
+    }
+}
+
+/// Imitates the OpenAI-style `/models` endpoint, listing the speech models and one other
+fn model_list(api: Api, models: &[&str], other: &str, request: &Request) -> Reply {
+    if let Err(reply) = authenticate(api, "GET", request) {
+        return reply;
+    }
+    let data: Vec<Value> = models
+        .iter()
+        .chain([&other])
+        .map(|id| json!({"id": id, "object": "model", "created": 0, "owned_by": "mock"}))
+        .collect();
+    Reply::json(json!({"object": "list", "data": data}))
+}
+
+/// Imitates Deepgram's `/models` endpoint
+fn deepgram_models(request: &Request) -> Reply {
+    if let Err(reply) = authenticate(Api::Deepgram, "GET", request) {
+        return reply;
+    }
+    let stt: Vec<Value> = DEEPGRAM_MODELS
+        .iter()
+        .map(|name| json!({"name": name, "canonical_name": name, "batch": true}))
+        .collect();
+    Reply::json(json!({"stt": stt, "tts": [{"name": "aura-2", "canonical_name": "aura-2"}]}))
+}
+
+/// Imitates AssemblyAI's `/upload` and `/transcript` endpoints
+fn assemblyai(endpoint: &str, request: &Request, state: &mut AssemblyAiState) -> Reply {
+    let api = Api::AssemblyAi;
+    let method = request.method.as_str();
+    let job_id = endpoint.strip_prefix("transcript/");
+    let method_allowed = match (endpoint, job_id) {
+        ("upload", _) => method == "POST",
+        ("transcript", _) => method == "POST" || method == "GET",
+        (_, Some(_)) => method == "GET" || method == "DELETE",
+        _ => return Reply::error(api, 404, &format!("Unknown endpoint '{endpoint}'")),
+    };
+    if !method_allowed {
+        return Reply::error(api, 405, "Method not allowed");
+    }
+    if let Err(reply) = authenticate(api, method, request) {
+        return reply;
+    }
+
+    match (endpoint, job_id) {
+        ("upload", _) => {
+            if request.body.is_empty() {
+                return Reply::error(api, 400, "Upload body is empty");
+            }
+            if request.body.len() > MAX_UPLOAD_BYTES {
+                return Reply::error(api, 413, "Upload is too large");
+            }
+            let url = format!("https://cdn.assemblyai.com/upload/mock-{}", state.uploads.len());
+            state.uploads.push(url.clone());
+            Reply::json(json!({"upload_url": url}))
+        }
+        ("transcript", _) if method == "GET" => {
+            let jobs: Vec<Value> = state
+                .jobs
+                .iter()
+                .map(|job| json!({"id": job.id, "status": job_status(job)}))
+                .collect();
+            Reply::json(json!({"transcripts": jobs}))
+        }
+        ("transcript", _) => create_job(request, state),
+        (_, Some(id)) => {
+            let Some(index) = state.jobs.iter().position(|job| job.id == id) else {
+                return Reply::error(api, 404, "Transcript not found");
+            };
+            if method == "DELETE" {
+                let job = state.jobs.remove(index);
+                return Reply::json(json!({"id": job.id, "status": "completed", "text": ""}));
+            }
+            let job = &mut state.jobs[index];
+            job.polls += 1;
+            job_reply(job)
+        }
+        _ => Reply::error(api, 404, &format!("Unknown endpoint '{endpoint}'")),
+    }
+}
+
+/// Validates a job creation request and queues the job
+fn create_job(request: &Request, state: &mut AssemblyAiState) -> Reply {
+    let api = Api::AssemblyAi;
+    let body: Value = match serde_json::from_slice(&request.body) {
+        Ok(body) => body,
+        Err(e) => return Reply::error(api, 400, &format!("Invalid JSON: {e}")),
+    };
+    let audio_url = body["audio_url"].as_str().unwrap_or_default();
+    if !state.uploads.iter().any(|url| url == audio_url) {
+        return Reply::error(api, 400, "audio_url is not a valid upload URL");
+    }
+    let model = body["speech_model"].as_str().unwrap_or("universal").to_string();
+    if !ASSEMBLYAI_MODELS.contains(&model.as_str()) {
+        return Reply::error(api, 400, &format!("Invalid speech_model '{model}'"));
+    }
+    if let Some(boost) = body.get("boost_param").and_then(Value::as_str) {
+        if !["low", "default", "high"].contains(&boost) {
+            return Reply::error(api, 400, &format!("Invalid boost_param '{boost}'"));
+        }
+    }
+    if body.get("word_boost").is_some_and(|words| {
+        !words.as_array().is_some_and(|words| words.iter().all(Value::is_string))
+    }) {
+        return Reply::error(api, 400, "word_boost must be a list of strings");
+    }
+    if body["language_code"].is_string() && body["language_detection"] == json!(true) {
+        return Reply::error(api, 400, "language_code can't be combined with language_detection");
+    }
+
+    let job = MockJob {
+        id: format!("mock-job-{}", state.jobs.len() + 1),
+        model,
+        speaker_labels: body["speaker_labels"] == json!(true),
+        polls: 0,
+        slow: request.header("authorization") == Some("slow"),
+    };
+    let reply = job_reply(&job);
+    state.jobs.push(job);
+    reply
+}
+
+/// Status of a job, which completes on its second status check unless it is slow
+fn job_status(job: &MockJob) -> &'static str {
+    match job.polls {
+        0 => "queued",
+        1 => "processing",
+        _ if job.slow => "processing",
+        _ => "completed",
+    }
+}
+
+/// The job as AssemblyAI returns it, with the transcript once it is completed
+fn job_reply(job: &MockJob) -> Reply {
+    let status = job_status(job);
+    if status != "completed" {
+        return Reply::json(json!({"id": job.id, "status": status, "text": null, "words": null}));
+    }
+
+    let text = mock_text(&job.model, false);
+    let speaker = job.speaker_labels.then_some("A");
+    let words: Vec<Value> = words_of(&text)
+        .map(|(word, start, end)| {
+            json!({
+                "text": word,
+                "start": (start * 1000.0) as u64,
+                "end": (end * 1000.0) as u64,
+                "confidence": 0.97,
+                "speaker": speaker,
+            })
+        })
+        .collect();
+    let duration = duration_of(&text);
+    let utterances = job.speaker_labels.then(|| {
+        json!([{
+            "text": text, "start": 0, "end": (duration * 1000.0) as u64,
+            "confidence": 0.97, "speaker": "A"
+        }])
+    });
+    Reply::json(json!({
+        "id": job.id,
+        "status": status,
+        "text": text,
+        "words": words,
+        "utterances": utterances,
+        "confidence": 0.97,
+        "language_code": "en",
+        "audio_duration": duration,
+    }))
+}
+
+/// Imitates DeepInfra's inference endpoint for Whisper models
+fn deepinfra(model: &str, request: &Request) -> Reply {
+    let api = Api::DeepInfra;
+    if let Err(reply) = authenticate(api, "POST", request) {
+        return reply;
+    }
+    if !DEEPINFRA_MODELS.contains(&model) {
+        return Reply::error(api, 404, &format!("Model is not available: {model}"));
+    }
+    let parts = match multipart(request) {
+        Ok(parts) => parts,
+        Err(message) => return Reply::error(api, 400, &message),
+    };
+    let field = |name: &str| parts.iter().find(|part| part.name == name);
+
+    if field("audio").is_none_or(|audio| audio.data.is_empty()) {
+        return Reply::error(api, 422, "Field required: audio");
+    }
+    let translate = match field("task").map(Part::text).as_deref() {
+        None | Some("transcribe") => false,
+        Some("translate") => true,
+        Some(task) => return Reply::error(api, 422, &format!("Invalid task '{task}'")),
+    };
+
+    let text = mock_text(model, translate);
+    if field("response_format").is_some_and(|format| format.text() == "text") {
+        return Reply::text(text);
+    }
+    let duration = duration_of(&text);
+    Reply::json(json!({
+        "text": text,
+        "language": "en",
+        "segments": [{"id": 0, "start": 0.0, "end": duration, "text": text}],
+        "input_length_ms": (duration * 1000.0) as u64,
+    }))
+}
+
+/// Imitates Deepgram's pre-recorded `/listen` endpoint
+fn deepgram(request: &Request) -> Reply {
+    let api = Api::Deepgram;
+    if let Err(reply) = authenticate(api, "POST", request) {
+        return reply;
+    }
+    let model = request.query_values("model").first().copied().unwrap_or("nova-3");
+    if !DEEPGRAM_MODELS.contains(&model) {
+        return Reply::error(api, 400, &format!("No such model: {model}"));
+    }
+    let keyword_param = request.query_values("keywords");
+    if model.starts_with("nova-3") && !keyword_param.is_empty() {
+        return Reply::error(api, 400, "Keywords are not supported for nova-3, use keyterm");
+    }
+    if !request.header("content-type").is_some_and(|t| t.starts_with("audio/")) {
+        return Reply::error(api, 415, "Unsupported content type, send audio");
+    }
+    if request.body.is_empty() {
+        return Reply::error(api, 400, "Failed to process audio: corrupt or unsupported data");
+    }
+
+    let enabled = |name: &str| request.query_values(name).contains(&"true");
+    let text = mock_text(model, false);
+    let duration = duration_of(&text);
+    let words: Vec<Value> = words_of(&text)
+        .map(|(word, start, end)| {
+            let mut word = json!({
+                "word": word.trim_end_matches('.').to_lowercase(),
+                "punctuated_word": word,
+                "start": start,
+                "end": end,
+                "confidence": 0.98,
+            });
+            if enabled("diarize") {
+                word["speaker"] = json!(0);
+            }
+            word
+        })
+        .collect();
+
+    let mut channel = json!({
+        "alternatives": [{"transcript": text, "confidence": 0.98, "words": words}]
+    });
+    if enabled("detect_language") {
+        channel["detected_language"] = json!("en");
+    }
+    let mut results = json!({"channels": [channel]});
+    if enabled("utterances") {
+        results["utterances"] = json!([{
+            "start": 0.0, "end": duration, "transcript": text, "confidence": 0.98
+        }]);
+    }
+    Reply::json(json!({"metadata": {"duration": duration}, "results": results}))
+}
+
+/// Splits a multipart request into its fields
+fn multipart(request: &Request) -> Result<Vec<Part>, String> {
+    let content_type = request.header("content-type").unwrap_or_default();
+    let boundary = content_type
+        .strip_prefix("multipart/form-data")
+        .and_then(|params| {
+            params
+                .split(';')
+                .find_map(|param| param.trim().strip_prefix("boundary="))
+        })
+        .map(|boundary| boundary.trim_matches('"'))
+        .ok_or_else(|| "Expected a multipart/form-data body".to_string())?;
+
+    let delimiter = format!("--{boundary}");
+    let mut parts = Vec::new();
+    for section in split_bytes(&request.body, delimiter.as_bytes()).into_iter().skip(1) {
+        if section.starts_with(b"--") {
+            break;
+        }
+        let section = section.strip_prefix(b"\r\n").unwrap_or(section);
+        let header_end = find_bytes(section, b"\r\n\r\n").ok_or("Malformed multipart body")?;
+        let headers = String::from_utf8_lossy(&section[..header_end]);
+        let data = &section[header_end + 4..];
+        let data = data.strip_suffix(b"\r\n").unwrap_or(data);
+
+        let disposition = headers
+            .lines()
+            .find(|line| line.to_ascii_lowercase().starts_with("content-disposition"))
+            .ok_or("Multipart field without Content-Disposition")?;
+        let param = |key: &str| {
+            disposition.split(';').find_map(|param| {
+                param
+                    .trim()
+                    .strip_prefix(key)
+                    .and_then(|value| value.strip_prefix('='))
+                    .map(|value| value.trim_matches('"').to_string())
+            })
+        };
+        parts.push(Part {
+            name: param("name").ok_or("Multipart field without a name")?,
+            file_name: param("filename"),
+            data: data.to_vec(),
+        });
+    }
+    Ok(parts)
+}
+
+/// Splits bytes at every occurrence of `delimiter`
+fn split_bytes<'a>(bytes: &'a [u8], delimiter: &[u8]) -> Vec<&'a [u8]> {
+    let mut sections = Vec::new();
+    let mut rest = bytes;
+    while let Some(position) = find_bytes(rest, delimiter) {
+        sections.push(&rest[..position]);
+        rest = &rest[position + delimiter.len()..];
+    }
+    sections.push(rest);
+    sections
+}
+
+/// Finds the first occurrence of `needle` in `haystack`
+fn find_bytes(haystack: &[u8], needle: &[u8]) -> Option<usize> {
+    haystack
+        .windows(needle.len())
+        .position(|window| window == needle)
+}
+
+/// The transcript every mock endpoint returns
+fn mock_text(model: &str, translate: bool) -> String {
+    let task = if translate { "translation" } else { "transcription" };
+    format!("This is a mock {task} by {model}.")
+}
+
+/// Duration of a mock transcript when spoken
+fn duration_of(text: &str) -> f64 {
+    text.split_whitespace().count() as f64 * WORD_SECS
+}
+
+/// Words of a mock transcript with their start and end times
+fn words_of(text: &str) -> impl Iterator<Item = (&str, f64, f64)> {
+    text.split_whitespace().enumerate().map(|(index, word)| {
+        let start = index as f64 * WORD_SECS;
+        (word, start, start + WORD_SECS)
+    })
+}
+
+/// Formats seconds as a subtitle timestamp with the given decimal separator
+fn timestamp(seconds: f64, separator: char) -> String {
+    let millis = (seconds * 1000.0).round() as u64;
+    format!(
+        "{:02}:{:02}:{:02}{separator}{:03}",
+        millis / 3_600_000,
+        millis / 60_000 % 60,
+        millis / 1000 % 60,
+        millis % 1000
+    )
+}
+
+/// Reason phrase for the status codes the mock returns
+fn reason_phrase(status: u16) -> &'static str {
+    match status {
+        200 => "OK",
+        400 => "Bad Request",
+        401 => "Unauthorized",
+        404 => "Not Found",
+        405 => "Method Not Allowed",
+        413 => "Payload Too Large",
+        415 => "Unsupported Media Type",
+        422 => "Unprocessable Entity",
+        429 => "Too Many Requests",
+        _ => "Internal Server Error",
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::file::{NetworkConfig, ProvidersConfig};
+    use crate::history::HistoryManager;
+    use crate::recording::encode::write_wav;
+    use crate::transcription::discovery::{self, ModelCache};
+    use crate::transcription::{
+        api, Cleanup, ProgressReporter, ProviderRegistry, TranscriptionConfig,
+        TranscriptionStatus,
+    };
+
+    /// Writes a short silent recording and returns its path
+    fn recording(name: &str) -> std::path::PathBuf {
+        let path = std::env::temp_dir().join(format!("ostt_mock_{}_{name}.wav", std::process::id()));
+        write_wav(&[0i16; 1600], 16000, &path).unwrap();
+        path
+    }
+
+    /// Configures a model of the registry to use the mock server
+    fn config(server: &MockServer, model_id: &str, api_key: &str) -> TranscriptionConfig {
+        let mut providers = ProvidersConfig::default();
+        providers.openai.base_url = server.base_url("openai");
+        providers.groq.base_url = server.base_url("groq");
+        providers.deepinfra.base_url = server.base_url("deepinfra");
+        providers.deepgram.base_url = server.base_url("deepgram");
+        providers.deepgram.diarize = true;
+        providers.assemblyai.base_url = server.base_url("assemblyai");
+        providers.assemblyai.speaker_labels = true;
+        let network = NetworkConfig {
+            max_retries: 0,
+            ..NetworkConfig::default()
+        };
+        let model = ProviderRegistry::from_config(&providers)
+            .find_model(model_id)
+            .unwrap();
+        TranscriptionConfig::new(model, api_key.to_string(), Vec::new(), providers, network)
+    }
+
+    #[tokio::test]
+    async fn transcribes_with_every_provider_and_saves_to_history() {
+        let server = MockServer::start("127.0.0.1:0").await.unwrap();
+        let path = recording("providers");
+
+        let response = api::transcribe(&config(&server, "whisper", "key"), &path).await.unwrap();
+        assert_eq!(response.text, "This is a mock transcription by whisper-1.");
+        assert_eq!(response.words.len(), 7);
+        assert_eq!(response.language.as_deref(), Some("en"));
+
+        let (progress, status) = ProgressReporter::channel();
+        let groq = config(&server, "groq-whisper-large-v3", "key").with_progress(progress);
+        let response = api::transcribe(&groq, &path).await.unwrap();
+        assert!(!response.segments.is_empty());
+        // The streamed upload was sent in full before the response came back
+        assert_eq!(status.borrow().provider(), Some("Groq"));
+        let response = api::transcribe(&config(&server, "deepinfra-whisper-base", "key"), &path)
+            .await
+            .unwrap();
+        assert_eq!(response.text, "This is a mock transcription by openai/whisper-base.");
+        let (progress, status) = ProgressReporter::channel();
+        let nova = config(&server, "nova-3", "key").with_progress(progress);
+        let response = api::transcribe(&nova, &path).await.unwrap();
+        assert_eq!(response.text, "Speaker 1: This is a mock transcription by nova-3.");
+        assert_eq!(
+            *status.borrow(),
+            TranscriptionStatus::Parsing {
+                provider: "Deepgram".to_string()
+            }
+        );
+
+        let (progress, status) = ProgressReporter::channel();
+        let universal = config(&server, "assemblyai-universal", "key").with_progress(progress);
+        let response = api::transcribe(&universal, &path).await.unwrap();
+        assert_eq!(response.text, "Speaker 1: This is a mock transcription by universal.");
+        assert_eq!(response.words[1].start, 0.3);
+        assert_eq!(status.borrow().provider(), Some("AssemblyAI"));
+
+        let data_dir = std::env::temp_dir().join(format!("ostt_mock_history_{}", std::process::id()));
+        std::fs::create_dir_all(&data_dir).unwrap();
+        let mut history = HistoryManager::new(&data_dir).unwrap();
+        history.save_transcription(&response, "nova-3", false, None).unwrap();
+        let entries = history.get_all_transcriptions().unwrap();
+        assert_eq!(entries[0].text, response.text);
+        assert_eq!(entries[0].model.as_deref(), Some("nova-3"));
+
+        std::fs::remove_dir_all(&data_dir).ok();
+        std::fs::remove_file(&path).ok();
+    }
+
+    #[tokio::test]
+    async fn discovers_models_from_model_lists() {
+        let server = MockServer::start("127.0.0.1:0").await.unwrap();
+        let base = config(&server, "whisper", "key");
+        let mut registry = ProviderRegistry::from_config(&base.providers);
+
+        let mut cache = ModelCache::default();
+        for provider in registry.providers() {
+            let models =
+                discovery::fetch_models(provider.as_ref(), &base.providers, &base.network, "key")
+                    .await
+                    .unwrap();
+            cache.set(provider.id(), models, chrono::Utc::now());
+        }
+        let discovered: Vec<String> =
+            cache.models(&registry).iter().map(|m| m.id().to_string()).collect();
+        assert_eq!(
+            discovered,
+            [
+                "nova-3-medical",
+                "deepinfra-whisper-large-v3-turbo",
+                "groq-distil-whisper-large-v3-en",
+            ]
+        );
+        registry.add_discovered(cache.models(&registry));
+
+        let path = recording("discovered");
+        let model = registry.find_model("groq-distil-whisper-large-v3-en").unwrap();
+        let config = TranscriptionConfig::new(
+            model,
+            "key".to_string(),
+            Vec::new(),
+            base.providers.clone(),
+            base.network.clone(),
+        );
+        let response = api::transcribe(&config, &path).await.unwrap();
+        assert_eq!(response.text, "This is a mock transcription by distil-whisper-large-v3-en.");
+
+        std::fs::remove_file(&path).ok();
+    }
+
+    #[tokio::test]
+    async fn translates_with_translation_models_only() {
+        let server = MockServer::start("127.0.0.1:0").await.unwrap();
+        let path = recording("translations");
+
+        // The mock answers requests to /audio/translations with a translation
+        let whisper = config(&server, "whisper", "key").with_translate(true);
+        let response = api::transcribe(&whisper, &path).await.unwrap();
+        assert_eq!(response.text, "This is a mock translation by whisper-1.");
+        let groq = config(&server, "groq-whisper-large-v3", "key").with_translate(true);
+        let response = api::transcribe(&groq, &path).await.unwrap();
+        assert_eq!(response.text, "This is a mock translation by whisper-large-v3.");
+
+        for model_id in ["gpt-4o-transcribe", "nova-3"] {
+            let config = config(&server, model_id, "key").with_translate(true);
+            let error = api::transcribe(&config, &path).await.unwrap_err();
+            assert!(error.to_string().contains("cannot translate to English"), "{error}");
+        }
+
+        std::fs::remove_file(&path).ok();
+    }
+
+    #[tokio::test]
+    async fn rejects_oversized_bodies_unread() {
+        let server = MockServer::start("127.0.0.1:0").await.unwrap();
+        let mut stream = TcpStream::connect(server.address).await.unwrap();
+        let request = "POST /openai/v1/audio/transcriptions HTTP/1.1\r\nHost: localhost\r\n\
+                       Content-Length: 1000000000000\r\n\r\n";
+        stream.write_all(request.as_bytes()).await.unwrap();
+
+        let mut response = String::new();
+        stream.read_to_string(&mut response).await.unwrap();
+        assert!(response.starts_with("HTTP/1.1 413"), "{response}");
+    }
+
+    #[tokio::test]
+    async fn maps_simulated_failures_to_errors() {
+        let server = MockServer::start("127.0.0.1:0").await.unwrap();
+        let path = recording("failures");
+
+        let error = api::transcribe(&config(&server, "whisper", "invalid"), &path)
+            .await
+            .unwrap_err();
+        assert!(error.to_string().contains("API key is invalid"));
+        let error = api::transcribe(&config(&server, "nova-3", "rate-limited"), &path)
+            .await
+            .unwrap_err();
+        assert!(error.to_string().contains("rate limit"));
+        let error = api::transcribe(&config(&server, "groq-whisper-large-v3", "server-error"), &path)
+            .await
+            .unwrap_err();
+        assert!(error.to_string().contains("server is experiencing issues"));
+
+        std::fs::remove_file(&path).ok();
+    }
+
+    #[tokio::test]
+    async fn deletes_assemblyai_job_when_cancelled() {
+        let server = MockServer::start("127.0.0.1:0").await.unwrap();
+        let path = recording("cancelled");
+        let jobs_url = format!("{}/transcript", server.base_url("assemblyai").unwrap());
+        let jobs = || async {
+            let body: Value = reqwest::Client::new()
+                .get(&jobs_url)
+                .header("Authorization", "key")
+                .send()
+                .await
+                .unwrap()
+                .json()
+                .await
+                .unwrap();
+            body["transcripts"].as_array().unwrap().len()
+        };
+
+        let cleanup = Cleanup::new();
+        let config = config(&server, "assemblyai-nano", "slow").with_cleanup(cleanup.clone());
+        let handle = tokio::spawn({
+            let path = path.clone();
+            async move { api::transcribe(&config, &path).await }
+        });
+        while jobs().await == 0 {
+            tokio::time::sleep(std::time::Duration::from_millis(20)).await;
+        }
+        handle.abort();
+        assert!(handle.await.unwrap_err().is_cancelled());
+        assert_eq!(jobs().await, 1);
+        cleanup.run().await;
+        assert_eq!(jobs().await, 0);
+
+        std::fs::remove_file(&path).ok();
+    }
+}
@@ -1,14 +1,34 @@
 //! Transcription service for audio-to-text conversion.
 //!
 //! This module provides support for multiple transcription providers and models through a
-//! unified interface. Each provider has its own API endpoint and authentication method.
+//! unified interface. Each provider implements the `Provider` trait and is looked up through
+//! the `ProviderRegistry`.
 
 pub mod animation;
 pub mod api;
+pub mod chunking;
+pub mod cleanup;
+pub mod discovery;
+pub mod fallback;
+pub mod http;
+pub mod language;
+pub mod live;
+#[cfg(any(test, feature = "mock-server"))]
+pub mod mock;
 pub mod model;
+pub mod progress;
 pub mod provider;
+pub mod registry;
+pub mod response;
 
 pub use animation::TranscriptionAnimation;
-pub use api::{transcribe, TranscriptionConfig, TranscriptionResponse};
-pub use model::TranscriptionModel;
-pub use provider::TranscriptionProvider;
+pub use api::{transcribe, TranscriptionConfig};
+pub use chunking::AudioChunk;
+pub use cleanup::Cleanup;
+pub use live::{LiveSession, LiveTranscript};
+pub use fallback::{transcribe_with_fallback, Attempt, FallbackTranscript};
+pub use model::{KeywordSupport, ModelCapabilities, TranscriptionModel};
+pub use progress::{ProgressReporter, TranscriptionStatus, UploadProgress};
+pub use provider::{AudioUpload, Provider, ProviderCapabilities};
+pub use registry::ProviderRegistry;
+pub use response::{Segment, TranscriptionResponse, Word};
@@ -1,155 +1,279 @@
 //! Transcription model definitions and metadata.
 //!
-//! Defines supported transcription models (e.g., Whisper) with their associated metadata,
-//! providers, API endpoints, and model names.
+//! A model is plain metadata: its identifier, the provider that serves it, the name sent to
+//! the provider's API, a description for the UI and its capabilities (what the API accepts
+//! and returns, and what it costs). Providers declare their models through
+//! `Provider::models`, and the `ProviderRegistry` collects them. Requests are checked
+//! against the capabilities before upload, and `ostt auth` describes models with them.
 
 use serde::{Deserialize, Serialize};
 
-use super::provider::TranscriptionProvider;
-
-/// Represents a supported transcription model
-#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
-pub enum TranscriptionModel {
-    /// OpenAI GPT-4o Transcribe model (latest, best accuracy)
-    Gpt4oTranscribe,
-    /// OpenAI GPT-4o Mini Transcribe model (faster, lighter)
-    Gpt4oMiniTranscribe,
-    /// OpenAI Whisper model (legacy)
-    Whisper,
-    /// Deepgram Nova 3 model (latest, fastest)
-    DeepgramNova3,
-    /// Deepgram Nova 2 model (previous generation)
-    DeepgramNova2,
-    /// DeepInfra Whisper Large V3 model
-    DeepInfraWhisperLargeV3,
-    /// DeepInfra Whisper Base model
-    DeepInfraWhisperBase,
-    /// Groq Whisper Large V3 model
-    GroqWhisperLargeV3,
-    /// Groq Whisper Large V3 Turbo model (faster)
-    GroqWhisperLargeV3Turbo,
+use super::language;
+use crate::recording::AudioFormat;
+
+/// How a model takes the keywords from `ostt keywords`.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum KeywordSupport {
+    /// Keywords are not sent
+    #[default]
+    None,
+    /// Keywords are appended to the prompt
+    Prompt,
+    /// Keywords are boosted (Deepgram's `keywords` parameter)
+    Keywords,
+    /// Keywords are key terms (Deepgram's `keyterm` parameter)
+    Keyterms,
 }
 
-impl TranscriptionModel {
-    /// Returns the provider for this model
-    pub fn provider(&self) -> TranscriptionProvider {
+impl KeywordSupport {
+    /// Returns the name shown in model descriptions
+    pub fn as_str(self) -> &'static str {
         match self {
-            TranscriptionModel::Gpt4oTranscribe
-            | TranscriptionModel::Gpt4oMiniTranscribe
-            | TranscriptionModel::Whisper => TranscriptionProvider::OpenAI,
-            TranscriptionModel::DeepgramNova3 | TranscriptionModel::DeepgramNova2 => {
-                TranscriptionProvider::Deepgram
-            }
-            TranscriptionModel::DeepInfraWhisperLargeV3
-            | TranscriptionModel::DeepInfraWhisperBase => TranscriptionProvider::DeepInfra,
-            TranscriptionModel::GroqWhisperLargeV3
-            | TranscriptionModel::GroqWhisperLargeV3Turbo => TranscriptionProvider::Groq,
+            KeywordSupport::None => "none",
+            KeywordSupport::Prompt => "prompt",
+            KeywordSupport::Keywords => "keywords",
+            KeywordSupport::Keyterms => "keyterms",
         }
     }
+}
 
-    /// Returns the model identifier as a string
-    pub fn id(&self) -> &'static str {
-        match self {
-            TranscriptionModel::Gpt4oTranscribe => "gpt-4o-transcribe",
-            TranscriptionModel::Gpt4oMiniTranscribe => "gpt-4o-mini-transcribe",
-            TranscriptionModel::Whisper => "whisper",
-            TranscriptionModel::DeepgramNova3 => "nova-3",
-            TranscriptionModel::DeepgramNova2 => "nova-2",
-            TranscriptionModel::DeepInfraWhisperLargeV3 => "deepinfra-whisper-large-v3",
-            TranscriptionModel::DeepInfraWhisperBase => "deepinfra-whisper-base",
-            TranscriptionModel::GroqWhisperLargeV3 => "groq-whisper-large-v3",
-            TranscriptionModel::GroqWhisperLargeV3Turbo => "groq-whisper-large-v3-turbo",
-        }
+/// What a model's API accepts and returns.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ModelCapabilities {
+    /// Whether the API takes a free-text prompt
+    pub prompt: bool,
+    /// How keywords are sent
+    pub keywords: KeywordSupport,
+    /// Largest audio file the API accepts, in bytes (`None` if effectively unlimited)
+    pub max_upload_bytes: Option<u64>,
+    /// Longest audio the API accepts, in seconds (`None` if only the size is limited)
+    pub max_duration_secs: Option<u64>,
+    /// Audio formats the API accepts, in order of preference (empty if it accepts all)
+    pub formats: Vec<AudioFormat>,
+    /// Whether the model can translate speech to English
+    pub translation: bool,
+    /// Whether the response includes segment or word timestamps
+    pub timestamps: bool,
+    /// Spoken languages as ISO 639-1 codes (empty if the model is multilingual)
+    pub languages: Vec<String>,
+    /// List price in USD per minute of audio (`None` if unknown)
+    pub price_per_minute: Option<f64>,
+}
+
+impl ModelCapabilities {
+    /// Returns whether the model transcribes the given language (a code like "pt-BR")
+    pub fn supports_language(&self, code: &str) -> bool {
+        let code = language::base_code(code);
+        self.languages.is_empty()
+            || self
+                .languages
+                .iter()
+                .any(|supported| language::base_code(supported).eq_ignore_ascii_case(code))
     }
 
-    /// Returns a human-readable description of the model
-    pub fn description(&self) -> &'static str {
-        match self {
-            TranscriptionModel::Gpt4oTranscribe => "GPT-4o Transcribe (latest, best accuracy)",
-            TranscriptionModel::Gpt4oMiniTranscribe => "GPT-4o Mini Transcribe (faster, lighter)",
-            TranscriptionModel::Whisper => "Whisper (legacy)",
-            TranscriptionModel::DeepgramNova3 => "Nova 3 (latest, fastest)",
-            TranscriptionModel::DeepgramNova2 => "Nova 2 (previous generation)",
-            TranscriptionModel::DeepInfraWhisperLargeV3 => "Whisper Large V3 (best accuracy)",
-            TranscriptionModel::DeepInfraWhisperBase => "Whisper Base (fast, lightweight)",
-            TranscriptionModel::GroqWhisperLargeV3 => "Whisper Large V3 (high accuracy)",
-            TranscriptionModel::GroqWhisperLargeV3Turbo => "Whisper Large V3 Turbo (fastest)",
+    /// Returns a one-line summary, e.g. "translation · timestamps · ≤25 MB · $0.0060/min"
+    pub fn summary(&self) -> String {
+        let mut parts = Vec::new();
+        if !self.languages.is_empty() {
+            parts.push(self.languages.join("/"));
+        }
+        if self.translation {
+            parts.push("translation".to_string());
+        }
+        if self.timestamps {
+            parts.push("timestamps".to_string());
+        }
+        match (self.keywords, self.prompt) {
+            (KeywordSupport::None, false) => parts.push("no keywords".to_string()),
+            (KeywordSupport::None | KeywordSupport::Prompt, _) => parts.push("prompt".to_string()),
+            (keywords, _) => parts.push(keywords.as_str().to_string()),
+        }
+        if let Some(bytes) = self.max_upload_bytes {
+            parts.push(format!("≤{} MB", bytes / (1024 * 1024)));
+        }
+        if let Some(secs) = self.max_duration_secs {
+            parts.push(format!("≤{} min", secs / 60));
+        }
+        if let [format] = self.formats.as_slice() {
+            parts.push(format!("{format} only"));
+        }
+        match self.price_per_minute {
+            Some(0.0) => parts.push("free".to_string()),
+            Some(price) => parts.push(format!("${price:.4}/min")),
+            None => {}
         }
+        parts.join(" · ")
     }
+}
 
-    /// Returns the API endpoint for this model
-    pub fn endpoint(&self) -> &'static str {
-        match self {
-            TranscriptionModel::Gpt4oTranscribe
-            | TranscriptionModel::Gpt4oMiniTranscribe
-            | TranscriptionModel::Whisper => "https://api.openai.com/v1/audio/transcriptions",
-            TranscriptionModel::DeepgramNova3 | TranscriptionModel::DeepgramNova2 => {
-                "https://api.deepgram.com/v1/listen"
-            }
-            TranscriptionModel::DeepInfraWhisperLargeV3
-            | TranscriptionModel::DeepInfraWhisperBase => "https://api.deepinfra.com/v1/inference",
-            TranscriptionModel::GroqWhisperLargeV3
-            | TranscriptionModel::GroqWhisperLargeV3Turbo => "https://api.groq.com/openai/v1/audio/transcriptions",
+/// Represents a transcription model offered by a provider
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TranscriptionModel {
+    /// Unique identifier stored by `ostt auth` (e.g. "nova-3")
+    id: String,
+    /// Identifier of the provider serving this model
+    provider_id: String,
+    /// Model name sent to the provider's API
+    api_name: String,
+    /// Human-readable description
+    description: String,
+    /// What the model's API accepts and returns
+    #[serde(flatten)]
+    capabilities: ModelCapabilities,
+}
+
+impl TranscriptionModel {
+    /// Creates a new model description
+    pub fn new(
+        id: impl Into<String>,
+        provider_id: impl Into<String>,
+        api_name: impl Into<String>,
+        description: impl Into<String>,
+    ) -> Self {
+        Self {
+            id: id.into(),
+            provider_id: provider_id.into(),
+            api_name: api_name.into(),
+            description: description.into(),
+            capabilities: ModelCapabilities::default(),
         }
     }
 
+    /// Replaces all capabilities at once
+    pub fn with_capabilities(mut self, capabilities: ModelCapabilities) -> Self {
+        self.capabilities = capabilities;
+        self
+    }
+
+    /// Marks the model as taking a free-text prompt
+    pub fn with_prompt(mut self) -> Self {
+        self.capabilities.prompt = true;
+        self
+    }
+
+    /// Sets how keywords are sent
+    pub fn with_keywords(mut self, keywords: KeywordSupport) -> Self {
+        self.capabilities.keywords = keywords;
+        self
+    }
+
+    /// Sets the largest audio file the API accepts; longer recordings are split into chunks
+    pub fn with_max_upload_bytes(mut self, bytes: u64) -> Self {
+        self.capabilities.max_upload_bytes = Some(bytes);
+        self
+    }
+
+    /// Sets the longest audio the API accepts; longer recordings are split into chunks
+    pub fn with_max_duration_secs(mut self, secs: u64) -> Self {
+        self.capabilities.max_duration_secs = Some(secs);
+        self
+    }
+
+    /// Marks the model as able to translate speech to English
+    pub fn with_translation(mut self) -> Self {
+        self.capabilities.translation = true;
+        self
+    }
+
+    /// Marks the response as including segment or word timestamps
+    pub fn with_timestamps(mut self) -> Self {
+        self.capabilities.timestamps = true;
+        self
+    }
+
+    /// Restricts the spoken languages to the given ISO 639-1 codes
+    pub fn with_languages(mut self, languages: &[&str]) -> Self {
+        self.capabilities.languages = languages.iter().map(|code| code.to_string()).collect();
+        self
+    }
+
+    /// Restricts the audio formats the API accepts; the first one is used for transcoding
+    pub fn with_formats(mut self, formats: &[AudioFormat]) -> Self {
+        self.capabilities.formats = formats.to_vec();
+        self
+    }
+
+    /// Sets the list price in USD per minute of audio
+    pub fn with_price_per_minute(mut self, price: f64) -> Self {
+        self.capabilities.price_per_minute = Some(price);
+        self
+    }
+
+    /// Returns the model identifier as a string
+    pub fn id(&self) -> &str {
+        &self.id
+    }
+
+    /// Returns the identifier of the provider serving this model
+    pub fn provider_id(&self) -> &str {
+        &self.provider_id
+    }
+
+    /// Returns a human-readable description of the model
+    pub fn description(&self) -> &str {
+        &self.description
+    }
+
     /// Returns the model name to send to the API
-    pub fn api_model_name(&self) -> &'static str {
-        match self {
-            TranscriptionModel::Gpt4oTranscribe => "gpt-4o-transcribe",
-            TranscriptionModel::Gpt4oMiniTranscribe => "gpt-4o-mini-transcribe",
-            TranscriptionModel::Whisper => "whisper-1",
-            TranscriptionModel::DeepgramNova3 => "nova-3",
-            TranscriptionModel::DeepgramNova2 => "nova-2",
-            TranscriptionModel::DeepInfraWhisperLargeV3 => "openai/whisper-large-v3",
-            TranscriptionModel::DeepInfraWhisperBase => "openai/whisper-base",
-            TranscriptionModel::GroqWhisperLargeV3 => "whisper-large-v3",
-            TranscriptionModel::GroqWhisperLargeV3Turbo => "whisper-large-v3-turbo",
-        }
+    pub fn api_model_name(&self) -> &str {
+        &self.api_name
     }
 
-    /// Parses a model ID string into a TranscriptionModel
-    pub fn from_id(id: &str) -> Option<Self> {
-        match id {
-            "gpt-4o-transcribe" => Some(TranscriptionModel::Gpt4oTranscribe),
-            "gpt-4o-mini-transcribe" => Some(TranscriptionModel::Gpt4oMiniTranscribe),
-            "whisper" => Some(TranscriptionModel::Whisper),
-            "nova-3" => Some(TranscriptionModel::DeepgramNova3),
-            "nova-2" => Some(TranscriptionModel::DeepgramNova2),
-            "deepinfra-whisper-large-v3" => Some(TranscriptionModel::DeepInfraWhisperLargeV3),
-            "deepinfra-whisper-base" => Some(TranscriptionModel::DeepInfraWhisperBase),
-            "groq-whisper-large-v3" => Some(TranscriptionModel::GroqWhisperLargeV3),
-            "groq-whisper-large-v3-turbo" => Some(TranscriptionModel::GroqWhisperLargeV3Turbo),
-            _ => None,
-        }
+    /// Returns what the model's API accepts and returns
+    pub fn capabilities(&self) -> &ModelCapabilities {
+        &self.capabilities
+    }
+
+    /// Returns the largest audio file the API accepts, in bytes
+    pub fn max_upload_bytes(&self) -> Option<u64> {
+        self.capabilities.max_upload_bytes
+    }
+
+    /// Returns whether the model can translate speech to English
+    pub fn supports_translation(&self) -> bool {
+        self.capabilities.translation
+    }
+
+    /// Returns whether the API accepts audio in the given format
+    pub fn accepts_format(&self, format: AudioFormat) -> bool {
+        self.capabilities.formats.is_empty() || self.capabilities.formats.contains(&format)
+    }
+
+    /// Returns the list price in USD per minute of audio, if known
+    pub fn price_per_minute(&self) -> Option<f64> {
+        self.capabilities.price_per_minute
+    }
+
+    /// Returns the format to transcode to when the recording's format isn't accepted
+    pub fn preferred_format(&self) -> AudioFormat {
+        self.capabilities.formats.first().copied().unwrap_or(AudioFormat::Mp3)
     }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn describes_and_checks_capabilities() {
+        let model = TranscriptionModel::new("nova-3", "deepgram", "nova-3", "Nova 3")
+            .with_keywords(KeywordSupport::Keyterms)
+            .with_max_upload_bytes(25 * 1024 * 1024)
+            .with_timestamps()
+            .with_price_per_minute(0.0043);
+        assert_eq!(
+            model.capabilities().summary(),
+            "timestamps · keyterms · ≤25 MB · $0.0043/min"
+        );
 
-    /// Returns all available models
-    pub fn all() -> &'static [Self] {
-        &[
-            TranscriptionModel::Gpt4oTranscribe,
-            TranscriptionModel::Gpt4oMiniTranscribe,
-            TranscriptionModel::Whisper,
-            TranscriptionModel::DeepgramNova3,
-            TranscriptionModel::DeepgramNova2,
-            TranscriptionModel::DeepInfraWhisperLargeV3,
-            TranscriptionModel::DeepInfraWhisperBase,
-            TranscriptionModel::GroqWhisperLargeV3,
-            TranscriptionModel::GroqWhisperLargeV3Turbo,
-        ]
-    }
-
-    /// Returns all available model IDs
-    pub fn available_ids() -> Vec<&'static str> {
-        Self::all().iter().map(|m| m.id()).collect()
-    }
-
-    /// Returns all models for a given provider
-    pub fn models_for_provider(provider: &TranscriptionProvider) -> Vec<TranscriptionModel> {
-        Self::all()
-            .iter()
-            .filter(|m| m.provider() == *provider)
-            .cloned()
-            .collect()
+        let english = ModelCapabilities {
+            languages: vec!["en".to_string()],
+            ..ModelCapabilities::default()
+        };
+        assert!(english.supports_language("en-US"));
+        assert!(!english.supports_language("de"));
+        assert!(ModelCapabilities::default().supports_language("de"));
     }
 }
@@ -0,0 +1,215 @@
+//! Transcription progress reporting.
+//!
+//! The transcription runs on a background task while the UI thread draws the
+//! `TranscriptionAnimation`. Providers and the shared request flow report what they are
+//! doing (encoding, uploading, waiting for the provider, retrying a rate-limited request)
+//! through a `ProgressReporter`, and the UI picks the latest status up from the paired
+//! watch receiver. Upload bodies are streamed in small pieces so that the share of the
+//! audio sent so far can be reported.
+
+use futures_util::StreamExt;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::watch;
+
+/// Size of the pieces an upload body is streamed in
+const UPLOAD_CHUNK_BYTES: usize = 64 * 1024;
+
+/// Current state of a running transcription, as shown to the user.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub enum TranscriptionStatus {
+    /// Request in flight, nothing special to report
+    #[default]
+    Transcribing,
+    /// The audio is being encoded or transcoded for upload
+    Encoding,
+    /// The audio is being sent to the provider
+    Uploading {
+        /// Name of the provider
+        provider: String,
+        /// Bytes sent so far
+        sent: u64,
+        /// Size of the upload
+        total: u64,
+    },
+    /// The upload is complete and the provider is transcribing
+    Waiting {
+        /// Name of the provider
+        provider: String,
+    },
+    /// The provider is transcribing the upload as a job, and the job is being polled
+    Polling {
+        /// Name of the provider
+        provider: String,
+        /// State the provider reported for the job (e.g. "queued")
+        state: String,
+    },
+    /// The provider answered and its response is being read
+    Parsing {
+        /// Name of the provider
+        provider: String,
+    },
+    /// A request failed and will be retried after `delay`
+    Retrying {
+        /// Retry number (1-based)
+        attempt: u32,
+        /// Maximum number of retries
+        max_retries: u32,
+        /// Time until the retry is sent
+        delay: Duration,
+        /// Short description of why the request failed
+        reason: String,
+    },
+    /// A long recording is being transcribed in chunks
+    TranscribingChunks {
+        /// Chunks transcribed so far
+        completed: usize,
+        /// Number of chunks
+        total: usize,
+    },
+    /// A model failed and the next model of the fallback chain is being tried
+    FallingBack {
+        /// ID of the model that failed
+        from: String,
+        /// ID of the model now being tried
+        to: String,
+    },
+}
+
+impl TranscriptionStatus {
+    /// Returns a one-line description for the animation screen, if there is anything to show.
+    pub fn message(&self) -> Option<String> {
+        match self {
+            TranscriptionStatus::Transcribing => None,
+            TranscriptionStatus::Encoding => Some("Encoding audio".to_string()),
+            TranscriptionStatus::Uploading {
+                provider,
+                sent,
+                total,
+            } => Some(format!(
+                "Uploading to {provider}: {}% of {:.1} MB",
+                (sent * 100).checked_div(*total).unwrap_or(100),
+                *total as f64 / (1024.0 * 1024.0)
+            )),
+            TranscriptionStatus::Waiting { provider } => {
+                Some(format!("Waiting for {provider} to transcribe"))
+            }
+            TranscriptionStatus::Polling { provider, state } => {
+                Some(format!("Waiting for {provider} to transcribe (job {state})"))
+            }
+            TranscriptionStatus::Parsing { provider } => {
+                Some(format!("Reading the response from {provider}"))
+            }
+            TranscriptionStatus::Retrying {
+                attempt,
+                max_retries,
+                delay,
+                reason,
+            } => Some(format!(
+                "{reason} - retrying in {:.1}s (attempt {attempt}/{max_retries})",
+                delay.as_secs_f32()
+            )),
+            TranscriptionStatus::TranscribingChunks { completed, total } => Some(format!(
+                "Long recording: {completed}/{total} parts transcribed"
+            )),
+            TranscriptionStatus::FallingBack { from, to } => {
+                Some(format!("{from} failed - trying {to}"))
+            }
+        }
+    }
+
+    /// Returns the name of the provider the status is about, if it names one
+    pub fn provider(&self) -> Option<&str> {
+        match self {
+            TranscriptionStatus::Uploading { provider, .. }
+            | TranscriptionStatus::Waiting { provider }
+            | TranscriptionStatus::Polling { provider, .. }
+            | TranscriptionStatus::Parsing { provider } => Some(provider),
+            _ => None,
+        }
+    }
+}
+
+/// Sending half of a progress channel, cheap to clone into transcription tasks.
+#[derive(Debug, Clone)]
+pub struct ProgressReporter {
+    sender: Arc<watch::Sender<TranscriptionStatus>>,
+}
+
+impl ProgressReporter {
+    /// Creates a reporter and the receiver the UI reads the status from
+    pub fn channel() -> (Self, watch::Receiver<TranscriptionStatus>) {
+        let (sender, receiver) = watch::channel(TranscriptionStatus::default());
+        (
+            Self {
+                sender: Arc::new(sender),
+            },
+            receiver,
+        )
+    }
+
+    /// Publishes a new status. Silently ignored if the UI is no longer listening.
+    pub fn report(&self, status: TranscriptionStatus) {
+        let _ = self.sender.send(status);
+    }
+}
+
+/// Reports how much of an upload body has been sent to a provider.
+#[derive(Debug, Clone)]
+pub struct UploadProgress {
+    reporter: ProgressReporter,
+    provider: String,
+}
+
+impl UploadProgress {
+    /// Creates an upload progress for a provider
+    pub fn new(reporter: ProgressReporter, provider: &str) -> Self {
+        Self {
+            reporter,
+            provider: provider.to_string(),
+        }
+    }
+
+    /// Turns the data into a streamed request body that reports each piece as it is sent.
+    ///
+    /// Once the last piece is handed to the connection, the provider is reported as
+    /// transcribing.
+    pub fn body(self, data: Vec<u8>) -> reqwest::Body {
+        let total = data.len() as u64;
+        let pieces: Vec<Vec<u8>> = data.chunks(UPLOAD_CHUNK_BYTES).map(<[u8]>::to_vec).collect();
+        let mut sent = 0;
+        let stream = futures_util::stream::iter(pieces).map(move |piece| {
+            sent += piece.len() as u64;
+            let provider = self.provider.clone();
+            self.reporter.report(if sent < total {
+                TranscriptionStatus::Uploading {
+                    provider,
+                    sent,
+                    total,
+                }
+            } else {
+                TranscriptionStatus::Waiting { provider }
+            });
+            Ok::<_, std::io::Error>(piece)
+        });
+        reqwest::Body::wrap_stream(stream)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn describes_upload_stages() {
+        let uploading = TranscriptionStatus::Uploading {
+            provider: "Groq".to_string(),
+            sent: 512 * 1024,
+            total: 2 * 1024 * 1024,
+        };
+        assert_eq!(uploading.message().as_deref(), Some("Uploading to Groq: 25% of 2.0 MB"));
+        assert_eq!(uploading.provider(), Some("Groq"));
+        assert_eq!(TranscriptionStatus::Transcribing.message(), None);
+        assert_eq!(TranscriptionStatus::Encoding.provider(), None);
+    }
+}
@@ -1,54 +1,204 @@
-//! Transcription provider definitions and methods.
+//! Transcription provider trait.
 //!
-//! Defines supported transcription service providers (e.g., OpenAI).
-//! Each provider has its own API endpoint and authentication method.
-
-use serde::{Deserialize, Serialize};
-
-/// Represents a supported transcription provider
-#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
-pub enum TranscriptionProvider {
-    OpenAI,
-    Deepgram,
-    DeepInfra,
-    Groq,
+//! Every transcription service (OpenAI, Deepgram, a self-hosted server, ...) implements the
+//! `Provider` trait: it declares its models and capabilities, builds the HTTP request for an
+//! upload, parses the response and maps HTTP errors to human-readable messages. Providers
+//! with a model list endpoint can also describe the models it returns. The shared
+//! request flow in `api::send` drives these hooks, so adding a provider only means writing
+//! one implementation and registering it in the `ProviderRegistry`.
+
+use async_trait::async_trait;
+use reqwest::StatusCode;
+use serde::Deserialize;
+use std::path::Path;
+
+use super::api::TranscriptionConfig;
+use super::model::TranscriptionModel;
+use super::progress::UploadProgress;
+use super::response::TranscriptionResponse;
+use crate::config::file::ProvidersConfig;
+
+/// What a provider supports, used by commands to adapt their flow.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ProviderCapabilities {
+    /// Whether an API key must be configured before transcribing
+    pub requires_api_key: bool,
+    /// Whether the provider runs locally and reads the uncompressed WAV recording directly
+    pub local: bool,
 }
 
-impl TranscriptionProvider {
-    pub fn id(&self) -> &'static str {
-        match self {
-            TranscriptionProvider::OpenAI => "openai",
-            TranscriptionProvider::Deepgram => "deepgram",
-            TranscriptionProvider::DeepInfra => "deepinfra",
-            TranscriptionProvider::Groq => "groq",
+impl Default for ProviderCapabilities {
+    fn default() -> Self {
+        Self {
+            requires_api_key: true,
+            local: false,
         }
     }
+}
+
+/// Audio payload handed to a provider when building an upload request.
+#[derive(Debug, Clone)]
+pub struct AudioUpload {
+    /// Encoded audio bytes
+    pub data: Vec<u8>,
+    /// File name of the recording (including extension)
+    pub file_name: String,
+    /// MIME type of the encoded audio
+    pub mime_type: &'static str,
+    /// Where upload progress is reported, if anyone is listening
+    pub progress: Option<UploadProgress>,
+}
+
+impl AudioUpload {
+    /// Size of the audio in bytes
+    pub fn len(&self) -> u64 {
+        self.data.len() as u64
+    }
+
+    /// Returns whether there is no audio
+    pub fn is_empty(&self) -> bool {
+        self.data.is_empty()
+    }
 
-    pub fn name(&self) -> &'static str {
-        match self {
-            TranscriptionProvider::OpenAI => "OpenAI",
-            TranscriptionProvider::Deepgram => "Deepgram",
-            TranscriptionProvider::DeepInfra => "DeepInfra",
-            TranscriptionProvider::Groq => "Groq",
+    /// Turns the audio into a request body, streamed with progress reports if anyone is
+    /// listening.
+    ///
+    /// Streamed bodies have no length of their own, so providers sending the audio as the
+    /// raw body set `Content-Length` from `len`.
+    pub fn into_body(self) -> reqwest::Body {
+        match self.progress {
+            Some(progress) => progress.body(self.data),
+            None => reqwest::Body::from(self.data),
         }
     }
+}
+
+/// A transcription service that can turn an audio file into text.
+#[async_trait]
+pub trait Provider: Send + Sync {
+    /// Stable identifier, also used as the credentials key (e.g. "openai")
+    fn id(&self) -> &str;
+
+    /// Human-readable name shown in the UI and in error messages
+    fn name(&self) -> &str;
+
+    /// Models offered by this provider
+    fn models(&self) -> Vec<TranscriptionModel>;
+
+    /// Capabilities of this provider
+    fn capabilities(&self) -> ProviderCapabilities {
+        ProviderCapabilities::default()
+    }
+
+    /// Builds the request for the provider's model list, if the provider has one.
+    ///
+    /// Used to discover models that were released after this version of ostt.
+    fn models_request(
+        &self,
+        _client: &reqwest::Client,
+        _providers: &ProvidersConfig,
+        _api_key: &str,
+    ) -> Option<reqwest::RequestBuilder> {
+        None
+    }
 
-    pub fn from_id(id: &str) -> Option<Self> {
-        match id {
-            "openai" => Some(TranscriptionProvider::OpenAI),
-            "deepgram" => Some(TranscriptionProvider::Deepgram),
-            "deepinfra" => Some(TranscriptionProvider::DeepInfra),
-            "groq" => Some(TranscriptionProvider::Groq),
-            _ => None,
+    /// Extracts the API model names from a model list response.
+    ///
+    /// The default implementation reads the OpenAI-style `{"data": [{"id": ...}]}` body.
+    ///
+    /// # Errors
+    /// - If the body cannot be parsed
+    fn parse_models(&self, body: &[u8]) -> anyhow::Result<Vec<String>> {
+        #[derive(Deserialize)]
+        struct ModelList {
+            data: Vec<ListedModel>,
         }
+        #[derive(Deserialize)]
+        struct ListedModel {
+            id: String,
+        }
+
+        let list: ModelList = serde_json::from_slice(body)
+            .map_err(|e| anyhow::anyhow!("Failed to parse {} model list: {e}", self.name()))?;
+        Ok(list.data.into_iter().map(|model| model.id).collect())
+    }
+
+    /// Describes a model found in the provider's model list.
+    ///
+    /// Returns `None` for models that don't transcribe speech (chat models, TTS, ...).
+    /// Built-in models are never passed here.
+    fn discovered_model(&self, _api_name: &str) -> Option<TranscriptionModel> {
+        None
+    }
+
+    /// Builds the upload request for the given audio.
+    ///
+    /// # Errors
+    /// - If the request cannot be constructed (invalid MIME type, missing configuration, etc.)
+    fn build_request(
+        &self,
+        client: &reqwest::Client,
+        config: &TranscriptionConfig,
+        audio: AudioUpload,
+    ) -> anyhow::Result<reqwest::RequestBuilder>;
+
+    /// Extracts the transcription from a successful response body.
+    ///
+    /// Providers fill in as much of the structured result (segments, words, confidence,
+    /// language) as their API returns. `config` is the configuration the request was built
+    /// from, for providers whose output formatting depends on it.
+    ///
+    /// # Errors
+    /// - If the body cannot be parsed or contains no transcript
+    fn parse_response(
+        &self,
+        config: &TranscriptionConfig,
+        body: &[u8],
+    ) -> anyhow::Result<TranscriptionResponse>;
+
+    /// Maps an unsuccessful HTTP status to a human-readable error.
+    fn map_http_error(&self, status: StatusCode, body: &str) -> anyhow::Error {
+        let name = self.name();
+        let message = match status.as_u16() {
+            401 => format!("{name} API key is invalid or expired. Please run 'ostt auth' to update your API key."),
+            403 => format!("You don't have permission to use {name}'s API. Check your API key and account status."),
+            413 => format!("Recording is too large for {name}'s upload limit."),
+            429 => format!("Too many requests to {name}. You've hit the API rate limit. Please wait and try again."),
+            500 | 502 | 503 | 504 => format!("{name} API server is experiencing issues. Please try again later."),
+            _ => format!("{name} API error (status {status}): {body}"),
+        };
+        anyhow::anyhow!(message)
+    }
+
+    /// Maps a network-level failure (connection, timeout) to a human-readable error.
+    fn map_network_error(&self, error: &reqwest::Error) -> anyhow::Error {
+        let name = self.name();
+        let message = if error.is_connect() {
+            format!("Failed to connect to {name} API server. Check your internet connection.")
+        } else if error.is_timeout() {
+            format!("Request to {name} timed out. The API server is not responding.")
+        } else if error.is_builder() {
+            format!("Failed to build {name} API request: {error}. This may be a configuration error.")
+        } else {
+            format!("{name} network error: {error}")
+        };
+        anyhow::anyhow!(message)
     }
 
-    pub fn all() -> &'static [Self] {
-        &[
-            TranscriptionProvider::OpenAI,
-            TranscriptionProvider::Deepgram,
-            TranscriptionProvider::DeepInfra,
-            TranscriptionProvider::Groq,
-        ]
+    /// Transcribes an audio file.
+    ///
+    /// The default implementation reads the file and runs the shared HTTP flow
+    /// (`build_request` → send → `map_http_error` / `parse_response`). Providers that do not
+    /// follow a single request/response exchange (e.g. local inference) override this.
+    ///
+    /// # Errors
+    /// - If the audio file cannot be read
+    /// - If the request fails or the response cannot be parsed
+    async fn transcribe(
+        &self,
+        config: &TranscriptionConfig,
+        audio_path: &Path,
+    ) -> anyhow::Result<TranscriptionResponse> {
+        super::api::send(self, config, audio_path).await
     }
 }
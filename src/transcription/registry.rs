@@ -0,0 +1,125 @@
+//! Registry of available transcription providers.
+//!
+//! Collects the built-in providers plus any custom providers from the config file, and is the
+//! single place that `ostt auth`, `ostt list-models` and `transcribe` look up providers and
+//! models from. Adding a provider means implementing `Provider` and registering it here.
+//! Models discovered from the providers' model lists can be added next to the built-in ones.
+
+use std::sync::Arc;
+
+use super::api::{assemblyai, custom, deepgram, deepinfra, groq, openai};
+use super::model::TranscriptionModel;
+use super::provider::Provider;
+use crate::config::file::ProvidersConfig;
+
+/// Ordered collection of transcription providers, keyed by provider ID.
+#[derive(Clone, Default)]
+pub struct ProviderRegistry {
+    providers: Vec<Arc<dyn Provider>>,
+    /// Models found in the providers' model lists that aren't built in
+    discovered: Vec<TranscriptionModel>,
+}
+
+impl ProviderRegistry {
+    /// Creates an empty registry
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Creates a registry with all built-in providers
+    pub fn builtin() -> Self {
+        let mut registry = Self::new();
+        registry.register(Arc::new(openai::OpenAiProvider));
+        registry.register(Arc::new(deepgram::DeepgramProvider));
+        registry.register(Arc::new(deepinfra::DeepInfraProvider));
+        registry.register(Arc::new(groq::GroqProvider));
+        registry.register(Arc::new(assemblyai::AssemblyAiProvider));
+        #[cfg(feature = "local-whisper")]
+        registry.register(Arc::new(super::api::local::LocalProvider));
+        registry
+    }
+
+    /// Creates a registry with the built-in providers and the custom providers from the config
+    pub fn from_config(providers: &ProvidersConfig) -> Self {
+        let mut registry = Self::builtin();
+        for (name, settings) in &providers.custom {
+            registry.register(Arc::new(custom::CustomProvider::new(name, settings.clone())));
+        }
+        registry
+    }
+
+    /// Registers a provider.
+    ///
+    /// Providers whose ID or model IDs collide with an already registered provider are skipped,
+    /// since their credentials and model selection would be ambiguous. Returns whether the
+    /// provider was added.
+    pub fn register(&mut self, provider: Arc<dyn Provider>) -> bool {
+        let collides = self.provider(provider.id()).is_some()
+            || provider
+                .models()
+                .iter()
+                .any(|model| self.find_model(model.id()).is_some());
+
+        if collides {
+            tracing::warn!(
+                "Ignoring provider '{}': its ID or a model ID collides with an existing provider",
+                provider.id()
+            );
+            return false;
+        }
+
+        self.providers.push(provider);
+        true
+    }
+
+    /// Adds models discovered from the providers' model lists.
+    ///
+    /// Models of unregistered providers and models whose ID is already taken are skipped.
+    pub fn add_discovered(&mut self, models: Vec<TranscriptionModel>) {
+        for model in models {
+            if self.provider_for(&model).is_none() || self.find_model(model.id()).is_some() {
+                tracing::debug!("Ignoring discovered model '{}'", model.id());
+                continue;
+            }
+            self.discovered.push(model);
+        }
+    }
+
+    /// Returns the models of a provider: its built-in models followed by discovered ones
+    pub fn provider_models(&self, provider: &dyn Provider) -> Vec<TranscriptionModel> {
+        let discovered = self
+            .discovered
+            .iter()
+            .filter(|model| model.provider_id() == provider.id())
+            .cloned();
+        provider.models().into_iter().chain(discovered).collect()
+    }
+
+    /// Returns all registered providers in registration order
+    pub fn providers(&self) -> impl Iterator<Item = &Arc<dyn Provider>> {
+        self.providers.iter()
+    }
+
+    /// Returns the provider with the given ID
+    pub fn provider(&self, id: &str) -> Option<&Arc<dyn Provider>> {
+        self.providers.iter().find(|p| p.id() == id)
+    }
+
+    /// Returns the provider serving the given model
+    pub fn provider_for(&self, model: &TranscriptionModel) -> Option<&Arc<dyn Provider>> {
+        self.provider(model.provider_id())
+    }
+
+    /// Returns all models of all registered providers, including discovered ones
+    pub fn models(&self) -> Vec<TranscriptionModel> {
+        self.providers
+            .iter()
+            .flat_map(|p| self.provider_models(p.as_ref()))
+            .collect()
+    }
+
+    /// Finds a model by its ID
+    pub fn find_model(&self, id: &str) -> Option<TranscriptionModel> {
+        self.models().into_iter().find(|m| m.id() == id)
+    }
+}
@@ -0,0 +1,158 @@
+//! Structured transcription results.
+//!
+//! Providers return more than plain text: segment and word timings, confidence scores and
+//! the detected language. `TranscriptionResponse` carries whatever a provider supplies in a
+//! provider-independent shape, so it can be persisted in the history and used by features
+//! such as subtitles or low-confidence highlighting. Fields a provider doesn't support stay
+//! empty.
+
+use serde::{Deserialize, Serialize};
+
+/// Result of a transcription (unified across providers).
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct TranscriptionResponse {
+    /// The transcribed text
+    pub text: String,
+    /// Detected or requested language (ISO 639-1 code or provider-specific name)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub language: Option<String>,
+    /// Overall confidence between 0 and 1
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub confidence: Option<f32>,
+    /// Duration of the transcribed audio in seconds
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub duration: Option<f64>,
+    /// Segments (sentences or utterances) with timings
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub segments: Vec<Segment>,
+    /// Individual words with timings
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub words: Vec<Word>,
+}
+
+/// A timed segment of the transcript.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct Segment {
+    /// Start time in seconds
+    pub start: f64,
+    /// End time in seconds
+    pub end: f64,
+    /// Segment text
+    pub text: String,
+    /// Confidence between 0 and 1
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub confidence: Option<f32>,
+    /// Speaker number (0-based) when diarization is enabled
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub speaker: Option<u32>,
+}
+
+/// A single timed word of the transcript.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct Word {
+    /// The word as transcribed
+    pub word: String,
+    /// Start time in seconds
+    pub start: f64,
+    /// End time in seconds
+    pub end: f64,
+    /// Confidence between 0 and 1
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub confidence: Option<f32>,
+    /// Speaker number (0-based) when diarization is enabled
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub speaker: Option<u32>,
+}
+
+impl TranscriptionResponse {
+    /// Creates a response carrying only text
+    pub fn from_text(text: impl Into<String>) -> Self {
+        Self {
+            text: text.into(),
+            ..Self::default()
+        }
+    }
+
+    /// Returns whether the response carries anything beyond the plain text
+    pub fn has_details(&self) -> bool {
+        self.language.is_some()
+            || self.confidence.is_some()
+            || self.duration.is_some()
+            || !self.segments.is_empty()
+            || !self.words.is_empty()
+    }
+
+    /// Formats the words into speaker-labelled turns, one per line.
+    ///
+    /// Consecutive words of the same speaker form a turn, prefixed with `label` in which
+    /// `{speaker}` is replaced by the 1-based speaker number. Returns `None` when the words
+    /// carry no speaker information.
+    pub fn speaker_turns(&self, label: &str) -> Option<String> {
+        if self.words.iter().all(|w| w.speaker.is_none()) {
+            return None;
+        }
+
+        let mut turns: Vec<(Option<u32>, Vec<&str>)> = Vec::new();
+        for word in &self.words {
+            match turns.last_mut() {
+                Some((speaker, words)) if *speaker == word.speaker => words.push(&word.word),
+                _ => turns.push((word.speaker, vec![&word.word])),
+            }
+        }
+
+        let lines: Vec<String> = turns
+            .into_iter()
+            .map(|(speaker, words)| {
+                let speaker = speaker.map(|s| (s + 1).to_string()).unwrap_or_else(|| "?".to_string());
+                format!("{}{}", label.replace("{speaker}", &speaker), words.join(" "))
+            })
+            .collect();
+        Some(lines.join("\n"))
+    }
+
+    /// Fills in the overall confidence from the segments or words if the provider gave none.
+    pub fn with_derived_confidence(mut self) -> Self {
+        if self.confidence.is_none() {
+            self.confidence = average(self.segments.iter().filter_map(|s| s.confidence))
+                .or_else(|| average(self.words.iter().filter_map(|w| w.confidence)));
+        }
+        self
+    }
+}
+
+/// Converts a Whisper-style average log probability into a 0-1 confidence
+pub fn confidence_from_logprob(avg_logprob: f64) -> f32 {
+    avg_logprob.exp().clamp(0.0, 1.0) as f32
+}
+
+/// Mean of the given values, `None` when there are none
+fn average(values: impl Iterator<Item = f32>) -> Option<f32> {
+    let (sum, count) = values.fold((0.0, 0), |(sum, count), v| (sum + v, count + 1));
+    (count > 0).then(|| sum / count as f32)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn word(word: &str, speaker: u32) -> Word {
+        Word {
+            word: word.to_string(),
+            speaker: Some(speaker),
+            ..Word::default()
+        }
+    }
+
+    #[test]
+    fn groups_words_into_speaker_turns() {
+        let response = TranscriptionResponse {
+            words: vec![word("Hi", 0), word("there.", 0), word("Hello!", 1), word("Bye.", 0)],
+            ..TranscriptionResponse::default()
+        };
+        assert_eq!(
+            response.speaker_turns("Speaker {speaker}: ").as_deref(),
+            Some("Speaker 1: Hi there.\nSpeaker 2: Hello!\nSpeaker 1: Bye.")
+        );
+        assert_eq!(TranscriptionResponse::from_text("Hi").speaker_turns("S{speaker}: "), None);
+    }
+}